@@ -11,18 +11,25 @@ DRIFT_OUT=../glam/anchor/programs/glam/src/cpi_autogen/drift.rs
 # placeOrders excluded because we do extra authz checks
 # --ixs placeOrders \
 
+# deposit and withdraw excluded because we check delegate limits (instructions/drift.rs)
+# --ixs deposit \
+# --ixs withdraw \
+
+# updateUserCustomMarginRatio and modifyOrder excluded because we check drift limits (instructions/drift.rs)
+# --ixs updateUserCustomMarginRatio \
+# --ixs modifyOrder \
+
+# updateUserDelegate excluded because only the owner can set it with drift limits or delegate scopes
+# (instructions/drift.rs)
+# --ixs updateUserDelegate \
+
 $CPI_GEN $DRIFT_IDL \
     --ixs initializeUserStats \
     --ixs initializeUser \
     --ixs deleteUser \
-    --ixs updateUserCustomMarginRatio \
-    --ixs updateUserDelegate \
     --ixs updateUserMarginTradingEnabled \
-    --ixs deposit \
-    --ixs withdraw \
     --ixs cancelOrders \
     --ixs cancelOrdersByIds \
-    --ixs modifyOrder \
     --output $DRIFT_OUT
 
 #
@@ -34,14 +41,15 @@ JUP_VOTE_IDL=$(realpath ../glam/anchor/deps/jupiter_vote/locked_voter.json)
 JUP_VOTE_OUT=../glam/anchor/programs/glam/src/cpi_autogen/jupiter_vote.rs
 
 # toggleMaxLock excluded because we do custom authz
+# withdraw and withdrawPartialUnstaking excluded because we guard vault balances (instructions/jupiter_vote.rs)
+# --ixs withdraw \
+# --ixs withdrawPartialUnstaking \
 
 $CPI_GEN $JUP_VOTE_IDL --idl-name-alias jupiter_vote \
     --ixs newEscrow \
     --ixs increaseLockedAmount \
     --ixs openPartialUnstaking \
     --ixs mergePartialUnstaking \
-    --ixs withdraw \
-    --ixs withdrawPartialUnstaking \
     --ixs castVote \
     --output $JUP_VOTE_OUT
 
@@ -57,20 +65,28 @@ MET_AMM_OUT=../glam/anchor/programs/glam/src/cpi_autogen/meteora_amm.rs
 MET_DLMM_IDL=$(realpath ../glam/anchor/deps/meteora_dlmm/lb_clmm.json)
 MET_DLMM_OUT=../glam/anchor/programs/glam/src/cpi_autogen/meteora_dlmm.rs
 
-$CPI_GEN $MET_AMM_IDL --idl-name-alias meteora_amm \
-    --ixs swap \
-    --ixs addImbalanceLiquidity \
-    --ixs removeBalanceLiquidity \
-    --output $MET_AMM_OUT
-
-$CPI_GEN $MET_DLMM_IDL --idl-name-alias meteora_dlmm \
-    --ixs initializePosition \
-    --ixs addLiquidityByStrategy \
-    --ixs removeLiquidityByRange \
-    --ixs claimFee \
-    --ixs closePosition \
-    --ixs swap \
-    --output $MET_DLMM_OUT
+# All meteora amm instructions are excluded because swap and liquidity must guard vault balances.
+# They're not wired up, to be written in instructions/meteora_amm.rs with VaultBalanceGuard.
+
+# $CPI_GEN $MET_AMM_IDL --idl-name-alias meteora_amm \
+#     --ixs swap \
+#     --ixs addImbalanceLiquidity \
+#     --ixs removeBalanceLiquidity \
+#     --output $MET_AMM_OUT
+
+# All meteora dlmm instructions are excluded (instructions/meteora_dlmm.rs):
+# - initializePosition, addLiquidityByStrategy and closePosition because we check delegate scopes
+# - removeLiquidityByRange and claimFee because we guard vault balances
+# - swap because we require a risk limits check
+
+# $CPI_GEN $MET_DLMM_IDL --idl-name-alias meteora_dlmm \
+#     --ixs initializePosition \
+#     --ixs addLiquidityByStrategy \
+#     --ixs closePosition \
+#     --ixs removeLiquidityByRange \
+#     --ixs claimFee \
+#     --ixs swap \
+#     --output $MET_DLMM_OUT
 
 #
 # KAMINO
@@ -78,9 +94,11 @@ $CPI_GEN $MET_DLMM_IDL --idl-name-alias meteora_dlmm \
 KAMINO_IDL=$(realpath ../glam/anchor/deps/kamino_lending/kamino_lending.json)
 KAMINO_OUT=../glam/anchor/programs/glam/src/cpi_autogen/kamino_lending.rs
 
+# initObligationFarmsForReserve excluded because we check delegate scopes (instructions/kamino_lending.rs)
+# depositReserveLiquidityAndObligationCollateral excluded because we check delegate scopes
+# (instructions/kamino_lending.rs)
+
 # $CPI_GEN $KAMINO_IDL \
 #     --ixs initUserMetadata \
 #     --ixs initObligation \
-#     --ixs initObligationFarmsForReserve \
-#     --ixs depositReserveLiquidityAndObligationCollateral \
 #     > $KAMINO_OUT
@@ -9,6 +9,20 @@ pub const SEED_VAULT: &str = "vault";
 pub const SEED_METADATA: &str = "metadata";
 #[constant]
 pub const SEED_MINT: &str = "mint";
+#[constant]
+pub const SEED_ATTESTATION: &str = "attestation";
+#[constant]
+pub const SEED_COMPLIANCE_LIST: &str = "compliance-list";
+#[constant]
+pub const SEED_COMPLIANCE_MEMBER: &str = "compliance-member";
+#[constant]
+pub const SEED_WSOL_UNWRAP: &str = "wsol-unwrap";
+#[constant]
+pub const SEED_PENDING_STATE_UPDATE: &str = "pending-state-update";
+#[constant]
+pub const SEED_DELEGATE_USAGE: &str = "delegate-usage";
+#[constant]
+pub const SEED_CPI_POLICY: &str = "cpi-policy";
 
 pub const DEFAULT_DRIFT_USER_NAME: [u8; 32] = [
     b'G', b'L', b'A', b'M', b' ', b'*', b'.', b'+', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -17,12 +31,24 @@ pub const DEFAULT_DRIFT_USER_NAME: [u8; 32] = [
 
 pub const MAX_ASSETS: usize = 100;
 pub const MAX_MINTS: usize = 1;
+pub const IN_KIND_WEIGHTS_TOLERANCE_BPS: u64 = 100; // 1%
+pub const DEAD_SHARES: u64 = 1_000; // minted to the share class itself by the first subscription
+pub const MIN_INITIAL_SHARES: u64 = 1_000_000; // min shares minted by the first subscription
+pub const MAX_MINT_COMPLIANCE_LISTS: usize = 2;
+pub const NOTIONAL_DECIMALS: u8 = 6; // delegate notional limits, in USD
+pub const MAX_COMPLIANCE_LIST_PUBKEYS: usize = 100;
 pub const MAX_SIZE_SYMBOL: usize = 32;
 pub const MAX_SIZE_NAME: usize = 64;
 pub const MAX_SIZE_URI: usize = 128;
+pub const MAX_SIZE_ROLE_NAME: usize = 32;
+pub const MAX_SIZE_DISCRIMINATOR: usize = 8;
+pub const MAX_CPI_POLICY_INSTRUCTIONS: usize = 8;
+pub const MAX_CPI_POLICY_ACCOUNT_SLOTS: usize = 8;
 
 pub const WSOL: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 pub const MSOL: Pubkey = pubkey!("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So");
 pub const SANCTUM_SINGLE_VALIDATOR: Pubkey = pubkey!("SP12tWFxD9oJsVWNavTTBZvMbA6gkAmxtVgxdqvyvhY");
 pub const SANCTUM_MULTI_VALIDATOR: Pubkey = pubkey!("SPMBzsVUuoHA4Jm6KunbsotaahvVikZs1JyTW6iJvbn");
+pub const CIVIC_GATEWAY_PROGRAM: Pubkey = pubkey!("gatem74V238djXdzWnJf94Wo1DcnuGkfijbf3AuBhfs");
+pub const SQUADS_PROGRAM: Pubkey = pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
 pub const POOL_MINT_OFFSET: usize = 162; // Offset of pool_mint in the StakePool struct
@@ -55,68 +55,6 @@ pub struct DriftInitializeUserStats<'info> {
     pub system_program: Program<'info, System>,
 }
 #[derive(Accounts)]
-pub struct DriftDeposit<'info> {
-    pub glam_state: Box<Account<'info, StateAccount>>,
-    #[account(
-        mut,
-        seeds = [crate::constants::SEED_VAULT.as_bytes(),
-        glam_state.key().as_ref()],
-        bump
-    )]
-    pub glam_vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub glam_signer: Signer<'info>,
-    pub cpi_program: Program<'info, Drift>,
-    /// CHECK: should be validated by target program
-    pub state: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub user: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub user_stats: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub spot_market_vault: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub user_token_account: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    pub token_program: AccountInfo<'info>,
-}
-#[derive(Accounts)]
-pub struct DriftWithdraw<'info> {
-    pub glam_state: Box<Account<'info, StateAccount>>,
-    #[account(
-        mut,
-        seeds = [crate::constants::SEED_VAULT.as_bytes(),
-        glam_state.key().as_ref()],
-        bump
-    )]
-    pub glam_vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub glam_signer: Signer<'info>,
-    pub cpi_program: Program<'info, Drift>,
-    /// CHECK: should be validated by target program
-    pub state: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub user: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub user_stats: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub spot_market_vault: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    pub drift_signer: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub user_token_account: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    pub token_program: AccountInfo<'info>,
-}
-#[derive(Accounts)]
 pub struct DriftCancelOrders<'info> {
     pub glam_state: Box<Account<'info, StateAccount>>,
     #[account(
@@ -135,24 +73,6 @@ pub struct DriftCancelOrders<'info> {
     pub user: AccountInfo<'info>,
 }
 #[derive(Accounts)]
-pub struct DriftModifyOrder<'info> {
-    pub glam_state: Box<Account<'info, StateAccount>>,
-    #[account(
-        seeds = [crate::constants::SEED_VAULT.as_bytes(),
-        glam_state.key().as_ref()],
-        bump
-    )]
-    pub glam_vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub glam_signer: Signer<'info>,
-    pub cpi_program: Program<'info, Drift>,
-    /// CHECK: should be validated by target program
-    pub state: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub user: AccountInfo<'info>,
-}
-#[derive(Accounts)]
 pub struct DriftUpdateUser<'info> {
     pub glam_state: Box<Account<'info, StateAccount>>,
     #[account(
@@ -251,83 +171,6 @@ pub fn drift_initialize_user_stats(
         ),
     )
 }
-#[access_control(
-    acl::check_access(
-        &ctx.accounts.glam_state,
-        &ctx.accounts.glam_signer.key,
-        Permission::DriftDeposit
-    )
-)]
-#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
-#[glam_macros::glam_vault_signer_seeds]
-pub fn drift_deposit<'c: 'info, 'info>(
-    ctx: Context<'_, '_, 'c, 'info, DriftDeposit<'info>>,
-    market_index: u16,
-    amount: u64,
-    reduce_only: bool,
-) -> Result<()> {
-    drift::cpi::deposit(
-        CpiContext::new_with_signer(
-                ctx.accounts.cpi_program.to_account_info(),
-                drift::cpi::accounts::Deposit {
-                    state: ctx.accounts.state.to_account_info(),
-                    user: ctx.accounts.user.to_account_info(),
-                    user_stats: ctx.accounts.user_stats.to_account_info(),
-                    authority: ctx.accounts.glam_vault.to_account_info(),
-                    spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
-                    user_token_account: ctx
-                        .accounts
-                        .user_token_account
-                        .to_account_info(),
-                    token_program: ctx.accounts.token_program.to_account_info(),
-                },
-                glam_vault_signer_seeds,
-            )
-            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
-        market_index,
-        amount,
-        reduce_only,
-    )
-}
-#[access_control(
-    acl::check_access(
-        &ctx.accounts.glam_state,
-        &ctx.accounts.glam_signer.key,
-        Permission::DriftWithdraw
-    )
-)]
-#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
-#[glam_macros::glam_vault_signer_seeds]
-pub fn drift_withdraw<'c: 'info, 'info>(
-    ctx: Context<'_, '_, 'c, 'info, DriftWithdraw<'info>>,
-    market_index: u16,
-    amount: u64,
-    reduce_only: bool,
-) -> Result<()> {
-    drift::cpi::withdraw(
-        CpiContext::new_with_signer(
-                ctx.accounts.cpi_program.to_account_info(),
-                drift::cpi::accounts::Withdraw {
-                    state: ctx.accounts.state.to_account_info(),
-                    user: ctx.accounts.user.to_account_info(),
-                    user_stats: ctx.accounts.user_stats.to_account_info(),
-                    authority: ctx.accounts.glam_vault.to_account_info(),
-                    spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
-                    drift_signer: ctx.accounts.drift_signer.to_account_info(),
-                    user_token_account: ctx
-                        .accounts
-                        .user_token_account
-                        .to_account_info(),
-                    token_program: ctx.accounts.token_program.to_account_info(),
-                },
-                glam_vault_signer_seeds,
-            )
-            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
-        market_index,
-        amount,
-        reduce_only,
-    )
-}
 #[access_control(
     acl::check_access(
         &ctx.accounts.glam_state,
@@ -386,61 +229,6 @@ pub fn drift_cancel_orders_by_ids<'c: 'info, 'info>(
         order_ids,
     )
 }
-#[access_control(
-    acl::check_access(
-        &ctx.accounts.glam_state,
-        &ctx.accounts.glam_signer.key,
-        Permission::DriftModifyOrders
-    )
-)]
-#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
-#[glam_macros::glam_vault_signer_seeds]
-pub fn drift_modify_order(
-    ctx: Context<DriftModifyOrder>,
-    order_id: Option<u32>,
-    modify_order_params: ModifyOrderParams,
-) -> Result<()> {
-    drift::cpi::modify_order(
-        CpiContext::new_with_signer(
-            ctx.accounts.cpi_program.to_account_info(),
-            drift::cpi::accounts::ModifyOrder {
-                state: ctx.accounts.state.to_account_info(),
-                user: ctx.accounts.user.to_account_info(),
-                authority: ctx.accounts.glam_vault.to_account_info(),
-            },
-            glam_vault_signer_seeds,
-        ),
-        order_id,
-        modify_order_params,
-    )
-}
-#[access_control(
-    acl::check_access(
-        &ctx.accounts.glam_state,
-        &ctx.accounts.glam_signer.key,
-        Permission::DriftUpdateUser
-    )
-)]
-#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
-#[glam_macros::glam_vault_signer_seeds]
-pub fn drift_update_user_custom_margin_ratio(
-    ctx: Context<DriftUpdateUser>,
-    sub_account_id: u16,
-    margin_ratio: u32,
-) -> Result<()> {
-    drift::cpi::update_user_custom_margin_ratio(
-        CpiContext::new_with_signer(
-            ctx.accounts.cpi_program.to_account_info(),
-            drift::cpi::accounts::UpdateUserCustomMarginRatio {
-                user: ctx.accounts.user.to_account_info(),
-                authority: ctx.accounts.glam_vault.to_account_info(),
-            },
-            glam_vault_signer_seeds,
-        ),
-        sub_account_id,
-        margin_ratio,
-    )
-}
 #[access_control(
     acl::check_access(
         &ctx.accounts.glam_state,
@@ -468,33 +256,6 @@ pub fn drift_update_user_margin_trading_enabled(
         margin_trading_enabled,
     )
 }
-#[access_control(
-    acl::check_access(
-        &ctx.accounts.glam_state,
-        &ctx.accounts.glam_signer.key,
-        Permission::DriftUpdateUser
-    )
-)]
-#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
-#[glam_macros::glam_vault_signer_seeds]
-pub fn drift_update_user_delegate(
-    ctx: Context<DriftUpdateUser>,
-    sub_account_id: u16,
-    delegate: Pubkey,
-) -> Result<()> {
-    drift::cpi::update_user_delegate(
-        CpiContext::new_with_signer(
-            ctx.accounts.cpi_program.to_account_info(),
-            drift::cpi::accounts::UpdateUserDelegate {
-                user: ctx.accounts.user.to_account_info(),
-                authority: ctx.accounts.glam_vault.to_account_info(),
-            },
-            glam_vault_signer_seeds,
-        ),
-        sub_account_id,
-        delegate,
-    )
-}
 #[access_control(
     acl::check_access(
         &ctx.accounts.glam_state,
@@ -56,34 +56,6 @@ pub struct JupiterVoteIncreaseLockedAmount<'info> {
     pub token_program: AccountInfo<'info>,
 }
 #[derive(Accounts)]
-pub struct JupiterVoteWithdraw<'info> {
-    pub glam_state: Box<Account<'info, StateAccount>>,
-    #[account(
-        mut,
-        seeds = [crate::constants::SEED_VAULT.as_bytes(),
-        glam_state.key().as_ref()],
-        bump
-    )]
-    pub glam_vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub glam_signer: Signer<'info>,
-    pub cpi_program: Program<'info, JupiterVote>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub locker: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub escrow: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub escrow_tokens: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub destination_tokens: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    pub token_program: AccountInfo<'info>,
-}
-#[derive(Accounts)]
 pub struct JupiterVoteCastVote<'info> {
     pub glam_state: Box<Account<'info, StateAccount>>,
     #[account(
@@ -154,37 +126,6 @@ pub struct JupiterVoteMergePartialUnstaking<'info> {
     #[account(mut)]
     pub partial_unstake: AccountInfo<'info>,
 }
-#[derive(Accounts)]
-pub struct JupiterVoteWithdrawPartialUnstaking<'info> {
-    pub glam_state: Box<Account<'info, StateAccount>>,
-    #[account(
-        mut,
-        seeds = [crate::constants::SEED_VAULT.as_bytes(),
-        glam_state.key().as_ref()],
-        bump
-    )]
-    pub glam_vault: SystemAccount<'info>,
-    #[account(mut)]
-    pub glam_signer: Signer<'info>,
-    pub cpi_program: Program<'info, JupiterVote>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub locker: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub escrow: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub partial_unstake: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub escrow_tokens: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    #[account(mut)]
-    pub destination_tokens: AccountInfo<'info>,
-    /// CHECK: should be validated by target program
-    pub token_program: AccountInfo<'info>,
-}
 #[access_control(
     acl::check_access(
         &ctx.accounts.glam_state,
@@ -242,34 +183,6 @@ pub fn jupiter_vote_increase_locked_amount(
         amount,
     )
 }
-#[access_control(
-    acl::check_access(
-        &ctx.accounts.glam_state,
-        &ctx.accounts.glam_signer.key,
-        Permission::UnstakeJup
-    )
-)]
-#[access_control(
-    acl::check_integration(&ctx.accounts.glam_state, Integration::JupiterVote)
-)]
-#[glam_macros::glam_vault_signer_seeds]
-pub fn jupiter_vote_withdraw(ctx: Context<JupiterVoteWithdraw>) -> Result<()> {
-    jupiter_vote::cpi::withdraw(
-        CpiContext::new_with_signer(
-            ctx.accounts.cpi_program.to_account_info(),
-            jupiter_vote::cpi::accounts::Withdraw {
-                locker: ctx.accounts.locker.to_account_info(),
-                escrow: ctx.accounts.escrow.to_account_info(),
-                escrow_owner: ctx.accounts.glam_vault.to_account_info(),
-                escrow_tokens: ctx.accounts.escrow_tokens.to_account_info(),
-                destination_tokens: ctx.accounts.destination_tokens.to_account_info(),
-                payer: ctx.accounts.glam_vault.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-            },
-            glam_vault_signer_seeds,
-        ),
-    )
-}
 #[access_control(
     acl::check_access(
         &ctx.accounts.glam_state,
@@ -361,34 +274,3 @@ pub fn jupiter_vote_merge_partial_unstaking(
         ),
     )
 }
-#[access_control(
-    acl::check_access(
-        &ctx.accounts.glam_state,
-        &ctx.accounts.glam_signer.key,
-        Permission::UnstakeJup
-    )
-)]
-#[access_control(
-    acl::check_integration(&ctx.accounts.glam_state, Integration::JupiterVote)
-)]
-#[glam_macros::glam_vault_signer_seeds]
-pub fn jupiter_vote_withdraw_partial_unstaking(
-    ctx: Context<JupiterVoteWithdrawPartialUnstaking>,
-) -> Result<()> {
-    jupiter_vote::cpi::withdraw_partial_unstaking(
-        CpiContext::new_with_signer(
-            ctx.accounts.cpi_program.to_account_info(),
-            jupiter_vote::cpi::accounts::WithdrawPartialUnstaking {
-                locker: ctx.accounts.locker.to_account_info(),
-                escrow: ctx.accounts.escrow.to_account_info(),
-                partial_unstake: ctx.accounts.partial_unstake.to_account_info(),
-                owner: ctx.accounts.glam_vault.to_account_info(),
-                escrow_tokens: ctx.accounts.escrow_tokens.to_account_info(),
-                destination_tokens: ctx.accounts.destination_tokens.to_account_info(),
-                payer: ctx.accounts.glam_vault.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-            },
-            glam_vault_signer_seeds,
-        ),
-    )
-}
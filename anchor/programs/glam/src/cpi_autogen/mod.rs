@@ -2,10 +2,8 @@ pub mod drift;
 pub mod jupiter_gov;
 pub mod jupiter_vote;
 pub mod kamino_lending;
-pub mod meteora_dlmm;
 
 pub use drift::*;
 pub use jupiter_gov::*;
 pub use jupiter_vote::*;
 pub use kamino_lending::*;
-pub use meteora_dlmm::*;
@@ -15,6 +15,30 @@ pub enum GlamError {
     #[msg("Invalid signer ata")]
     InvalidSignerAccount,
 
+    #[msg("No pending ownership transfer")]
+    NoPendingOwner,
+
+    #[msg("State update is timelocked, it must be queued")]
+    TimelockActive,
+
+    #[msg("Timelock has not expired")]
+    TimelockNotExpired,
+
+    #[msg("No timelock set, state updates apply immediately")]
+    NoTimelock,
+
+    #[msg("Invalid or missing delegate usage account")]
+    InvalidDelegateUsage,
+
+    #[msg("Delegate notional limit exceeded")]
+    DelegateNotionalLimitExceeded,
+
+    #[msg("Asset or market out of the delegate's scope")]
+    OutOfDelegateScope,
+
+    #[msg("Delegate permissions are paused")]
+    DelegatesPaused,
+
     // State & mint errors (43000-)
     #[msg("Invalid account type")]
     InvalidAccountType = 43000,
@@ -58,6 +82,42 @@ pub enum GlamError {
     #[msg("Share class mint supply not zero")]
     ShareClassNotEmpty,
 
+    #[msg("Invalid launch price")]
+    InvalidLaunchPrice,
+
+    #[msg("Invalid KYC gateway program or gatekeeper network")]
+    InvalidKycGateway,
+
+    #[msg("Too many compliance lists: max 2 per share class")]
+    InvalidComplianceListsLen,
+
+    #[msg("Invalid compliance list")]
+    InvalidComplianceList,
+
+    #[msg("Compliance list full: max 100 pubkeys, use a merkle root instead")]
+    ComplianceListFull,
+
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+
+    #[msg("Invalid role: name too long (max 32 chars) or undefined")]
+    InvalidRole,
+
+    #[msg("Invalid risk limits: max 10000 bps")]
+    InvalidRiskLimits,
+
+    #[msg("Invalid drift limits: min leverage 1x, max margin ratio 10000")]
+    InvalidDriftLimits,
+
+    #[msg("Merkle roots are only supported by allowlists")]
+    BlocklistMerkleRoot,
+
+    #[msg("Base asset can't change once a share class has a launch price")]
+    BaseAssetLocked,
+
+    #[msg("Compliance list is referenced by share classes")]
+    ComplianceListInUse,
+
     // Vault errors (44000-)
     #[msg("Withdraw denied. Only vaults allow withdraws (funds and mints don't)")]
     WithdrawDenied = 44000,
@@ -74,6 +134,30 @@ pub enum GlamError {
     #[msg("Invalid vote side")]
     InvalidVoteSide,
 
+    #[msg("Invalid CPI policy: too many instructions or account slots, or program not allowed")]
+    InvalidCpiPolicy,
+
+    #[msg("CPI not allowed by the policy")]
+    CpiPolicyViolation,
+
+    #[msg("Vault balance violation: value left the vault's control")]
+    VaultBalanceViolation,
+
+    #[msg("Risk limits must be checked later in the transaction")]
+    RiskLimitsCheckRequired,
+
+    #[msg("Risk limit exceeded")]
+    RiskLimitExceeded,
+
+    #[msg("Invalid drift user: not a drift user of the vault")]
+    InvalidDriftUser,
+
+    #[msg("Drift order size above the vault limit")]
+    DriftOrderTooLarge,
+
+    #[msg("Drift custom margin ratio below the vault minimum")]
+    DriftMarginRatioTooLow,
+
     // Subscription & redemption errors (45000-)
     #[msg("Invalid asset price")]
     InvalidAssetPrice = 45000,
@@ -90,6 +174,42 @@ pub enum GlamError {
     #[msg("Price is too old")]
     PriceTooOld,
 
+    #[msg("In-kind subscription doesn't match the portfolio weights")]
+    InvalidBasketWeights,
+
+    #[msg("Asset not allowed to redeem")]
+    InvalidAssetRedeem,
+
+    #[msg("Redemption failed: insufficient liquidity in the output assets")]
+    InsufficientLiquidity,
+
+    #[msg("Native SOL is only supported for wSOL subscriptions and redemptions")]
+    InvalidNativeSol,
+
+    #[msg("Math overflow in NAV computation")]
+    MathOverflow,
+
+    #[msg("First subscription is below the minimum amount of shares")]
+    InitialSubscriptionTooSmall,
+
+    #[msg("Invalid dead shares account")]
+    InvalidDeadSharesAccount,
+
+    #[msg("Subscription would mint fewer shares than the minimum")]
+    InsufficientSharesOut,
+
+    #[msg("Redemption would pay out less than the minimum amount of an asset")]
+    InsufficientAssetsOut,
+
+    #[msg("Beneficiary must sign subscriptions to share classes with a lock-up")]
+    BeneficiarySignatureRequired,
+
+    #[msg("Invalid stake account: delegated stake above its lamports")]
+    InvalidStakeAccount,
+
+    #[msg("Subscriptions paused")]
+    SubscriptionsPaused,
+
     // Transfer hook errors (46000-)
     #[msg("Policy violation: transfers disabled")]
     TransfersDisabled = 46000,
@@ -99,4 +219,19 @@ pub enum GlamError {
 
     #[msg("Policy violation: lock-up period")]
     LockUp,
+
+    #[msg("Policy violation: invalid transfer authority")]
+    InvalidTransferAuthority,
+
+    #[msg("Policy violation: account not allowed")]
+    AccountNotAllowed,
+
+    #[msg("Policy violation: KYC gateway token required")]
+    KycRequired,
+
+    #[msg("Policy violation: invalid KYC gateway token")]
+    InvalidKycToken,
+
+    #[msg("Policy violation: KYC gateway token expired")]
+    KycExpired,
 }
@@ -0,0 +1,287 @@
+use anchor_lang::prelude::*;
+use solana_program::keccak;
+
+use crate::{constants::*, error::GlamError, ID};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq)]
+pub enum ComplianceListType {
+    Allowlist,
+    Blocklist,
+}
+
+/// Owner-managed list of pubkeys (e.g. a sanctions list), shared by all the
+/// share classes referencing it.
+///
+/// Small sets are stored in `pubkeys`. Large allowlists can be committed to with a
+/// merkle root, membership is then proven once per wallet with
+/// `prove_compliance_membership` and recorded in a ComplianceMemberAccount.
+///
+/// Blocklists can't use a merkle root: a blocked wallet would pass unless it
+/// proved its own membership, and all of them would pass after a root update.
+///
+/// A list can't be closed while share classes reference it, so the transfer hook
+/// and subscriptions never see it missing or re-created with other contents.
+#[account]
+pub struct ComplianceListAccount {
+    pub owner: Pubkey,
+    pub list_type: ComplianceListType,
+    pub merkle_root: [u8; 32], // all zeros if not used
+    pub share_classes: u32, // number of share classes referencing the list
+    pub pubkeys: Vec<Pubkey>,
+}
+impl ComplianceListAccount {
+    pub const INIT_SIZE: usize = 32 + 1 + 32 + 4 + 4 + 32 * MAX_COMPLIANCE_LIST_PUBKEYS;
+
+    pub fn has_merkle_root(&self) -> bool {
+        self.merkle_root != [0; 32]
+    }
+
+    /// Loads and validates a compliance list account.
+    pub fn load(list_info: &AccountInfo, list_key: &Pubkey) -> Result<Self> {
+        require!(
+            list_info.key == list_key && list_info.owner == &ID,
+            GlamError::InvalidComplianceList
+        );
+        let data = list_info.try_borrow_data()?;
+        Self::try_deserialize(&mut &data[..])
+            .map_err(|_| GlamError::InvalidComplianceList.into())
+    }
+
+    /// Adds (`referenced`) or removes a share class reference to each of the lists
+    /// `list_keys`, passed in the same order as writable `list_infos`.
+    pub fn update_references(
+        list_keys: &[Pubkey],
+        list_infos: &[AccountInfo],
+        referenced: bool,
+    ) -> Result<()> {
+        require!(list_infos.len() >= list_keys.len(), GlamError::InvalidComplianceList);
+        for (list_key, list_info) in list_keys.iter().zip(list_infos.iter()) {
+            require!(list_info.is_writable, GlamError::InvalidComplianceList);
+            let mut list = Self::load(list_info, list_key)?;
+            list.share_classes = if referenced {
+                list.share_classes.checked_add(1)
+            } else {
+                list.share_classes.checked_sub(1)
+            }
+            .ok_or(GlamError::InvalidComplianceList)?;
+            let mut data = list_info.try_borrow_mut_data()?;
+            list.try_serialize(&mut &mut data[..])?;
+        }
+        Ok(())
+    }
+
+    /// Checks if `wallet` is in the list, either stored in `pubkeys` or
+    /// proven against the current merkle root (`member_record`).
+    pub fn contains(
+        &self,
+        list_key: &Pubkey,
+        member_record: &AccountInfo,
+        wallet: &Pubkey,
+    ) -> bool {
+        if self.pubkeys.contains(wallet) {
+            return true;
+        }
+        // Only allowlists can be proven against a merkle root
+        if self.list_type != ComplianceListType::Allowlist
+            || !self.has_merkle_root()
+            || member_record.owner != &ID
+        {
+            return false;
+        }
+        let data = match member_record.try_borrow_data() {
+            Ok(data) => data,
+            Err(_) => return false,
+        };
+        match ComplianceMemberAccount::try_deserialize(&mut &data[..]) {
+            // Records proven against an older root are stale
+            Ok(record) => {
+                record.list == *list_key
+                    && record.member == *wallet
+                    && record.merkle_root == self.merkle_root
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Checks that `wallet` is allowed by the list: it must be a member of
+    /// an allowlist, and must not be a member of a blocklist.
+    pub fn check(
+        &self,
+        list_key: &Pubkey,
+        member_record: &AccountInfo,
+        wallet: &Pubkey,
+    ) -> Result<()> {
+        let is_member = self.contains(list_key, member_record, wallet);
+        match self.list_type {
+            ComplianceListType::Allowlist => require!(is_member, GlamError::AccountNotAllowed),
+            ComplianceListType::Blocklist => require!(!is_member, GlamError::AccountNotAllowed),
+        }
+        Ok(())
+    }
+}
+
+/// Record that `member` was proven to be in the merkle tree of `list`.
+#[account]
+pub struct ComplianceMemberAccount {
+    pub list: Pubkey,
+    pub member: Pubkey,
+    pub merkle_root: [u8; 32], // root the membership was proven against
+}
+impl ComplianceMemberAccount {
+    pub const INIT_SIZE: usize = 32 + 32 + 32;
+
+    pub fn pda(list: &Pubkey, member: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                SEED_COMPLIANCE_MEMBER.as_bytes(),
+                list.as_ref(),
+                member.as_ref(),
+            ],
+            &ID,
+        )
+        .0
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(id: [u8; 8])]
+pub struct CreateComplianceList<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ComplianceListAccount::INIT_SIZE,
+        seeds = [SEED_COMPLIANCE_LIST.as_bytes(), owner.key().as_ref(), id.as_ref()],
+        bump
+    )]
+    pub compliance_list: Box<Account<'info, ComplianceListAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_compliance_list_handler(
+    ctx: Context<CreateComplianceList>,
+    _id: [u8; 8],
+    list_type: ComplianceListType,
+) -> Result<()> {
+    let compliance_list = &mut ctx.accounts.compliance_list;
+    compliance_list.owner = ctx.accounts.owner.key();
+    compliance_list.list_type = list_type;
+    compliance_list.merkle_root = [0; 32];
+    compliance_list.share_classes = 0;
+    compliance_list.pubkeys = vec![];
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateComplianceList<'info> {
+    #[account(mut, has_one = owner @ GlamError::NotAuthorized)]
+    pub compliance_list: Box<Account<'info, ComplianceListAccount>>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn update_compliance_list_handler(
+    ctx: Context<UpdateComplianceList>,
+    added: Vec<Pubkey>,
+    removed: Vec<Pubkey>,
+    merkle_root: Option<[u8; 32]>,
+) -> Result<()> {
+    let compliance_list = &mut ctx.accounts.compliance_list;
+
+    compliance_list.pubkeys.retain(|k| !removed.contains(k));
+    for pubkey in added {
+        if !compliance_list.pubkeys.contains(&pubkey) {
+            compliance_list.pubkeys.push(pubkey);
+        }
+    }
+    require!(
+        compliance_list.pubkeys.len() <= MAX_COMPLIANCE_LIST_PUBKEYS,
+        GlamError::ComplianceListFull
+    );
+
+    if let Some(merkle_root) = merkle_root {
+        require!(
+            merkle_root == [0; 32] || compliance_list.list_type == ComplianceListType::Allowlist,
+            GlamError::BlocklistMerkleRoot
+        );
+        compliance_list.merkle_root = merkle_root;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseComplianceList<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ GlamError::NotAuthorized,
+        constraint = compliance_list.share_classes == 0 @ GlamError::ComplianceListInUse
+    )]
+    pub compliance_list: Box<Account<'info, ComplianceListAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn close_compliance_list_handler(_ctx: Context<CloseComplianceList>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct ProveComplianceMembership<'info> {
+    pub compliance_list: Box<Account<'info, ComplianceListAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ComplianceMemberAccount::INIT_SIZE,
+        seeds = [SEED_COMPLIANCE_MEMBER.as_bytes(), compliance_list.key().as_ref(), member.as_ref()],
+        bump
+    )]
+    pub member_record: Box<Account<'info, ComplianceMemberAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn prove_compliance_membership_handler(
+    ctx: Context<ProveComplianceMembership>,
+    member: Pubkey,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let compliance_list = &ctx.accounts.compliance_list;
+    require!(
+        compliance_list.has_merkle_root()
+            && verify_merkle_proof(&proof, &compliance_list.merkle_root, &member),
+        GlamError::InvalidMerkleProof
+    );
+
+    let member_record = &mut ctx.accounts.member_record;
+    member_record.list = compliance_list.key();
+    member_record.member = member;
+    member_record.merkle_root = compliance_list.merkle_root;
+
+    Ok(())
+}
+
+// Leaves are keccak(0x00 || pubkey), nodes are keccak(0x01 || min(a, b) || max(a, b))
+fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], member: &Pubkey) -> bool {
+    let leaf = keccak::hashv(&[&[0x00], member.as_ref()]).to_bytes();
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        keccak::hashv(&[&[0x01], &left, &right]).to_bytes()
+    });
+    computed == *root
+}
@@ -0,0 +1,262 @@
+use anchor_lang::prelude::*;
+use solana_program::{instruction::Instruction, program::invoke_signed};
+
+use crate::{constants::*, error::GlamError, state::*, ID};
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
+pub enum AccountSlotConstraint {
+    Vault,             // the vault, signing the CPI
+    VaultTokenAccount, // a token account owned by the vault
+    Pubkey { val: Pubkey },
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
+pub struct AccountSlot {
+    pub index: u8,
+    pub constraint: AccountSlotConstraint,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
+pub struct InstructionPolicy {
+    pub discriminator: Vec<u8>, // prefix of the instruction data
+    pub account_slots: Vec<AccountSlot>,
+}
+impl InstructionPolicy {
+    pub const MAX_SIZE: usize =
+        4 + MAX_SIZE_DISCRIMINATOR + 4 + MAX_CPI_POLICY_ACCOUNT_SLOTS * (1 + 1 + 32);
+}
+
+/// Owner-approved instructions of a program that delegates with `Permission::ExecuteCpi`
+/// can call through `execute_cpi`, signed by the vault.
+///
+/// The vault can only be passed in the account slots constrained to `Vault`, and only the
+/// vault token accounts in slots constrained to `VaultTokenAccount` can be debited.
+/// Writable token accounts not owned by the vault must be in slots constrained to a `Pubkey`.
+#[account]
+pub struct CpiPolicyAccount {
+    pub glam_state: Pubkey,
+    pub program_id: Pubkey,
+    pub activates_at: i64, // unix timestamp, delayed by the vault timelock
+    pub instructions: Vec<InstructionPolicy>,
+}
+impl CpiPolicyAccount {
+    pub const INIT_SIZE: usize =
+        32 + 32 + 8 + 4 + MAX_CPI_POLICY_INSTRUCTIONS * InstructionPolicy::MAX_SIZE;
+
+    pub fn find_instruction(&self, data: &[u8]) -> Option<&InstructionPolicy> {
+        self.instructions
+            .iter()
+            .find(|ix| data.starts_with(&ix.discriminator))
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct SetCpiPolicy<'info> {
+    #[account(constraint = glam_state.owner == glam_signer.key() @ GlamError::NotAuthorized)]
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [SEED_CPI_POLICY.as_bytes(), glam_state.key().as_ref(), program_id.as_ref()],
+        bump,
+        payer = glam_signer,
+        space = 8 + CpiPolicyAccount::INIT_SIZE
+    )]
+    pub cpi_policy: Box<Account<'info, CpiPolicyAccount>>,
+
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_cpi_policy_handler(
+    ctx: Context<SetCpiPolicy>,
+    program_id: Pubkey,
+    instructions: Vec<InstructionPolicy>,
+) -> Result<()> {
+    require!(program_id != ID, GlamError::InvalidCpiPolicy);
+    require!(
+        instructions.len() <= MAX_CPI_POLICY_INSTRUCTIONS,
+        GlamError::InvalidCpiPolicy
+    );
+    for ix in instructions.iter() {
+        require!(
+            !ix.discriminator.is_empty()
+                && ix.discriminator.len() <= MAX_SIZE_DISCRIMINATOR
+                && ix.account_slots.len() <= MAX_CPI_POLICY_ACCOUNT_SLOTS,
+            GlamError::InvalidCpiPolicy
+        );
+    }
+
+    // Like other sensitive updates, a new policy can only be used once the timelock expires.
+    // The previous policy is replaced right away, so restricting a policy is immediate.
+    let activates_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(ctx.accounts.glam_state.timelock_duration())
+        .ok_or(GlamError::MathOverflow)?;
+
+    let cpi_policy = &mut ctx.accounts.cpi_policy;
+    cpi_policy.glam_state = ctx.accounts.glam_state.key();
+    cpi_policy.program_id = program_id;
+    cpi_policy.activates_at = activates_at;
+    cpi_policy.instructions = instructions;
+
+    msg!("CPI policy for {} active at {}", program_id, activates_at);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct CloseCpiPolicy<'info> {
+    #[account(constraint = glam_state.owner == glam_signer.key() @ GlamError::NotAuthorized)]
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    #[account(
+        mut,
+        close = glam_signer,
+        seeds = [SEED_CPI_POLICY.as_bytes(), glam_state.key().as_ref(), program_id.as_ref()],
+        bump
+    )]
+    pub cpi_policy: Box<Account<'info, CpiPolicyAccount>>,
+
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+}
+
+/// Removing a policy only restricts delegates, so it's immediate even with a timelock.
+pub fn close_cpi_policy_handler(ctx: Context<CloseCpiPolicy>, program_id: Pubkey) -> Result<()> {
+    msg!("CPI policy for {} closed", program_id);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteCpi<'info> {
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    #[account(mut, seeds = [SEED_VAULT.as_bytes(), glam_state.key().as_ref()], bump)]
+    pub glam_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_CPI_POLICY.as_bytes(), glam_state.key().as_ref(), target_program.key().as_ref()],
+        bump
+    )]
+    pub cpi_policy: Box<Account<'info, CpiPolicyAccount>>,
+
+    /// CHECK: the program of the policy, checked by the cpi_policy seeds
+    #[account(executable)]
+    pub target_program: UncheckedAccount<'info>,
+}
+
+/// Calls `target_program` with `data` and the remaining accounts, signed by the vault,
+/// if allowed by the vault's policy for the program.
+#[access_control(acl::check_access(&ctx.accounts.glam_state, &ctx.accounts.glam_signer.key, Permission::ExecuteCpi))]
+#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::ExecuteCpi))]
+#[glam_macros::glam_vault_signer_seeds]
+pub fn execute_cpi_handler<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, ExecuteCpi<'info>>,
+    data: Vec<u8>,
+) -> Result<()> {
+    let cpi_policy = &ctx.accounts.cpi_policy;
+    require!(
+        Clock::get()?.unix_timestamp >= cpi_policy.activates_at,
+        GlamError::TimelockNotExpired
+    );
+
+    let vault_key = ctx.accounts.glam_vault.key();
+    let ix_policy = cpi_policy
+        .find_instruction(&data)
+        .ok_or(GlamError::CpiPolicyViolation)?;
+    check_account_slots(ix_policy, ctx.remaining_accounts, &vault_key)?;
+
+    let accounts: Vec<AccountMeta> = ctx
+        .remaining_accounts
+        .iter()
+        .map(|acc| AccountMeta {
+            pubkey: *acc.key,
+            is_signer: acc.is_signer || *acc.key == vault_key,
+            is_writable: acc.is_writable,
+        })
+        .collect();
+
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.target_program.to_account_info());
+
+    let outflows = vault_token_account_slots(ix_policy, ctx.remaining_accounts);
+    let guard = VaultBalanceGuard::new(&ctx.accounts.glam_vault, ctx.remaining_accounts);
+
+    invoke_signed(
+        &Instruction {
+            program_id: ctx.accounts.target_program.key(),
+            accounts,
+            data,
+        },
+        &account_infos,
+        glam_vault_signer_seeds,
+    )?;
+
+    guard.check(&ctx.accounts.glam_vault, ctx.remaining_accounts, &outflows, 0)
+}
+
+/// Vault token accounts the policy allows the CPI to debit
+fn vault_token_account_slots(
+    ix_policy: &InstructionPolicy,
+    accounts: &[AccountInfo],
+) -> Vec<Pubkey> {
+    ix_policy
+        .account_slots
+        .iter()
+        .filter(|slot| slot.constraint == AccountSlotConstraint::VaultTokenAccount)
+        .filter_map(|slot| accounts.get(slot.index as usize).map(|account| *account.key))
+        .collect()
+}
+
+fn check_account_slots(
+    ix_policy: &InstructionPolicy,
+    accounts: &[AccountInfo],
+    vault: &Pubkey,
+) -> Result<()> {
+    for slot in ix_policy.account_slots.iter() {
+        let account = accounts
+            .get(slot.index as usize)
+            .ok_or(GlamError::CpiPolicyViolation)?;
+        let valid = match &slot.constraint {
+            AccountSlotConstraint::Vault => account.key == vault,
+            AccountSlotConstraint::VaultTokenAccount => is_vault_token_account(account, vault),
+            AccountSlotConstraint::Pubkey { val } => account.key == val,
+        };
+        require!(valid, GlamError::CpiPolicyViolation);
+    }
+
+    for (index, account) in accounts.iter().enumerate() {
+        // The vault signs the CPI, it can't be passed in any other slot
+        if account.key == vault {
+            require!(
+                ix_policy.account_slots.iter().any(|slot| {
+                    slot.index as usize == index && slot.constraint == AccountSlotConstraint::Vault
+                }),
+                GlamError::CpiPolicyViolation
+            );
+        }
+
+        // Value can only leave the vault to token accounts approved by the owner
+        if account.is_writable
+            && is_token_account(account)
+            && !is_vault_token_account(account, vault)
+        {
+            require!(
+                ix_policy.account_slots.iter().any(|slot| {
+                    slot.index as usize == index
+                        && matches!(slot.constraint, AccountSlotConstraint::Pubkey { .. })
+                }),
+                GlamError::CpiPolicyViolation
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::Price;
+
+use crate::constants::*;
+use crate::error::GlamError;
+use crate::state::nav::{self, Rounding};
+use crate::state::*;
+
+const SECONDS_PER_HOUR: i64 = 3600;
+const HOURS_PER_WINDOW: usize = 24;
+
+/// Notional limits of a delegate, and the notional it moved over the last 24h.
+///
+/// Notionals are in USD with `NOTIONAL_DECIMALS`, valued with `AssetMeta` prices.
+/// Usage is recorded in hourly buckets, so the 24h window rolls by the hour.
+#[account]
+pub struct DelegateUsageAccount {
+    pub glam_state: Pubkey,
+    pub delegate: Pubkey,
+    pub max_notional_per_tx: u64,  // 0 = no limit
+    pub max_notional_per_day: u64, // 0 = no limit
+    pub last_hour: i64,            // unix hour of the last recorded usage
+    pub hourly_notional: [u64; 24], // notional per hour, indexed by unix hour % 24
+}
+impl DelegateUsageAccount {
+    pub const INIT_SIZE: usize = 32 + 32 + 8 + 8 + 8 + 8 * HOURS_PER_WINDOW;
+
+    /// Records `notional` moved at `timestamp`, failing if it exceeds the limits.
+    pub fn record(&mut self, notional: u64, timestamp: i64) -> Result<()> {
+        if self.max_notional_per_tx > 0 {
+            require!(
+                notional <= self.max_notional_per_tx,
+                GlamError::DelegateNotionalLimitExceeded
+            );
+        }
+
+        // Clear the buckets of the hours elapsed since the last usage
+        let hour = timestamp / SECONDS_PER_HOUR;
+        let elapsed = hour
+            .saturating_sub(self.last_hour)
+            .clamp(0, HOURS_PER_WINDOW as i64);
+        for h in 1..=elapsed {
+            self.hourly_notional[((self.last_hour + h) as usize) % HOURS_PER_WINDOW] = 0;
+        }
+        self.last_hour = self.last_hour.max(hour);
+
+        let bucket = &mut self.hourly_notional[(hour as usize) % HOURS_PER_WINDOW];
+        *bucket = bucket
+            .checked_add(notional)
+            .ok_or(GlamError::MathOverflow)?;
+
+        if self.max_notional_per_day > 0 {
+            let used = self
+                .hourly_notional
+                .iter()
+                .try_fold(0u64, |sum, &notional| sum.checked_add(notional))
+                .ok_or(GlamError::MathOverflow)?;
+            msg!(
+                "Delegate notional: {} in the last 24h, max {}",
+                used,
+                self.max_notional_per_day
+            );
+            require!(
+                used <= self.max_notional_per_day,
+                GlamError::DelegateNotionalLimitExceeded
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Whether the new limits are looser than the current ones.
+    pub fn relaxes(&self, max_notional_per_tx: u64, max_notional_per_day: u64) -> bool {
+        let relaxes = |current: u64, new: u64| current > 0 && (new == 0 || new > current);
+        relaxes(self.max_notional_per_tx, max_notional_per_tx)
+            || relaxes(self.max_notional_per_day, max_notional_per_day)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct SetDelegateLimits<'info> {
+    #[account(mut, constraint = glam_state.owner == glam_signer.key() @ GlamError::NotAuthorized)]
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [SEED_DELEGATE_USAGE.as_bytes(), glam_state.key().as_ref(), delegate.as_ref()],
+        bump,
+        payer = glam_signer,
+        space = 8 + DelegateUsageAccount::INIT_SIZE
+    )]
+    pub delegate_usage: Box<Account<'info, DelegateUsageAccount>>,
+
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_delegate_limits_handler(
+    ctx: Context<SetDelegateLimits>,
+    delegate: Pubkey,
+    max_notional_per_tx: u64,
+    max_notional_per_day: u64,
+) -> Result<()> {
+    // With a timelock, relaxing the limits must go through queue_state_update
+    require!(
+        ctx.accounts.glam_state.timelock_duration() == 0
+            || !ctx
+                .accounts
+                .delegate_usage
+                .relaxes(max_notional_per_tx, max_notional_per_day),
+        GlamError::TimelockActive
+    );
+
+    let state_key = ctx.accounts.glam_state.key();
+    apply_delegate_limits(
+        &mut ctx.accounts.glam_state,
+        state_key,
+        &mut ctx.accounts.delegate_usage,
+        delegate,
+        max_notional_per_tx,
+        max_notional_per_day,
+    );
+    Ok(())
+}
+
+/// Sets the limits of a delegate in its usage account, and tracks the delegates
+/// with limits in the state.
+pub fn apply_delegate_limits(
+    state: &mut StateAccount,
+    state_key: Pubkey,
+    delegate_usage: &mut DelegateUsageAccount,
+    delegate: Pubkey,
+    max_notional_per_tx: u64,
+    max_notional_per_day: u64,
+) {
+    delegate_usage.glam_state = state_key;
+    delegate_usage.delegate = delegate;
+    delegate_usage.max_notional_per_tx = max_notional_per_tx;
+    delegate_usage.max_notional_per_day = max_notional_per_day;
+
+    // Delegates with limits must provide their usage account
+    let has_limits = max_notional_per_tx > 0 || max_notional_per_day > 0;
+    let limited_delegates = state.get_pubkeys_from_engine_field(EngineFieldName::LimitedDelegates);
+    if has_limits && !limited_delegates.contains(&delegate) {
+        state.add_to_engine_field(EngineFieldName::LimitedDelegates, delegate);
+    } else if !has_limits {
+        state.delete_from_engine_field(EngineFieldName::LimitedDelegates, delegate);
+    }
+}
+
+pub fn has_delegate_limits(state: &StateAccount, signer: &Pubkey) -> bool {
+    state.owner != *signer
+        && state
+            .get_pubkeys_from_engine_field(EngineFieldName::LimitedDelegates)
+            .contains(signer)
+}
+
+/// Checks and records the notional of `amount` of `asset` moved by the signer,
+/// if it's a delegate with limits.
+///
+/// `asset_pricing` is the pricing account of the asset, and `sol_pricing` the
+/// pricing account of SOL, needed for assets priced in SOL (LSTs).
+pub fn check_delegate_limits<'info>(
+    state: &StateAccount,
+    signer: &Pubkey,
+    delegate_usage: Option<&mut Box<Account<'info, DelegateUsageAccount>>>,
+    asset: &Pubkey,
+    amount: u64,
+    asset_pricing: Option<&UncheckedAccount<'info>>,
+    sol_pricing: Option<&UncheckedAccount<'info>>,
+) -> Result<()> {
+    if !has_delegate_limits(state, signer) {
+        return Ok(());
+    }
+    let delegate_usage = delegate_usage.ok_or(GlamError::InvalidDelegateUsage)?;
+
+    let notional = get_notional(asset, amount, asset_pricing, sol_pricing)?;
+    delegate_usage.record(notional, Clock::get()?.unix_timestamp)
+}
+
+/// Notional of `amount` of `asset` in USD, with `NOTIONAL_DECIMALS`, rounded up.
+fn get_notional(
+    asset: &Pubkey,
+    amount: u64,
+    asset_pricing: Option<&UncheckedAccount>,
+    sol_pricing: Option<&UncheckedAccount>,
+) -> Result<u64> {
+    let asset_meta = AssetMeta::get(&asset.to_string())?;
+    let mut price = get_price(asset_meta, asset_pricing)?;
+    if asset_meta.get_price_denom() == PriceDenom::SOL {
+        let price_sol_usd = get_price(AssetMeta::get(&WSOL.to_string())?, sol_pricing)?;
+        price = nav::mul_price(&price, &price_sol_usd, Rounding::Up)?;
+    }
+
+    nav::to_u64(nav::value_of(
+        amount,
+        asset_meta.decimals,
+        &price,
+        -(NOTIONAL_DECIMALS as i32),
+        Rounding::Up,
+    )?)
+}
+
+fn get_price(asset_meta: &AssetMeta, pricing_account: Option<&UncheckedAccount>) -> Result<Price> {
+    let pricing_account = pricing_account.ok_or(GlamError::InvalidPricingOracle)?;
+    require!(
+        pricing_account.key().to_string() == asset_meta.get_pricing_account(),
+        GlamError::InvalidPricingOracle
+    );
+    // Action::Redeem values depegged stable coins at their price instead of failing
+    asset_meta.get_price(pricing_account, Clock::get()?.unix_timestamp, Action::Redeem)
+}
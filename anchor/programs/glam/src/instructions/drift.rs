@@ -1,9 +1,15 @@
+use std::cell::Ref;
+
 use anchor_lang::prelude::*;
 use drift::program::Drift;
-use drift::MarketType;
+use drift::{MarketType, ModifyOrderParams};
 pub use drift::OrderParams;
 
+use crate::constants::SEED_DELEGATE_USAGE;
+use crate::cpi_autogen::drift::DriftUpdateUser;
 use crate::error::GlamError;
+use crate::instructions::delegate_limits::*;
+use crate::instructions::risk_limits::require_risk_limits_check;
 use crate::state::*;
 
 #[derive(Accounts)]
@@ -31,6 +37,7 @@ pub fn drift_place_orders_pre_checks<'c: 'info, 'info>(
     params: &Vec<OrderParams>,
 ) -> Result<()> {
     let state = &ctx.accounts.glam_state;
+    let drift_limits = state.drift_limits();
     for order in params {
         let permission = match order.market_type {
             MarketType::Spot => Permission::DriftSpotMarket,
@@ -72,10 +79,127 @@ pub fn drift_place_orders_pre_checks<'c: 'info, 'info>(
                 );
             }
         }
+
+        // Markets and order types allowed to the delegate
+        if let Some(scope) = acl::get_delegate_scope(state, ctx.accounts.glam_signer.key) {
+            let market_indexes = match order.market_type {
+                MarketType::Spot => &scope.drift_market_indexes_spot,
+                MarketType::Perp => &scope.drift_market_indexes_perp,
+            };
+            require!(
+                acl::in_scope(market_indexes, &(order.market_index as u32)),
+                GlamError::OutOfDelegateScope
+            );
+            require!(
+                acl::in_scope(&scope.drift_order_types, &(order.order_type as u32)),
+                GlamError::OutOfDelegateScope
+            );
+        }
+
+        if let Some(drift_limits) = &drift_limits {
+            if matches!(order.market_type, MarketType::Perp) {
+                check_drift_order_size(drift_limits, order.base_asset_amount)?;
+            }
+        }
+    }
+
+    if let Some(drift_limits) = &drift_limits {
+        check_drift_user_margin_ratio(
+            drift_limits,
+            &ctx.accounts.user,
+            ctx.accounts.glam_vault.key,
+        )?;
+    }
+    Ok(())
+}
+
+// Offsets in the drift `User` account, `drift::User` omits the positions and orders
+const DRIFT_USER_AUTHORITY_OFFSET: usize = 8;
+const DRIFT_USER_ORDERS_OFFSET: usize = 1192;
+const DRIFT_USER_NEXT_ORDER_ID_OFFSET: usize = 4336;
+const DRIFT_USER_MAX_MARGIN_RATIO_OFFSET: usize = 4340;
+const DRIFT_USER_MAX_ORDERS: usize = 32;
+// Offsets in a drift `Order`
+const DRIFT_ORDER_SIZE: usize = 96;
+const DRIFT_ORDER_ID_OFFSET: usize = 76;
+const DRIFT_ORDER_MARKET_TYPE_OFFSET: usize = 84;
+
+fn drift_user_data<'a, 'info>(
+    user: &'a AccountInfo<'info>,
+    vault: &Pubkey,
+) -> Result<Ref<'a, &'info mut [u8]>> {
+    require!(user.owner == &drift::ID, GlamError::InvalidDriftUser);
+    let data = user.try_borrow_data()?;
+    require!(
+        data.len() == drift::User::SIZE && data[..8] == drift::User::DISCRIMINATOR,
+        GlamError::InvalidDriftUser
+    );
+    require!(
+        &data[DRIFT_USER_AUTHORITY_OFFSET..DRIFT_USER_AUTHORITY_OFFSET + 32] == vault.as_ref(),
+        GlamError::InvalidDriftUser
+    );
+    Ok(data)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn check_drift_order_size(drift_limits: &DriftLimits, base_asset_amount: u64) -> Result<()> {
+    if drift_limits.max_order_base_asset_amount > 0 {
+        require!(
+            base_asset_amount <= drift_limits.max_order_base_asset_amount,
+            GlamError::DriftOrderTooLarge
+        );
+    }
+    Ok(())
+}
+
+/// Checks that the custom margin ratio of the drift user bounds its leverage to the vault limits.
+fn check_drift_user_margin_ratio(
+    drift_limits: &DriftLimits,
+    user: &AccountInfo,
+    vault: &Pubkey,
+) -> Result<()> {
+    let min_margin_ratio = drift_limits.min_margin_ratio();
+    if min_margin_ratio == 0 {
+        return Ok(());
     }
+    let data = drift_user_data(user, vault)?;
+    require!(
+        read_u32(&data, DRIFT_USER_MAX_MARGIN_RATIO_OFFSET) >= min_margin_ratio,
+        GlamError::DriftMarginRatioTooLow
+    );
     Ok(())
 }
 
+/// Checks the drift limits before modifying an order of the drift user. Like drift,
+/// no `order_id` means the last order placed.
+fn check_drift_modify_order(
+    drift_limits: &DriftLimits,
+    user: &AccountInfo,
+    vault: &Pubkey,
+    order_id: Option<u32>,
+    base_asset_amount: Option<u64>,
+) -> Result<()> {
+    if let Some(base_asset_amount) = base_asset_amount {
+        let data = drift_user_data(user, vault)?;
+        let order_id = order_id.unwrap_or_else(|| {
+            read_u32(&data, DRIFT_USER_NEXT_ORDER_ID_OFFSET).wrapping_sub(1)
+        });
+        // Only perp orders are capped, unknown orders are rejected by drift
+        let is_spot_order = (0..DRIFT_USER_MAX_ORDERS).any(|i| {
+            let offset = DRIFT_USER_ORDERS_OFFSET + i * DRIFT_ORDER_SIZE;
+            read_u32(&data, offset + DRIFT_ORDER_ID_OFFSET) == order_id
+                && data[offset + DRIFT_ORDER_MARKET_TYPE_OFFSET] == MarketType::Spot as u8
+        });
+        if !is_spot_order {
+            check_drift_order_size(drift_limits, base_asset_amount)?;
+        }
+    }
+    check_drift_user_margin_ratio(drift_limits, user, vault)
+}
+
 #[access_control(
     acl::check_access(
         &ctx.accounts.glam_state,
@@ -105,3 +229,411 @@ pub fn drift_place_orders<'c: 'info, 'info>(
         params,
     )
 }
+
+#[derive(Accounts)]
+pub struct DriftModifyOrder<'info> {
+    pub glam_state: Box<Account<'info, StateAccount>>,
+    #[account(
+        seeds = [crate::constants::SEED_VAULT.as_bytes(),
+        glam_state.key().as_ref()],
+        bump
+    )]
+    pub glam_vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+    pub cpi_program: Program<'info, Drift>,
+    /// CHECK: should be validated by target program
+    pub state: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub user: AccountInfo<'info>,
+}
+
+#[access_control(
+    acl::check_access(
+        &ctx.accounts.glam_state,
+        &ctx.accounts.glam_signer.key,
+        Permission::DriftModifyOrders
+    )
+)]
+#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
+#[glam_macros::glam_vault_signer_seeds]
+pub fn drift_modify_order(
+    ctx: Context<DriftModifyOrder>,
+    order_id: Option<u32>,
+    modify_order_params: ModifyOrderParams,
+) -> Result<()> {
+    if let Some(drift_limits) = ctx.accounts.glam_state.drift_limits() {
+        check_drift_modify_order(
+            &drift_limits,
+            &ctx.accounts.user,
+            ctx.accounts.glam_vault.key,
+            order_id,
+            modify_order_params.base_asset_amount,
+        )?;
+    }
+
+    drift::cpi::modify_order(
+        CpiContext::new_with_signer(
+            ctx.accounts.cpi_program.to_account_info(),
+            drift::cpi::accounts::ModifyOrder {
+                state: ctx.accounts.state.to_account_info(),
+                user: ctx.accounts.user.to_account_info(),
+                authority: ctx.accounts.glam_vault.to_account_info(),
+            },
+            glam_vault_signer_seeds,
+        ),
+        order_id,
+        modify_order_params,
+    )
+}
+
+#[access_control(
+    acl::check_access(
+        &ctx.accounts.glam_state,
+        &ctx.accounts.glam_signer.key,
+        Permission::DriftUpdateUser
+    )
+)]
+#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
+#[glam_macros::glam_vault_signer_seeds]
+pub fn drift_update_user_custom_margin_ratio(
+    ctx: Context<DriftUpdateUser>,
+    sub_account_id: u16,
+    margin_ratio: u32,
+) -> Result<()> {
+    if let Some(drift_limits) = ctx.accounts.glam_state.drift_limits() {
+        require!(
+            margin_ratio >= drift_limits.min_margin_ratio(),
+            GlamError::DriftMarginRatioTooLow
+        );
+    }
+
+    drift::cpi::update_user_custom_margin_ratio(
+        CpiContext::new_with_signer(
+            ctx.accounts.cpi_program.to_account_info(),
+            drift::cpi::accounts::UpdateUserCustomMarginRatio {
+                user: ctx.accounts.user.to_account_info(),
+                authority: ctx.accounts.glam_vault.to_account_info(),
+            },
+            glam_vault_signer_seeds,
+        ),
+        sub_account_id,
+        margin_ratio,
+    )
+}
+
+/// A drift delegate trades the vault's sub account directly, outside of the drift limits and
+/// the delegate scopes, so only the owner can set one when either is configured.
+fn check_drift_delegate_update(state: &StateAccount, signer: &Pubkey) -> Result<()> {
+    if state.drift_limits().is_some() || !state.delegate_scopes().is_empty() {
+        require!(state.owner == *signer, GlamError::NotAuthorized);
+    }
+    Ok(())
+}
+
+#[access_control(
+    acl::check_access(
+        &ctx.accounts.glam_state,
+        &ctx.accounts.glam_signer.key,
+        Permission::DriftUpdateUser
+    )
+)]
+#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
+#[access_control(
+    check_drift_delegate_update(&ctx.accounts.glam_state, ctx.accounts.glam_signer.key)
+)]
+#[glam_macros::glam_vault_signer_seeds]
+pub fn drift_update_user_delegate(
+    ctx: Context<DriftUpdateUser>,
+    sub_account_id: u16,
+    delegate: Pubkey,
+) -> Result<()> {
+    drift::cpi::update_user_delegate(
+        CpiContext::new_with_signer(
+            ctx.accounts.cpi_program.to_account_info(),
+            drift::cpi::accounts::UpdateUserDelegate {
+                user: ctx.accounts.user.to_account_info(),
+                authority: ctx.accounts.glam_vault.to_account_info(),
+            },
+            glam_vault_signer_seeds,
+        ),
+        sub_account_id,
+        delegate,
+    )
+}
+
+#[derive(Accounts)]
+pub struct DriftDeposit<'info> {
+    pub glam_state: Box<Account<'info, StateAccount>>,
+    #[account(
+        mut,
+        seeds = [crate::constants::SEED_VAULT.as_bytes(),
+        glam_state.key().as_ref()],
+        bump
+    )]
+    pub glam_vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+    pub cpi_program: Program<'info, Drift>,
+    /// CHECK: should be validated by target program
+    pub state: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub user: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub user_stats: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub spot_market_vault: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub user_token_account: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    pub token_program: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [SEED_DELEGATE_USAGE.as_bytes(), glam_state.key().as_ref(), glam_signer.key().as_ref()],
+        bump
+    )]
+    pub delegate_usage: Option<Box<Account<'info, DelegateUsageAccount>>>,
+    /// CHECK: checked against the deposited asset's pricing account
+    pub asset_pricing: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against SOL's pricing account
+    pub sol_pricing: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct DriftWithdraw<'info> {
+    pub glam_state: Box<Account<'info, StateAccount>>,
+    #[account(
+        mut,
+        seeds = [crate::constants::SEED_VAULT.as_bytes(),
+        glam_state.key().as_ref()],
+        bump
+    )]
+    pub glam_vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+    pub cpi_program: Program<'info, Drift>,
+    /// CHECK: should be validated by target program
+    pub state: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub user: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub user_stats: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub spot_market_vault: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    pub drift_signer: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    #[account(mut)]
+    pub user_token_account: AccountInfo<'info>,
+    /// CHECK: should be validated by target program
+    pub token_program: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [SEED_DELEGATE_USAGE.as_bytes(), glam_state.key().as_ref(), glam_signer.key().as_ref()],
+        bump
+    )]
+    pub delegate_usage: Option<Box<Account<'info, DelegateUsageAccount>>>,
+    /// CHECK: checked against the withdrawn asset's pricing account
+    pub asset_pricing: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against SOL's pricing account
+    pub sol_pricing: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar, to find the risk limits check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+/// Mint of a token account (token or token 2022), the first field of its data.
+fn token_account_mint(token_account: &AccountInfo) -> Result<Pubkey> {
+    let data = token_account.try_borrow_data()?;
+    require!(data.len() >= 32, GlamError::InvalidAccountType);
+    Ok(Pubkey::try_from(&data[..32]).unwrap())
+}
+
+#[access_control(
+    acl::check_access(
+        &ctx.accounts.glam_state,
+        &ctx.accounts.glam_signer.key,
+        Permission::DriftDeposit
+    )
+)]
+#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
+#[glam_macros::glam_vault_signer_seeds]
+pub fn drift_deposit<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, DriftDeposit<'info>>,
+    market_index: u16,
+    amount: u64,
+    reduce_only: bool,
+) -> Result<()> {
+    if has_delegate_limits(&ctx.accounts.glam_state, ctx.accounts.glam_signer.key) {
+        let asset = token_account_mint(&ctx.accounts.user_token_account)?;
+        check_delegate_limits(
+            &ctx.accounts.glam_state,
+            ctx.accounts.glam_signer.key,
+            ctx.accounts.delegate_usage.as_mut(),
+            &asset,
+            amount,
+            ctx.accounts.asset_pricing.as_ref(),
+            ctx.accounts.sol_pricing.as_ref(),
+        )?;
+    }
+
+    drift::cpi::deposit(
+        CpiContext::new_with_signer(
+            ctx.accounts.cpi_program.to_account_info(),
+            drift::cpi::accounts::Deposit {
+                state: ctx.accounts.state.to_account_info(),
+                user: ctx.accounts.user.to_account_info(),
+                user_stats: ctx.accounts.user_stats.to_account_info(),
+                authority: ctx.accounts.glam_vault.to_account_info(),
+                spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
+                user_token_account: ctx.accounts.user_token_account.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            glam_vault_signer_seeds,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        market_index,
+        amount,
+        reduce_only,
+    )
+}
+
+#[access_control(
+    acl::check_access(
+        &ctx.accounts.glam_state,
+        &ctx.accounts.glam_signer.key,
+        Permission::DriftWithdraw
+    )
+)]
+#[access_control(acl::check_integration(&ctx.accounts.glam_state, Integration::Drift))]
+#[access_control(require_risk_limits_check(&ctx.accounts.glam_state, &ctx.accounts.sysvar_instructions))]
+#[glam_macros::glam_vault_signer_seeds]
+pub fn drift_withdraw<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, DriftWithdraw<'info>>,
+    market_index: u16,
+    amount: u64,
+    reduce_only: bool,
+) -> Result<()> {
+    if has_delegate_limits(&ctx.accounts.glam_state, ctx.accounts.glam_signer.key) {
+        let asset = token_account_mint(&ctx.accounts.user_token_account)?;
+        check_delegate_limits(
+            &ctx.accounts.glam_state,
+            ctx.accounts.glam_signer.key,
+            ctx.accounts.delegate_usage.as_mut(),
+            &asset,
+            amount,
+            ctx.accounts.asset_pricing.as_ref(),
+            ctx.accounts.sol_pricing.as_ref(),
+        )?;
+    }
+
+    // Withdrawn tokens must land in the vault
+    check_vault_destinations(ctx.accounts.glam_vault.key, &[&ctx.accounts.user_token_account])?;
+    let accounts = ctx.accounts.to_account_infos();
+    let guard = VaultBalanceGuard::new(&ctx.accounts.glam_vault, &accounts);
+
+    drift::cpi::withdraw(
+        CpiContext::new_with_signer(
+            ctx.accounts.cpi_program.to_account_info(),
+            drift::cpi::accounts::Withdraw {
+                state: ctx.accounts.state.to_account_info(),
+                user: ctx.accounts.user.to_account_info(),
+                user_stats: ctx.accounts.user_stats.to_account_info(),
+                authority: ctx.accounts.glam_vault.to_account_info(),
+                spot_market_vault: ctx.accounts.spot_market_vault.to_account_info(),
+                drift_signer: ctx.accounts.drift_signer.to_account_info(),
+                user_token_account: ctx.accounts.user_token_account.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            glam_vault_signer_seeds,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        market_index,
+        amount,
+        reduce_only,
+    )?;
+
+    guard.check(&ctx.accounts.glam_vault, &accounts, &[], 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drift::{Order, OrderStatus, OrderTriggerCondition, OrderType, PositionDirection};
+    use std::mem::{offset_of, size_of};
+
+    // Spot and perp positions (8 each) and orders, omitted from `drift::User`
+    const OMITTED_SIZE: usize = 8 * 40 + 8 * 96 + DRIFT_USER_MAX_ORDERS * DRIFT_ORDER_SIZE;
+
+    #[test]
+    fn drift_user_offsets_match_the_account_layout() {
+        let user_offset = |field_offset: usize| 8 + field_offset + OMITTED_SIZE;
+
+        assert_eq!(
+            8 + size_of::<drift::User>() + OMITTED_SIZE,
+            drift::User::SIZE
+        );
+        assert_eq!(
+            DRIFT_USER_AUTHORITY_OFFSET,
+            8 + offset_of!(drift::User, authority)
+        );
+        assert_eq!(
+            DRIFT_USER_ORDERS_OFFSET + DRIFT_USER_MAX_ORDERS * DRIFT_ORDER_SIZE,
+            user_offset(offset_of!(drift::User, last_add_perp_lp_shares_ts))
+        );
+        assert_eq!(
+            DRIFT_USER_NEXT_ORDER_ID_OFFSET,
+            user_offset(offset_of!(drift::User, next_order_id))
+        );
+        assert_eq!(
+            DRIFT_USER_MAX_MARGIN_RATIO_OFFSET,
+            user_offset(offset_of!(drift::User, max_margin_ratio))
+        );
+    }
+
+    #[test]
+    fn drift_order_offsets_match_the_order_layout() {
+        // Orders have no padding, their borsh encoding is the account layout
+        let order = Order {
+            slot: 0,
+            price: 0,
+            base_asset_amount: 0,
+            base_asset_amount_filled: 0,
+            quote_asset_amount_filled: 0,
+            trigger_price: 0,
+            auction_start_price: 0,
+            auction_end_price: 0,
+            max_ts: 0,
+            oracle_price_offset: 0,
+            order_id: 0x0102_0304,
+            market_index: 0,
+            status: OrderStatus::Init,
+            order_type: OrderType::Market,
+            market_type: MarketType::Perp,
+            user_order_id: 0,
+            existing_position_direction: PositionDirection::Long,
+            direction: PositionDirection::Long,
+            reduce_only: false,
+            post_only: false,
+            immediate_or_cancel: false,
+            trigger_condition: OrderTriggerCondition::Above,
+            auction_duration: 0,
+            posted_slot_tail: 0,
+            padding: [0; 2],
+        };
+        let mut data = vec![];
+        order.serialize(&mut data).unwrap();
+
+        assert_eq!(data.len(), DRIFT_ORDER_SIZE);
+        assert_eq!(read_u32(&data, DRIFT_ORDER_ID_OFFSET), 0x0102_0304);
+        assert_eq!(data[DRIFT_ORDER_MARKET_TYPE_OFFSET], MarketType::Perp as u8);
+    }
+}
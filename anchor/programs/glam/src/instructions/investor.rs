@@ -1,28 +1,33 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 use anchor_spl::associated_token::{
-    spl_associated_token_account::get_associated_token_address_with_program_id, AssociatedToken,
+    create_idempotent, spl_associated_token_account::get_associated_token_address_with_program_id,
+    AssociatedToken, Create,
+};
+use anchor_spl::stake::{Stake, StakeAccount};
+use anchor_spl::token::{
+    close_account, initialize_account3, sync_native, CloseAccount, InitializeAccount3, SyncNative,
+    Token,
 };
-use anchor_spl::stake::StakeAccount;
-use anchor_spl::token::Token;
 use anchor_spl::token_interface::{
-    burn, mint_to, transfer_checked, Burn, Mint, MintTo, Token2022, TokenAccount, TransferChecked,
+    burn, mint_to, thaw_account, transfer_checked, Burn, Mint, MintTo, ThawAccount, Token2022,
+    TokenAccount, TransferChecked,
 };
 use glam_macros::glam_vault_signer_seeds;
 use glam_macros::mint_signer_seeds;
 use marinade::state::delayed_unstake_ticket::TicketAccountData;
 use pyth_solana_receiver_sdk::price_update::Price;
-use solana_program::stake::state::warmup_cooldown_rate;
+use solana_program::stake::state::{Authorized, Lockup, StakeAuthorize};
 
 use crate::constants::{self, WSOL};
 use crate::error::GlamError;
-use crate::instructions::policy_hook::PolicyAccount;
-use crate::state::pyth_price::PriceExt;
-use crate::{constants::*, state::*};
-
-fn log_decimal(amount: u64, minus_decimals: i32) -> f64 {
-    amount as f64 * 10f64.powf(minus_decimals as f64)
-}
+use crate::instructions::{
+    compliance::{ComplianceListAccount, ComplianceMemberAccount},
+    kyc::check_gateway_token,
+    policy_hook::PolicyAccount,
+};
+use crate::state::nav::{self, Rounding};
+use crate::{constants::*, gen_mint_signer_seeds, state::*};
 
 #[derive(Accounts)]
 #[instruction(mint_id: u8)]
@@ -43,22 +48,34 @@ pub struct Subscribe<'info> {
     )]
     pub glam_mint: Box<InterfaceAccount<'info, Mint>>,
 
+    /// CHECK: the investor credited with the shares, wallet or PDA,
+    /// may be the signer or any other account (e.g. distributors subscribing for a client).
+    /// Must also sign if it's not the signer and the share class has a lock-up.
+    pub beneficiary: UncheckedAccount<'info>,
+
     #[account(
-        mut,
+        init_if_needed,
+        payer = signer,
         associated_token::mint = glam_mint,
-        associated_token::authority = signer,
+        associated_token::authority = beneficiary,
         associated_token::token_program = token_2022_program
     )]
-    pub signer_share_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub beneficiary_share_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: share account of the share class itself, receiving the dead shares.
+    /// Only needed for the first subscription, validated and created in the handler
+    #[account(mut)]
+    pub dead_shares_ata: Option<UncheckedAccount<'info>>,
 
     // the asset to transfer in exchange for shares
     pub asset: Box<InterfaceAccount<'info, Mint>>,
     #[account(mut, constraint = vault_ata.mint == asset.key())]
     pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    // none to subscribe with native SOL (wSOL asset only)
     #[account(mut, constraint = signer_asset_ata.mint == asset.key())]
-    pub signer_asset_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub signer_asset_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    // signer_policy is required if a fund has a lock-up period.
+    // beneficiary_policy is required if a fund has a lock-up period.
     // it's optional, so we can avoid creating it for funds without
     // a lock-up period.
     #[account(
@@ -67,11 +84,15 @@ pub struct Subscribe<'info> {
         space = 8+8,
         seeds = [
           b"account-policy".as_ref(),
-          signer_share_ata.key().as_ref()
+          beneficiary_share_ata.key().as_ref()
         ],
         bump
     )]
-    pub signer_policy: Option<Account<'info, PolicyAccount>>,
+    pub beneficiary_policy: Option<Account<'info, PolicyAccount>>,
+
+    /// CHECK: beneficiary's gateway token (or glam attestation), required if the
+    /// share class requires KYC, validated in the handler
+    pub beneficiary_kyc_token: Option<UncheckedAccount<'info>>,
 
     // user
     #[account(mut)]
@@ -89,66 +110,19 @@ pub fn subscribe_handler<'c: 'info, 'info>(
     ctx: Context<'_, '_, 'c, 'info, Subscribe<'info>>,
     mint_id: u8,
     amount: u64,
+    min_shares_out: Option<u64>,
     skip_state: bool,
 ) -> Result<()> {
     let state = &ctx.accounts.glam_state;
-    require!(state.enabled, GlamError::StateAccountDisabled);
-
-    let external_vault_accounts =
-        state.get_pubkeys_from_engine_field(EngineFieldName::ExternalVaultAccounts);
-
-    // If system program is in the external vault accounts, it means that
-    // the state is disabled for subscription and redemption.
-    if external_vault_accounts.contains(&system_program::ID) {
-        return err!(GlamError::SubscribeRedeemDisable);
-    }
-
-    if state.mints.len() > 1 {
-        // we need to define how to split the total amount into share classes
-        panic!("not implemented")
-    }
-    require!(state.mints.len() > 0, GlamError::NoShareClass);
-    require!(
-        state.mints[0] == ctx.accounts.glam_mint.key(),
-        GlamError::InvalidShareClass
-    );
-
-    if let Some(blocklist) = state.mint_blocklist(0) {
-        require!(
-            blocklist.len() == 0 || !blocklist.iter().any(|&k| k == ctx.accounts.signer.key()),
-            GlamError::InvalidShareClass
-        );
-    }
-
-    if let Some(allowlist) = state.mint_allowlist(0) {
-        require!(
-            allowlist.len() == 0 || allowlist.iter().any(|&k| k == ctx.accounts.signer.key()),
-            GlamError::InvalidShareClass
-        );
-    }
-
-    // Lock-up
-    let lock_up = state.mint_lock_up(0);
-    if lock_up > 0 {
-        require!(
-            ctx.accounts.signer_policy.is_some(),
-            GlamError::InvalidPolicyAccount
-        );
-        let signer_policy = ctx.accounts.signer_policy.as_mut().unwrap();
-
-        let timestamp = Clock::get()?.unix_timestamp;
-        let cur_locked_until_ts = signer_policy.locked_until_ts;
-        let new_locked_until_ts = timestamp.saturating_add(lock_up);
-        // This check is only paranoia.
-        // If the fund changes the lock-up period to a shorter one,
-        // user with an existing lock-up won't get a shorter period
-        // just by re-subscribing.
-        // Note: because we use init_if_needed there might be a way
-        // to circumvent with re-init attack, but we accept the risk.
-        if new_locked_until_ts > cur_locked_until_ts {
-            signer_policy.locked_until_ts = new_locked_until_ts;
-        }
-    }
+    let external_vault_accounts = check_subscribe_enabled(state, &ctx.accounts.glam_mint.key())?;
+    let remaining_accounts = check_beneficiary(
+        state,
+        &ctx.accounts.beneficiary,
+        &ctx.accounts.signer.key(),
+        ctx.accounts.beneficiary_kyc_token.as_ref(),
+        ctx.accounts.beneficiary_policy.as_mut(),
+        ctx.remaining_accounts,
+    )?;
 
     let state_assets = &state.assets;
     let asset_idx = state_assets
@@ -158,8 +132,7 @@ pub fn subscribe_handler<'c: 'info, 'info>(
     // msg!("asset={:?} idx={:?}", asset_key, asset_idx);
 
     let asset_idx = asset_idx.unwrap();
-    let asset_base = state_assets[0];
-    //TODO check if in_kind is allowed, or idx must be 0
+    // in-kind subscriptions with multiple assets are handled by subscribe_in_kind
 
     //
     // Compute amount of shares to mint
@@ -172,7 +145,7 @@ pub fn subscribe_handler<'c: 'info, 'info>(
     let aum_components = get_aum_components(
         Action::Subscribe,
         &state_assets,
-        ctx.remaining_accounts,
+        remaining_accounts,
         &ctx.accounts.glam_vault,
         &external_vault_accounts,
         &ctx.accounts.signer,
@@ -182,49 +155,58 @@ pub fn subscribe_handler<'c: 'info, 'info>(
         asset_idx, // only for subscribe
     )?;
 
-    let subscribe_asset_price = aum_components[asset_idx].asset_price;
-    let mut total_value = Price {
-        price: 0,
-        conf: 0,
-        exponent: share_expo,
-        publish_time: 0,
-    };
-    for att in aum_components {
-        total_value = total_value
-            .add(&att.asset_value.scale_to_exponent(share_expo).unwrap())
-            .unwrap();
-    }
-
-    let asset_value = subscribe_asset_price
-        .cmul(amount.try_into().unwrap(), subscribe_asset_price.exponent)
-        .unwrap()
-        .scale_to_exponent(share_expo)
-        .unwrap()
-        .price as u128;
-    // msg!(
-    //     "- total_value={:.2} asset_value={}",
-    //     _log_price(total_value),
-    //     log_decimal(asset_value as u64, share_expo),
-    // );
+    let total_value = get_total_value(&aum_components)?;
+    let asset_value = nav::value_of(
+        amount,
+        ctx.accounts.asset.decimals,
+        &aum_components[asset_idx].asset_price,
+        nav::VALUE_EXPO,
+        Rounding::Down,
+    )?;
 
     // amount_shares = asset_value / nav = asset_value / (aum / total_shares) = asset_value * total_shares / aum
-    // - when total_shares = 0, default nav is $100 or 1 SOL
+    // - when total_shares = 0, nav is the launch price of the share class
     let amount_shares = if use_fixed_price {
-        if asset_base == WSOL {
-            // fixed 1SOL/share initial value
-            asset_value
-        } else {
-            // fixed $100/share initial value
-            asset_value / 100
-        }
+        get_launch_shares(
+            state,
+            mint_id,
+            &aum_components,
+            asset_value,
+            share_class.decimals,
+        )?
     } else {
-        // msg!("- total_shares={} total_value={}e{}", total_shares, total_value.price, total_value.expo);
-        (asset_value * total_shares as u128) / (total_value.price as u128)
-    } as u64;
+        nav::to_u64(nav::mul_div(
+            asset_value,
+            total_shares as u128,
+            total_value,
+            Rounding::Down,
+        )?)?
+    };
     msg!(
-        "Subscribe: {} for {} shares",
-        log_decimal(amount, subscribe_asset_price.exponent),
-        log_decimal(amount_shares, share_expo)
+        "Subscribe: {}e-{} for {}e{} shares",
+        amount,
+        ctx.accounts.asset.decimals,
+        amount_shares,
+        share_expo
+    );
+
+    let amount_shares = if use_fixed_price {
+        mint_dead_shares(
+            amount_shares,
+            ctx.accounts.dead_shares_ata.as_ref(),
+            &ctx.accounts.glam_mint,
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+            &ctx.accounts.associated_token_program,
+            &ctx.accounts.token_2022_program,
+            mint_signer_seeds,
+        )?
+    } else {
+        amount_shares
+    };
+    require!(
+        amount_shares > 0 && amount_shares >= min_shares_out.unwrap_or(0),
+        GlamError::InsufficientSharesOut
     );
 
     // transfer asset from user to vault
@@ -235,30 +217,54 @@ pub fn subscribe_handler<'c: 'info, 'info>(
     } else {
         ctx.accounts.token_program.to_account_info()
     };
-    transfer_checked(
-        CpiContext::new(
+    if let Some(signer_asset_ata) = &ctx.accounts.signer_asset_ata {
+        transfer_checked(
+            CpiContext::new(
+                asset_program,
+                TransferChecked {
+                    from: signer_asset_ata.to_account_info(),
+                    mint: asset_info,
+                    to: ctx.accounts.vault_ata.to_account_info(),
+                    authority: ctx.accounts.signer.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.asset.decimals,
+        )?;
+    } else {
+        // native SOL, wrapped into the vault wSOL account
+        require!(
+            ctx.accounts.asset.key() == WSOL,
+            GlamError::InvalidNativeSol
+        );
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.signer.to_account_info(),
+                    to: ctx.accounts.vault_ata.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        sync_native(CpiContext::new(
             asset_program,
-            TransferChecked {
-                from: ctx.accounts.signer_asset_ata.to_account_info(),
-                mint: asset_info,
-                to: ctx.accounts.vault_ata.to_account_info(),
-                authority: ctx.accounts.signer.to_account_info(),
+            SyncNative {
+                account: ctx.accounts.vault_ata.to_account_info(),
             },
-        ),
-        amount,
-        ctx.accounts.asset.decimals,
-    )?;
+        ))?;
+    }
 
     if skip_state {
         // TODO: we should read share class symbol from metadata so that we don't need to pass it as an argument
-        // mint shares to signer
+        // mint shares to beneficiary
 
         mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_2022_program.to_account_info(),
                 MintTo {
                     authority: ctx.accounts.glam_mint.to_account_info(),
-                    to: ctx.accounts.signer_share_ata.to_account_info(),
+                    to: ctx.accounts.beneficiary_share_ata.to_account_info(),
                     mint: ctx.accounts.glam_mint.to_account_info(),
                 },
                 mint_signer_seeds,
@@ -273,8 +279,537 @@ pub fn subscribe_handler<'c: 'info, 'info>(
     Ok(())
 }
 
+#[derive(Accounts)]
+#[instruction(mint_id: u8)]
+pub struct SubscribeInKind<'info> {
+    #[account()]
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    #[account(mut, seeds = [SEED_VAULT.as_bytes(), glam_state.key().as_ref()], bump)]
+    pub glam_vault: SystemAccount<'info>,
+
+    // the shares to mint
+    #[account(
+        mut,
+        seeds = [SEED_MINT.as_bytes(), &[mint_id], glam_state.key().as_ref()],
+        bump,
+        mint::authority = glam_mint,
+        mint::token_program = token_2022_program
+    )]
+    pub glam_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: the investor credited with the shares, wallet or PDA,
+    /// must also sign if it's not the signer and the share class has a lock-up
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = glam_mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_2022_program
+    )]
+    pub beneficiary_share_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: share account of the share class itself, receiving the dead shares.
+    /// Only needed for the first subscription, validated and created in the handler
+    #[account(mut)]
+    pub dead_shares_ata: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8+8,
+        seeds = [
+          b"account-policy".as_ref(),
+          beneficiary_share_ata.key().as_ref()
+        ],
+        bump
+    )]
+    pub beneficiary_policy: Option<Account<'info, PolicyAccount>>,
+
+    /// CHECK: beneficiary's gateway token (or glam attestation), required if the
+    /// share class requires KYC, validated in the handler
+    pub beneficiary_kyc_token: Option<UncheckedAccount<'info>>,
+
+    // user
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+/// Subscribes with a basket of assets, `amounts[i]` of `state.assets[i]`.
+///
+/// Remaining accounts are the same as for redeem: for each asset in `state.assets`
+/// vault ata, pricing account, asset mint and signer ata.
+#[mint_signer_seeds]
+pub fn subscribe_in_kind_handler<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, SubscribeInKind<'info>>,
+    mint_id: u8,
+    amounts: Vec<u64>,
+    match_weights: bool,
+    min_shares_out: Option<u64>,
+    skip_state: bool,
+) -> Result<()> {
+    let state = &ctx.accounts.glam_state;
+    let external_vault_accounts = check_subscribe_enabled(state, &ctx.accounts.glam_mint.key())?;
+    let remaining_accounts = check_beneficiary(
+        state,
+        &ctx.accounts.beneficiary,
+        &ctx.accounts.signer.key(),
+        ctx.accounts.beneficiary_kyc_token.as_ref(),
+        ctx.accounts.beneficiary_policy.as_mut(),
+        ctx.remaining_accounts,
+    )?;
+
+    let state_assets = &state.assets;
+    require!(
+        amounts.len() == state_assets.len() && amounts.iter().any(|&amount| amount > 0),
+        GlamError::InvalidAssetSubscribe
+    );
+
+    //
+    // Compute amount of shares to mint
+    //
+    let share_class = &ctx.accounts.glam_mint;
+    let share_expo = -(share_class.decimals as i32);
+    let total_shares = share_class.supply;
+    let use_fixed_price = total_shares == 0;
+
+    let aum_components = get_aum_components(
+        Action::SubscribeInKind,
+        &state_assets,
+        remaining_accounts,
+        &ctx.accounts.glam_vault,
+        &external_vault_accounts,
+        &ctx.accounts.signer,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_2022_program,
+        false,      // only for redeem
+        usize::MAX, // only for subscribe
+    )?;
+
+    // Value of each leg of the basket, and of each asset in the vault
+    let mut basket_values = Vec::with_capacity(amounts.len());
+    let mut vault_values = Vec::with_capacity(amounts.len());
+    for (att, &amount) in aum_components.iter().zip(amounts.iter()) {
+        basket_values.push(nav::value_of(
+            amount,
+            att.asset_decimals,
+            &att.asset_price,
+            nav::VALUE_EXPO,
+            Rounding::Down,
+        )?);
+        vault_values.push(att.asset_value);
+    }
+    let basket_value = basket_values
+        .iter()
+        .try_fold(0u128, |sum, &value| sum.checked_add(value))
+        .ok_or(GlamError::MathOverflow)?;
+    let total_value = get_total_value(&aum_components)?;
+    require!(basket_value > 0, GlamError::InvalidAssetSubscribe);
+
+    // Optionally, the basket must match the current portfolio weights,
+    // so that the subscription doesn't change the vault allocation
+    if match_weights && !use_fixed_price {
+        require!(total_value > 0, GlamError::InvalidBasketWeights);
+        for (&leg_value, &vault_value) in basket_values.iter().zip(vault_values.iter()) {
+            let leg_weight_bps = nav::mul_div(leg_value, 10_000, basket_value, Rounding::Down)?;
+            let vault_weight_bps = nav::mul_div(vault_value, 10_000, total_value, Rounding::Down)?;
+            require!(
+                leg_weight_bps.abs_diff(vault_weight_bps) <= IN_KIND_WEIGHTS_TOLERANCE_BPS as u128,
+                GlamError::InvalidBasketWeights
+            );
+        }
+    }
+
+    // Same as subscribe, when total_shares = 0, nav is the launch price of the share class
+    let amount_shares = if use_fixed_price {
+        get_launch_shares(
+            state,
+            mint_id,
+            &aum_components,
+            basket_value,
+            share_class.decimals,
+        )?
+    } else {
+        nav::to_u64(nav::mul_div(
+            basket_value,
+            total_shares as u128,
+            total_value,
+            Rounding::Down,
+        )?)?
+    };
+    msg!(
+        "Subscribe in kind: {}e{} for {}e{} shares",
+        basket_value,
+        nav::VALUE_EXPO,
+        amount_shares,
+        share_expo
+    );
+
+    let amount_shares = if use_fixed_price {
+        mint_dead_shares(
+            amount_shares,
+            ctx.accounts.dead_shares_ata.as_ref(),
+            &ctx.accounts.glam_mint,
+            &ctx.accounts.signer,
+            &ctx.accounts.system_program,
+            &ctx.accounts.associated_token_program,
+            &ctx.accounts.token_2022_program,
+            mint_signer_seeds,
+        )?
+    } else {
+        amount_shares
+    };
+    require!(
+        amount_shares > 0 && amount_shares >= min_shares_out.unwrap_or(0),
+        GlamError::InsufficientSharesOut
+    );
+
+    // transfer each leg of the basket from user to vault
+    let (_, _, accounts_for_pricing) = split_remaining_accounts(remaining_accounts)?;
+    for ((att, accounts), &amount) in aum_components
+        .iter()
+        .zip(accounts_for_pricing.chunks(4))
+        .zip(amounts.iter())
+    {
+        if amount == 0 {
+            continue;
+        }
+        let asset = att.asset.as_ref().unwrap();
+        let asset_info = asset.to_account_info();
+        let asset_program = if *asset_info.owner == Token2022::id() {
+            ctx.accounts.token_2022_program.to_account_info()
+        } else {
+            ctx.accounts.token_program.to_account_info()
+        };
+        transfer_checked(
+            CpiContext::new(
+                asset_program,
+                TransferChecked {
+                    from: att.signer_asset_ata.as_ref().unwrap().to_account_info(),
+                    mint: asset_info,
+                    to: accounts[0].to_account_info(),
+                    authority: ctx.accounts.signer.to_account_info(),
+                },
+            ),
+            amount,
+            asset.decimals,
+        )?;
+    }
+
+    if skip_state {
+        // mint shares to beneficiary
+        mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_2022_program.to_account_info(),
+                MintTo {
+                    authority: ctx.accounts.glam_mint.to_account_info(),
+                    to: ctx.accounts.beneficiary_share_ata.to_account_info(),
+                    mint: ctx.accounts.glam_mint.to_account_info(),
+                },
+                mint_signer_seeds,
+            ),
+            amount_shares,
+        )?;
+    } else {
+        //TODO: create subscribe state
+        panic!("not implemented")
+    }
+
+    Ok(())
+}
+
+fn get_dead_shares_ata(glam_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(glam_mint, glam_mint, &Token2022::id())
+}
+
+/// Mints `DEAD_SHARES` to the share account of the share class itself, on the first
+/// subscription, and returns the shares left for the beneficiary.
+///
+/// The dead shares are owned by the mint and can't be redeemed, so a first subscriber
+/// can't mint a tiny supply and then inflate the share price by donating to the vault,
+/// which would round the shares of the next subscribers down to zero.
+fn mint_dead_shares<'info>(
+    amount_shares: u64,
+    dead_shares_ata: Option<&UncheckedAccount<'info>>,
+    glam_mint: &InterfaceAccount<'info, Mint>,
+    signer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    associated_token_program: &Program<'info, AssociatedToken>,
+    token_2022_program: &Program<'info, Token2022>,
+    mint_signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    require!(
+        amount_shares >= MIN_INITIAL_SHARES,
+        GlamError::InitialSubscriptionTooSmall
+    );
+    let dead_shares_ata = dead_shares_ata.ok_or(GlamError::InvalidDeadSharesAccount)?;
+    require!(
+        dead_shares_ata.key() == get_dead_shares_ata(&glam_mint.key()),
+        GlamError::InvalidDeadSharesAccount
+    );
+
+    create_idempotent(CpiContext::new(
+        associated_token_program.to_account_info(),
+        Create {
+            payer: signer.to_account_info(),
+            associated_token: dead_shares_ata.to_account_info(),
+            authority: glam_mint.to_account_info(),
+            mint: glam_mint.to_account_info(),
+            system_program: system_program.to_account_info(),
+            token_program: token_2022_program.to_account_info(),
+        },
+    ))?;
+
+    // Share classes with frozen default state create the account frozen
+    let is_frozen =
+        TokenAccount::try_deserialize(&mut &dead_shares_ata.try_borrow_data()?[..])?.is_frozen();
+    if is_frozen {
+        thaw_account(CpiContext::new_with_signer(
+            token_2022_program.to_account_info(),
+            ThawAccount {
+                account: dead_shares_ata.to_account_info(),
+                mint: glam_mint.to_account_info(),
+                authority: glam_mint.to_account_info(),
+            },
+            mint_signer_seeds,
+        ))?;
+    }
+
+    mint_to(
+        CpiContext::new_with_signer(
+            token_2022_program.to_account_info(),
+            MintTo {
+                authority: glam_mint.to_account_info(),
+                to: dead_shares_ata.to_account_info(),
+                mint: glam_mint.to_account_info(),
+            },
+            mint_signer_seeds,
+        ),
+        DEAD_SHARES,
+    )?;
+
+    Ok(amount_shares - DEAD_SHARES)
+}
+
+/// Returns the amount of dead shares, 0 if the account is not provided or doesn't exist
+/// (share classes launched before dead shares).
+fn get_dead_shares(dead_shares_ata: Option<&UncheckedAccount>, glam_mint: &Pubkey) -> Result<u64> {
+    let Some(dead_shares_ata) = dead_shares_ata else {
+        return Ok(0);
+    };
+    require!(
+        dead_shares_ata.key() == get_dead_shares_ata(glam_mint),
+        GlamError::InvalidDeadSharesAccount
+    );
+    if dead_shares_ata.data_is_empty() {
+        return Ok(0);
+    }
+    Ok(TokenAccount::try_deserialize(&mut &dead_shares_ata.try_borrow_data()?[..])?.amount)
+}
+
+/// Checks that subscriptions are enabled and that the share class is valid.
+/// Returns the external vault accounts.
+fn check_subscribe_enabled(state: &StateAccount, glam_mint: &Pubkey) -> Result<Vec<Pubkey>> {
+    require!(state.enabled, GlamError::StateAccountDisabled);
+
+    let external_vault_accounts =
+        state.get_pubkeys_from_engine_field(EngineFieldName::ExternalVaultAccounts);
+
+    // If system program is in the external vault accounts, it means that
+    // the state is disabled for subscription and redemption.
+    if external_vault_accounts.contains(&system_program::ID) {
+        return err!(GlamError::SubscribeRedeemDisable);
+    }
+    require!(!state.subscriptions_paused(), GlamError::SubscriptionsPaused);
+
+    if state.mints.len() > 1 {
+        // we need to define how to split the total amount into share classes
+        panic!("not implemented")
+    }
+    require!(state.mints.len() > 0, GlamError::NoShareClass);
+    require!(state.mints[0] == *glam_mint, GlamError::InvalidShareClass);
+
+    Ok(external_vault_accounts)
+}
+
+/// Checks that the beneficiary can receive shares (allowlist, blocklist, KYC,
+/// compliance lists) and applies the lock-up to the beneficiary.
+/// Returns the remaining accounts, without the compliance lists accounts.
+fn check_beneficiary<'c, 'info>(
+    state: &StateAccount,
+    beneficiary: &UncheckedAccount<'info>,
+    signer_key: &Pubkey,
+    beneficiary_kyc_token: Option<&UncheckedAccount<'info>>,
+    beneficiary_policy: Option<&mut Account<'info, PolicyAccount>>,
+    remaining_accounts: &'c [AccountInfo<'info>],
+) -> Result<&'c [AccountInfo<'info>]> {
+    // Allowlist, blocklist, KYC and compliance lists apply to the beneficiary,
+    // i.e. the account receiving the shares, not to the signer paying for them
+    let beneficiary_key = beneficiary.key;
+
+    if let Some(blocklist) = state.mint_blocklist(0) {
+        require!(
+            blocklist.is_empty() || !blocklist.iter().any(|k| k == beneficiary_key),
+            GlamError::InvalidShareClass
+        );
+    }
+
+    if let Some(allowlist) = state.mint_allowlist(0) {
+        require!(
+            allowlist.is_empty() || allowlist.iter().any(|k| k == beneficiary_key),
+            GlamError::InvalidShareClass
+        );
+    }
+
+    // KYC
+    if let Some((gateway_program, gatekeeper_network)) = state.mint_kyc_gateway(0) {
+        let beneficiary_kyc_token = beneficiary_kyc_token.ok_or(GlamError::KycRequired)?;
+        check_gateway_token(
+            beneficiary_kyc_token,
+            &gateway_program,
+            &gatekeeper_network,
+            beneficiary_key,
+        )?;
+    }
+
+    // Compliance lists
+    // The last remaining accounts are, for each compliance list, the list and
+    // the beneficiary's member record (may not exist).
+    let compliance_lists = state.mint_compliance_lists(0);
+    let (remaining_accounts, compliance_accounts) = remaining_accounts.split_at(
+        remaining_accounts
+            .len()
+            .checked_sub(2 * compliance_lists.len())
+            .ok_or(GlamError::InvalidRemainingAccounts)?,
+    );
+    for (list_key, accounts) in compliance_lists.iter().zip(compliance_accounts.chunks(2)) {
+        let list = ComplianceListAccount::load(&accounts[0], list_key)?;
+        require!(
+            accounts[1].key() == ComplianceMemberAccount::pda(list_key, beneficiary_key),
+            GlamError::InvalidRemainingAccounts
+        );
+        list.check(list_key, &accounts[1], beneficiary_key)?;
+    }
+
+    // Lock-up
+    let lock_up = state.mint_lock_up(0);
+    if lock_up > 0 {
+        // Subscribing resets the beneficiary's lock-up of all their shares,
+        // so a third party can't do it without the beneficiary's consent
+        require!(
+            beneficiary_key == signer_key || beneficiary.is_signer,
+            GlamError::BeneficiarySignatureRequired
+        );
+        let beneficiary_policy = beneficiary_policy.ok_or(GlamError::InvalidPolicyAccount)?;
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        let cur_locked_until_ts = beneficiary_policy.locked_until_ts;
+        let new_locked_until_ts = timestamp.saturating_add(lock_up);
+        // This check is only paranoia.
+        // If the fund changes the lock-up period to a shorter one,
+        // user with an existing lock-up won't get a shorter period
+        // just by re-subscribing.
+        // Note: because we use init_if_needed there might be a way
+        // to circumvent with re-init attack, but we accept the risk.
+        if new_locked_until_ts > cur_locked_until_ts {
+            beneficiary_policy.locked_until_ts = new_locked_until_ts;
+        }
+    }
+
+    Ok(remaining_accounts)
+}
+
+/// Computes the amount of each asset to pay out for a cash redemption of `amount` shares.
+///
+/// Output assets are used in the given order of priority (default: base asset),
+/// each valued via its oracle. If the vault balance of an asset is insufficient,
+/// the remaining value is paid with the next one.
+fn get_cash_redemption_amounts(
+    aum_components: &[AumComponent],
+    assets: &[Pubkey],
+    output_assets: &[Pubkey],
+    amount: u64,
+    total_supply: u64,
+) -> Result<Vec<u64>> {
+    // value to redeem, rounded down
+    let total_value = get_total_value(aum_components)?;
+    let mut value_to_redeem = nav::mul_div(
+        total_value,
+        amount as u128,
+        total_supply as u128,
+        Rounding::Down,
+    )?;
+
+    let output_assets = if output_assets.is_empty() {
+        &assets[..1]
+    } else {
+        output_assets
+    };
+
+    let mut amounts_out = vec![0u64; aum_components.len()];
+    for output_asset in output_assets {
+        if value_to_redeem == 0 {
+            break;
+        }
+        let i = assets
+            .iter()
+            .position(|asset| asset == output_asset)
+            .ok_or(GlamError::InvalidAssetRedeem)?;
+        let att = &aum_components[i];
+        let available = att.vault_ata.as_ref().map_or(0, |vault_ata| vault_ata.amount);
+        if amounts_out[i] > 0 || available == 0 || att.asset_price.price <= 0 {
+            continue;
+        }
+
+        let needed = nav::amount_of(
+            value_to_redeem,
+            nav::VALUE_EXPO,
+            &att.asset_price,
+            att.asset_decimals,
+            Rounding::Down,
+        )?;
+
+        if needed <= available {
+            amounts_out[i] = needed;
+            value_to_redeem = 0;
+        } else {
+            // not enough balance, pay what's available and fall back to the next asset
+            amounts_out[i] = available;
+            let value_paid = nav::value_of(
+                available,
+                att.asset_decimals,
+                &att.asset_price,
+                nav::VALUE_EXPO,
+                Rounding::Up,
+            )?;
+            value_to_redeem = value_to_redeem.saturating_sub(value_paid);
+        }
+
+        #[cfg(not(feature = "mainnet"))]
+        msg!(
+            "Redeem into {}: amount={} remaining_value={}e{}",
+            output_asset,
+            amounts_out[i],
+            value_to_redeem,
+            nav::VALUE_EXPO
+        );
+    }
+    require!(value_to_redeem == 0, GlamError::InsufficientLiquidity);
+
+    Ok(amounts_out)
+}
+
 #[derive(Accounts)]
 pub struct Redeem<'info> {
+    #[account(mut)]
     pub glam_state: Account<'info, StateAccount>,
 
     #[account(mut, seeds = [SEED_VAULT.as_bytes(), glam_state.key().as_ref()], bump)]
@@ -299,10 +834,24 @@ pub struct Redeem<'info> {
       )]
     pub signer_policy: Option<UncheckedAccount<'info>>,
 
+    /// CHECK: temporary wSOL account, created and closed by the instruction to pay
+    /// native SOL. Only needed if the signer redeems wSOL as native SOL.
+    #[account(mut, seeds = [SEED_WSOL_UNWRAP.as_bytes(), glam_vault.key().as_ref()], bump)]
+    pub wsol_unwrap: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: share account of the share class itself, holding the dead shares,
+    /// validated in the handler. Needed to redeem all the other shares.
+    #[account(mut)]
+    pub dead_shares_ata: Option<UncheckedAccount<'info>>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+
     // programs
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub token_2022_program: Program<'info, Token2022>,
+    pub stake_program: Program<'info, Stake>,
 }
 
 #[glam_vault_signer_seeds]
@@ -310,6 +859,8 @@ pub fn redeem_handler<'c: 'info, 'info>(
     ctx: Context<'_, '_, 'c, 'info, Redeem<'info>>,
     amount: u64,
     in_kind: bool,
+    output_assets: Vec<Pubkey>,
+    min_amounts_out: Option<Vec<u64>>,
     skip_state: bool,
 ) -> Result<()> {
     let state = &ctx.accounts.glam_state;
@@ -375,22 +926,47 @@ pub fn redeem_handler<'c: 'info, 'info>(
     let mint = &ctx.accounts.glam_mint;
     let mint_expo = -(mint.decimals as i32);
     let total_supply = mint.supply;
-    let should_transfer_everything = amount == total_supply;
+    // Dead shares can't be redeemed: redeeming all the other shares redeems the whole vault
+    let dead_shares = get_dead_shares(ctx.accounts.dead_shares_ata.as_ref(), &mint.key())?;
+    let should_transfer_everything = amount.checked_add(dead_shares) == Some(total_supply);
 
     msg!(
-        "Redeem: amount={:.2} total_supply={:.2} ({}e{})",
-        log_decimal(amount, mint_expo),
-        log_decimal(total_supply, mint_expo),
+        "Redeem: amount={}e{} total_supply={}e{}",
+        amount,
+        mint_expo,
         total_supply,
         mint_expo,
     );
 
+    // In-kind redemptions also receive a pro rata share of the vault stake
+    // accounts: the redeemer provides one new (empty) stake account per vault
+    // stake account, at the end of remaining accounts.
+    let split_stake = in_kind && !should_transfer_everything;
+    let (remaining_accounts, new_stake_accounts) = if in_kind {
+        let num_stake_accounts = ctx
+            .remaining_accounts
+            .iter()
+            .filter(|account| {
+                *account.owner == solana_program::stake::program::ID
+                    && external_vault_accounts.contains(account.key)
+            })
+            .count();
+        require!(
+            ctx.remaining_accounts.len() >= 2 * num_stake_accounts,
+            GlamError::InvalidRemainingAccounts
+        );
+        ctx.remaining_accounts
+            .split_at(ctx.remaining_accounts.len() - num_stake_accounts)
+    } else {
+        (ctx.remaining_accounts, &[][..])
+    };
+
     let assets = &state.assets;
     let skip_prices = should_transfer_everything || in_kind;
     let aum_components = get_aum_components(
         Action::Redeem,
         &assets,
-        ctx.remaining_accounts,
+        remaining_accounts,
         &ctx.accounts.glam_vault,
         &external_vault_accounts,
         &ctx.accounts.signer,
@@ -412,6 +988,27 @@ pub fn redeem_handler<'c: 'info, 'info>(
         amount,
     )?;
 
+    if should_transfer_everything && dead_shares > 0 {
+        let state_key = ctx.accounts.glam_state.key();
+        let (_, mint_bump) = Pubkey::find_program_address(
+            &[SEED_MINT.as_bytes(), &[0u8], state_key.as_ref()],
+            &crate::ID,
+        );
+        let mint_signer_seeds = gen_mint_signer_seeds!(state_key, 0u8, mint_bump);
+        burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_2022_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.glam_mint.to_account_info(),
+                    from: ctx.accounts.dead_shares_ata.as_ref().unwrap().to_account_info(),
+                    authority: ctx.accounts.glam_mint.to_account_info(),
+                },
+                &[mint_signer_seeds],
+            ),
+            dead_shares,
+        )?;
+    }
+
     if skip_state {
         // msg!(
         //     "= tot_value={:.2} ({}e{})",
@@ -427,48 +1024,93 @@ pub fn redeem_handler<'c: 'info, 'info>(
         //     value_to_redeem.expo
         // );
 
-        for (i, att) in aum_components.iter().enumerate() {
-            let asset = att.asset.clone().unwrap();
+        // In kind, the pro rata share of each vault stake account is split into a new
+        // stake account. The part that can't be split (see `get_splittable_stake_lamports`)
+        // is paid in wSOL instead if the vault holds enough, else it's left in the vault.
+        let (vault_stake_accounts, _, _) = split_remaining_accounts(remaining_accounts)?;
+        let mut stake_shortfall = 0u64;
+        let stake_splits = if split_stake {
+            require!(
+                vault_stake_accounts.len() == new_stake_accounts.len(),
+                GlamError::InvalidRemainingAccounts
+            );
+            let minimum_delegation = solana_program::stake::tools::get_minimum_delegation()?;
+            vault_stake_accounts
+                .iter()
+                .map(|stake_account| {
+                    let lamports = nav::pro_rata(
+                        stake_account.lamports(),
+                        amount,
+                        total_supply,
+                        Rounding::Down,
+                    )?;
+                    let splittable =
+                        get_splittable_stake_lamports(stake_account, lamports, minimum_delegation)?;
+                    stake_shortfall = stake_shortfall
+                        .checked_add(lamports - splittable)
+                        .ok_or(GlamError::MathOverflow)?;
+                    Ok(splittable)
+                })
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            vec![]
+        };
 
-            let amount_asset = if should_transfer_everything {
-                if let Some(vault_ata) = &att.vault_ata {
-                    vault_ata.amount
-                } else {
-                    0
-                }
-            } else if in_kind {
-                //TODO do not compute pricing
-                ((att.asset_amount as u128 * amount as u128) / total_supply as u128) as u64
-            } else {
-                if i > 0 {
-                    break;
-                }
+        let amounts_out: Vec<u64> = if should_transfer_everything {
+            aum_components
+                .iter()
+                .map(|att| att.vault_ata.as_ref().map_or(0, |vault_ata| vault_ata.amount))
+                .collect()
+        } else if in_kind {
+            // Pro rata of the vault token accounts only, external accounts
+            // (e.g. stake accounts) are split separately below
+            let vault_amounts: Vec<u64> = aum_components
+                .iter()
+                .map(|att| att.vault_ata.as_ref().map_or(0, |vault_ata| vault_ata.amount))
+                .collect();
+            let mut amounts_out = vault_amounts
+                .iter()
+                .map(|&vault_amount| {
+                    nav::pro_rata(vault_amount, amount, total_supply, Rounding::Down)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(i) = assets.iter().position(|asset| *asset == WSOL) {
+                amounts_out[i] += stake_shortfall.min(vault_amounts[i] - amounts_out[i]);
+            }
+            amounts_out
+        } else {
+            get_cash_redemption_amounts(
+                &aum_components,
+                assets,
+                &output_assets,
+                amount,
+                total_supply,
+            )?
+        };
 
-                let mut total_value = Price {
-                    price: 0,
-                    conf: 0,
-                    exponent: mint_expo,
-                    publish_time: 0,
-                };
-                for att in &aum_components {
-                    total_value = total_value
-                        .add(&att.asset_value.scale_to_exponent(mint_expo).unwrap())
-                        .unwrap();
+        // Slippage bounds, amounts_out[i] is the amount of assets[i]
+        if let Some(min_amounts_out) = &min_amounts_out {
+            require!(
+                min_amounts_out.len() == amounts_out.len(),
+                GlamError::InvalidAssetRedeem
+            );
+            for ((asset, &amount_out), &min_amount_out) in
+                assets.iter().zip(amounts_out.iter()).zip(min_amounts_out.iter())
+            {
+                if amount_out < min_amount_out {
+                    msg!(
+                        "Redeem into {}: amount={} < min_amount_out={}",
+                        asset,
+                        amount_out,
+                        min_amount_out
+                    );
+                    return err!(GlamError::InsufficientAssetsOut);
                 }
+            }
+        }
 
-                let value_to_redeem = Price {
-                    price: ((total_value.price as u128 * amount as u128) / total_supply as u128)
-                        as i64,
-                    conf: 0,
-                    exponent: mint_expo,
-                    publish_time: 0,
-                };
-                let value = value_to_redeem
-                    .scale_to_exponent(att.asset_price.exponent)
-                    .unwrap();
-                ((value.price as u128 * 10u128.pow(asset.decimals as u32))
-                    / att.asset_price.price as u128) as u64
-            };
+        for (att, &amount_asset) in aum_components.iter().zip(amounts_out.iter()) {
+            let asset = att.asset.clone().unwrap();
 
             if amount_asset == 0 {
                 continue;
@@ -495,8 +1137,22 @@ pub fn redeem_handler<'c: 'info, 'info>(
                 GlamError::InvalidVaultTokenAccount
             );
 
-            let signer_asset_ata = att.signer_asset_ata.clone().unwrap();
             let vault_ata: InterfaceAccount<TokenAccount> = att.vault_ata.clone().unwrap();
+            let signer_asset_ata = match att.signer_asset_ata.clone() {
+                Some(signer_asset_ata) => signer_asset_ata,
+                None => {
+                    // native SOL
+                    unwrap_wsol_to_signer(
+                        &ctx.accounts,
+                        ctx.bumps.wsol_unwrap,
+                        &vault_ata,
+                        &asset,
+                        amount_asset,
+                        glam_vault_signer_seeds,
+                    )?;
+                    continue;
+                }
+            };
             transfer_checked(
                 CpiContext::new_with_signer(
                     asset_program,
@@ -513,6 +1169,88 @@ pub fn redeem_handler<'c: 'info, 'info>(
             )?;
         }
 
+        // Stake accounts: split the pro rata share into the new stake accounts
+        // (or hand over the whole accounts when redeeming everything), then
+        // authorize the redeemer as staker and withdrawer.
+        // Marinade tickets can't be split and remain in the vault until claimed.
+        // LSTs are vault tokens and are already redeemed in kind above.
+        if split_stake || should_transfer_everything {
+            let stake_accounts_out: Vec<&AccountInfo<'info>> = if split_stake {
+                let mut stake_accounts_out = Vec::new();
+                for ((existing_stake, new_stake), &lamports) in vault_stake_accounts
+                    .iter()
+                    .zip(new_stake_accounts.iter())
+                    .zip(stake_splits.iter())
+                {
+                    if lamports == 0 {
+                        // Nothing to split: the new stake account is initialized with the
+                        // signer as staker and withdrawer, so that they can reclaim its rent.
+                        let ix = solana_program::stake::instruction::initialize(
+                            new_stake.key,
+                            &Authorized::auto(&ctx.accounts.signer.key()),
+                            &Lockup::default(),
+                        );
+                        solana_program::program::invoke(
+                            &ix,
+                            &[new_stake.clone(), ctx.accounts.rent.to_account_info()],
+                        )?;
+                        continue;
+                    }
+
+                    let ix = solana_program::stake::instruction::split(
+                        existing_stake.key,
+                        &ctx.accounts.glam_vault.key(),
+                        lamports,
+                        new_stake.key,
+                    );
+                    // Only the split instruction at index #2 is needed, the
+                    // new stake account is created client side.
+                    solana_program::program::invoke_signed(
+                        &ix[2],
+                        &[
+                            (*existing_stake).clone(),
+                            new_stake.clone(),
+                            ctx.accounts.glam_vault.to_account_info(),
+                            ctx.accounts.clock.to_account_info(),
+                        ],
+                        glam_vault_signer_seeds,
+                    )?;
+                    stake_accounts_out.push(new_stake);
+                }
+                stake_accounts_out
+            } else {
+                vault_stake_accounts
+            };
+
+            for stake_account in stake_accounts_out {
+                for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+                    let ix = solana_program::stake::instruction::authorize(
+                        stake_account.key,
+                        &ctx.accounts.glam_vault.key(),
+                        &ctx.accounts.signer.key(),
+                        stake_authorize,
+                        None,
+                    );
+                    solana_program::program::invoke_signed(
+                        &ix,
+                        &[
+                            stake_account.clone(),
+                            ctx.accounts.clock.to_account_info(),
+                            ctx.accounts.glam_vault.to_account_info(),
+                        ],
+                        glam_vault_signer_seeds,
+                    )?;
+                }
+
+                if should_transfer_everything {
+                    ctx.accounts.glam_state.delete_from_engine_field(
+                        EngineFieldName::ExternalVaultAccounts,
+                        stake_account.key(),
+                    );
+                }
+            }
+        }
+
         if should_transfer_everything {
             let lamports = ctx.accounts.glam_vault.lamports();
             if lamports > 0 {
@@ -555,11 +1293,163 @@ pub struct AumComponent<'info> {
     pub signer_asset_ata: Option<InterfaceAccount<'info, TokenAccount>>,
     pub asset: Option<InterfaceAccount<'info, Mint>>,
     pub asset_amount: u64,
+    pub asset_decimals: u8,
     pub asset_price: Price,
-    pub asset_value: Price,
+    pub asset_value: u128, // in 10^nav::VALUE_EXPO
     pub price_type: PriceDenom,
 }
 
+/// Total value of the aum components, in 10^nav::VALUE_EXPO
+pub fn get_total_value(aum_components: &[AumComponent]) -> Result<u128> {
+    Ok(aum_components
+        .iter()
+        .try_fold(0u128, |total, att| total.checked_add(att.asset_value))
+        .ok_or(GlamError::MathOverflow)?)
+}
+
+/// Shares minted for `value` (in 10^nav::VALUE_EXPO) at the launch price of the share
+/// class, in base asset units. Defaults to 1 SOL per share for SOL denominated funds,
+/// $100 per share otherwise.
+fn get_launch_shares(
+    state: &StateAccount,
+    mint_id: u8,
+    aum_components: &[AumComponent],
+    value: u128,
+    share_decimals: u8,
+) -> Result<u64> {
+    let base = &aum_components[0];
+    let launch_price = state
+        .mint_launch_price(mint_id as usize)
+        .unwrap_or_else(|| {
+            let one = 10u64.pow(base.asset_decimals as u32);
+            if state.assets[0] == WSOL {
+                one
+            } else {
+                100 * one
+            }
+        });
+    let launch_value = nav::value_of(
+        launch_price,
+        base.asset_decimals,
+        &base.asset_price,
+        nav::VALUE_EXPO,
+        Rounding::Up,
+    )?;
+    nav::to_u64(nav::mul_div(
+        nav::rescale(value, 0, -(share_decimals as i32), Rounding::Down)?,
+        1,
+        launch_value,
+        Rounding::Down,
+    )?)
+}
+
+/// Pays `amount` of vault wSOL to the signer as native SOL: the wSOL is moved
+/// to a temporary token account, which is then closed to the signer.
+fn unwrap_wsol_to_signer<'info>(
+    accounts: &Redeem<'info>,
+    wsol_unwrap_bump: Option<u8>,
+    vault_ata: &InterfaceAccount<'info, TokenAccount>,
+    wsol: &InterfaceAccount<'info, Mint>,
+    amount: u64,
+    glam_vault_signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let (wsol_unwrap, bump) = match (&accounts.wsol_unwrap, wsol_unwrap_bump) {
+        (Some(wsol_unwrap), Some(bump)) => (wsol_unwrap.to_account_info(), bump),
+        _ => return err!(GlamError::InvalidNativeSol),
+    };
+    let token_program = accounts.token_program.to_account_info();
+
+    // the signer pays the rent, refunded when the account is closed below
+    let vault_key = accounts.glam_vault.key();
+    let wsol_unwrap_seeds: &[&[u8]] = &[SEED_WSOL_UNWRAP.as_bytes(), vault_key.as_ref(), &[bump]];
+    let system_program_info = accounts.system_program.to_account_info();
+    let rent = Rent::get()?.minimum_balance(anchor_spl::token::TokenAccount::LEN);
+    let current_lamports = wsol_unwrap.lamports();
+    if current_lamports == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info,
+                system_program::CreateAccount {
+                    from: accounts.signer.to_account_info(),
+                    to: wsol_unwrap.clone(),
+                },
+                &[wsol_unwrap_seeds],
+            ),
+            rent,
+            anchor_spl::token::TokenAccount::LEN as u64,
+            &Token::id(),
+        )?;
+    } else {
+        // Anyone can transfer lamports to the PDA, which makes create_account fail:
+        // top up the rent, then allocate and assign instead
+        let required_lamports = rent.saturating_sub(current_lamports);
+        if required_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program_info.clone(),
+                    system_program::Transfer {
+                        from: accounts.signer.to_account_info(),
+                        to: wsol_unwrap.clone(),
+                    },
+                ),
+                required_lamports,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program_info.clone(),
+                system_program::Allocate {
+                    account_to_allocate: wsol_unwrap.clone(),
+                },
+                &[wsol_unwrap_seeds],
+            ),
+            anchor_spl::token::TokenAccount::LEN as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program_info,
+                system_program::Assign {
+                    account_to_assign: wsol_unwrap.clone(),
+                },
+                &[wsol_unwrap_seeds],
+            ),
+            &Token::id(),
+        )?;
+    }
+    initialize_account3(CpiContext::new(
+        token_program.clone(),
+        InitializeAccount3 {
+            account: wsol_unwrap.clone(),
+            mint: wsol.to_account_info(),
+            authority: accounts.glam_vault.to_account_info(),
+        },
+    ))?;
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            TransferChecked {
+                from: vault_ata.to_account_info(),
+                mint: wsol.to_account_info(),
+                to: wsol_unwrap.clone(),
+                authority: accounts.glam_vault.to_account_info(),
+            },
+            glam_vault_signer_seeds,
+        ),
+        amount,
+        wsol.decimals,
+    )?;
+    close_account(CpiContext::new_with_signer(
+        token_program,
+        CloseAccount {
+            account: wsol_unwrap,
+            destination: accounts.signer.to_account_info(),
+            authority: accounts.glam_vault.to_account_info(),
+        },
+        glam_vault_signer_seeds,
+    ))
+}
+
 pub fn get_aum_components<'info>(
     action: Action,
     assets: &[Pubkey],
@@ -601,6 +1491,13 @@ pub fn get_aum_components<'info>(
     //
     let mut aum_components: Vec<AumComponent> = Vec::new();
     let timestamp = Clock::get()?.unix_timestamp;
+    // The AUM is rounded up for subscriptions (fewer shares minted) and
+    // down for redemptions (fewer assets paid out)
+    let rounding = if action == Action::Redeem {
+        Rounding::Down
+    } else {
+        Rounding::Up
+    };
     let mut price_sol_usd = Price {
         price: 0,
         conf: 0,
@@ -657,32 +1554,36 @@ pub fn get_aum_components<'info>(
             GlamError::InvalidPricingOracle
         );
 
-        let (asset, signer_asset_ata) = if action == Action::Redeem {
+        let (asset, signer_asset_ata) = if action != Action::Subscribe {
             // Parse and deser asset mint account
             let asset_account = &accounts[2];
             require!(
                 asset_account.key() == assets[i],
                 GlamError::InvalidRemainingAccounts
             );
-            let asset = InterfaceAccount::<Mint>::try_from(asset_account).expect("invalid asset");
+            let asset = InterfaceAccount::<Mint>::try_from(asset_account)
+                .map_err(|_| GlamError::InvalidRemainingAccounts)?;
 
             // Parse and deser signer ata account
+            // Redemptions in native SOL pass the signer instead of its wSOL account
             let signer_ata_account = &accounts[3];
-            let signer_asset_ata: InterfaceAccount<'_, TokenAccount> =
-                InterfaceAccount::<TokenAccount>::try_from(signer_ata_account).expect(&format!(
-                    "invalid user account: {}",
-                    signer_ata_account.key()
-                ));
-            require!(
-                signer_asset_ata.mint == cur_asset,
-                GlamError::InvalidSignerAccount
-            );
-            require!(
-                signer_asset_ata.owner == signer.key(),
-                GlamError::InvalidSignerAccount
-            );
+            if action == Action::Redeem && is_wsol && signer_ata_account.key() == signer.key() {
+                (Some(asset), None)
+            } else {
+                let signer_asset_ata: InterfaceAccount<'_, TokenAccount> =
+                    InterfaceAccount::<TokenAccount>::try_from(signer_ata_account)
+                        .map_err(|_| GlamError::InvalidSignerAccount)?;
+                require!(
+                    signer_asset_ata.mint == cur_asset,
+                    GlamError::InvalidSignerAccount
+                );
+                require!(
+                    signer_asset_ata.owner == signer.key(),
+                    GlamError::InvalidSignerAccount
+                );
 
-            (Some(asset), Some(signer_asset_ata))
+                (Some(asset), Some(signer_asset_ata))
+            }
         } else {
             (None, None)
         };
@@ -713,8 +1614,13 @@ pub fn get_aum_components<'info>(
             None
         };
 
-        let need_price =
-            !skip_prices && (asset_amount > 0 || i == force_price_asset_idx || is_wsol);
+        // in-kind subscriptions need the price of all assets in the basket
+        let need_price = !skip_prices
+            && (asset_amount > 0
+                || i == force_price_asset_idx
+                || i == 0 // base asset, for the launch price
+                || is_wsol
+                || action == Action::SubscribeInKind);
         let mut asset_price = if need_price {
             cur_asset_meta.get_price(pricing_account, timestamp, action)?
         } else {
@@ -741,15 +1647,25 @@ pub fn get_aum_components<'info>(
             }
         }
 
-        let asset_value = asset_price
-            .cmul(asset_amount.try_into().unwrap(), asset_price.exponent)
-            .unwrap();
+        let asset_decimals = cur_asset_meta.decimals;
+        let asset_value = if skip_prices {
+            0
+        } else {
+            nav::value_of(
+                asset_amount,
+                asset_decimals,
+                &asset_price,
+                nav::VALUE_EXPO,
+                rounding,
+            )?
+        };
 
         aum_components.push(AumComponent {
             vault_ata,
             signer_asset_ata,
             asset,
             asset_amount,
+            asset_decimals,
             asset_price,
             asset_value,
             price_type: asset_price_type,
@@ -765,88 +1681,41 @@ pub fn get_aum_components<'info>(
     }) {
         msg!("wsol aum_component={:?}", wsol_component);
 
-        let mut external_lamports = marinade_tickets
-            .iter()
-            .map(|account_info| {
-                let mut data_slice: &[u8] = &account_info.data.borrow();
-                let data: &mut &[u8] = &mut data_slice;
-                let ticket = TicketAccountData::try_deserialize(data).unwrap();
-
-                account_info.lamports() + ticket.lamports_amount // total lamports hold by the ticket account
-            })
-            .sum::<u64>();
-
-        external_lamports += stake_accounts
-            .iter()
-            .map(|account_info| account_info.lamports())
-            .sum::<u64>();
-
-        /*
-         * Besides lamports in the tickets and stake accounts, we also estimate the yields of eligible stake accounts:
-         * 1. iterate through stake accounts and calculate the activated stake
-         * 2. calculate the rewards based on the activated stake
-         */
-        let clock = Clock::get()?;
-        let activated_stake: u64 = stake_accounts
-            .iter()
-            .map(|account_info| {
-                let mut data_slice: &[u8] = &account_info.data.borrow();
-                let data: &mut &[u8] = &mut data_slice;
-                let stake = StakeAccount::try_deserialize(data).unwrap();
-
-                #[cfg(not(feature = "mainnet"))]
-                msg!(
-                    "Stake account {:?}, delegation: {:?}",
-                    account_info.key,
-                    stake.delegation()
-                );
-
-                let delegation = stake.delegation().unwrap();
-
-                // stake is activating, not eligible for yields
-                if clock.epoch <= delegation.activation_epoch {
-                    return 0;
-                }
-                // stake has been deactivated, not eligible for yields
-                if clock.epoch > delegation.deactivation_epoch {
-                    return 0;
-                }
-                // activation_epoch < clock.epoch <= deactivation_epoch
-                let mut activated_stake_pct = (clock.epoch - delegation.activation_epoch) as f64
-                    * warmup_cooldown_rate(clock.epoch, None);
-                activated_stake_pct = activated_stake_pct.min(1.0);
-
-                #[cfg(not(feature = "mainnet"))]
-                msg!(
-                    "current epoch: {:?}, activation epoch {:?}, activated_stake_pct {:?}",
-                    clock.epoch,
-                    delegation.activation_epoch,
-                    activated_stake_pct
-                );
-
-                return (activated_stake_pct * delegation.stake as f64) as u64;
-            })
-            .sum();
-
-        // TODO: allow fund manager to set the yield rate
-        let stake_rewards = activated_stake as f64 * 0.0005 * get_epoch_progress().unwrap();
-
-        msg!(
-            "external_lamports={:?}, stake_rewards={:?}",
-            external_lamports,
-            stake_rewards as u64
-        );
+        let mut external_lamports = 0u64;
+        for account_info in marinade_tickets.iter() {
+            let ticket = TicketAccountData::try_deserialize(&mut &account_info.data.borrow()[..])
+                .map_err(|_| GlamError::InvalidRemainingAccounts)?;
+            // total lamports hold by the ticket account
+            external_lamports = external_lamports
+                .checked_add(account_info.lamports())
+                .and_then(|lamports| lamports.checked_add(ticket.lamports_amount))
+                .ok_or(GlamError::MathOverflow)?;
+        }
 
-        let expo = wsol_component.asset_price.exponent;
-        let updated_asset_amount =
-            wsol_component.asset_amount + external_lamports + stake_rewards as u64;
-        let updated_asset_value = wsol_component
-            .asset_price
-            .cmul(updated_asset_amount.try_into().unwrap(), expo)
-            .unwrap();
+        // Stake accounts are valued at their lamports (see `nav::stake_value`)
+        for account_info in stake_accounts.iter() {
+            let stake = StakeAccount::try_deserialize(&mut &account_info.data.borrow()[..])?;
+            let value = nav::stake_value(account_info.lamports(), &stake)?;
+            external_lamports = external_lamports
+                .checked_add(value)
+                .ok_or(GlamError::MathOverflow)?;
+        }
 
-        wsol_component.asset_amount = updated_asset_amount;
-        wsol_component.asset_value = updated_asset_value;
+        msg!("external_lamports={:?}", external_lamports);
+
+        wsol_component.asset_amount = wsol_component
+            .asset_amount
+            .checked_add(external_lamports)
+            .ok_or(GlamError::MathOverflow)?;
+        if !skip_prices {
+            wsol_component.asset_value = nav::value_of(
+                wsol_component.asset_amount,
+                wsol_component.asset_decimals,
+                &wsol_component.asset_price,
+                nav::VALUE_EXPO,
+                rounding,
+            )?;
+        }
     }
 
     // SOL <-> USD conversion not needed if skip_prices is true
@@ -856,15 +1725,15 @@ pub fn get_aum_components<'info>(
                 // Any asset priced in USD, should be converted in SOL
                 // by divinging by the SOL price.
                 // Note: wSOL price is already in SOL
-                if att.price_type == PriceDenom::USD {
-                    let expo = att.asset_price.exponent;
-                    att.asset_price = att.asset_price.div(&price_sol_usd).unwrap();
-                    att.asset_value = att
-                        .asset_price
-                        .cmul(att.asset_amount.try_into().unwrap(), expo)
-                        .unwrap()
-                        .scale_to_exponent(expo)
-                        .unwrap()
+                if att.price_type == PriceDenom::USD && att.asset_price.price > 0 {
+                    att.asset_price = nav::div_price(&att.asset_price, &price_sol_usd, rounding)?;
+                    att.asset_value = nav::value_of(
+                        att.asset_amount,
+                        att.asset_decimals,
+                        &att.asset_price,
+                        nav::VALUE_EXPO,
+                        rounding,
+                    )?;
                 }
             }
 
@@ -872,15 +1741,15 @@ pub fn get_aum_components<'info>(
                 // LST (or any asset with price in SOL) should be converted to USD
                 // by multiplying their price time SOL price
                 // Note: wSOL price is already in USD
-                if att.price_type == PriceDenom::SOL {
-                    let expo = att.asset_price.exponent;
-                    att.asset_price = att.asset_price.mul(&price_sol_usd).unwrap();
-                    att.asset_value = att
-                        .asset_price
-                        .cmul(att.asset_amount.try_into().unwrap(), expo)
-                        .unwrap()
-                        .scale_to_exponent(expo)
-                        .unwrap()
+                if att.price_type == PriceDenom::SOL && att.asset_price.price > 0 {
+                    att.asset_price = nav::mul_price(&att.asset_price, &price_sol_usd, rounding)?;
+                    att.asset_value = nav::value_of(
+                        att.asset_amount,
+                        att.asset_decimals,
+                        &att.asset_price,
+                        nav::VALUE_EXPO,
+                        rounding,
+                    )?;
                 }
             }
         }
@@ -889,6 +1758,36 @@ pub fn get_aum_components<'info>(
     Ok(aum_components)
 }
 
+/// Lamports that can be split off `stake_account`, at most `lamports`.
+///
+/// The stake program requires the split stake and the stake remaining in the
+/// account to be at least the minimum delegation (if delegated), and the account
+/// to stay rent-exempt. Returns 0 if less than the minimum can be split (dust).
+fn get_splittable_stake_lamports(
+    stake_account: &AccountInfo,
+    lamports: u64,
+    minimum_delegation: u64,
+) -> Result<u64> {
+    let stake = StakeAccount::try_deserialize(&mut &stake_account.data.borrow()[..])?;
+    let rent_exempt_reserve = stake.meta().map_or(0, |meta| meta.rent_exempt_reserve);
+    let minimum_stake = if stake.delegation().is_some() {
+        minimum_delegation
+    } else {
+        0
+    };
+
+    let lamports = lamports.min(
+        stake_account
+            .lamports()
+            .saturating_sub(rent_exempt_reserve.saturating_add(minimum_stake)),
+    );
+    Ok(if lamports < minimum_stake.max(1) {
+        0
+    } else {
+        lamports
+    })
+}
+
 /**
  * Split remaining_accounts into 3 categories:
  * 1) Accounts with owner being stake program
@@ -936,26 +1835,3 @@ fn split_remaining_accounts<'info>(
 
     Ok((stake_accounts, marinade_tickets, accounts_for_pricing))
 }
-
-pub fn get_epoch_progress<'info>() -> Result<f64> {
-    let clock = Clock::get()?;
-
-    // Retrieve the EpochSchedule sysvar and get first slot in epoch & slots per epoch
-    let epoch_schedule = EpochSchedule::get()?;
-    let first_slot_in_epoch = epoch_schedule.get_first_slot_in_epoch(clock.epoch);
-    let slots_in_epoch = epoch_schedule.get_slots_in_epoch(clock.epoch);
-
-    // Calculate epoch progress as a percentage
-    let slot_index = clock.slot - first_slot_in_epoch;
-    let epoch_progress = (slot_index as f64 / slots_in_epoch as f64) * 100.0;
-
-    #[cfg(not(feature = "mainnet"))]
-    msg!(
-        "Epoch: {}, Slot: {}, Progress: {:.2}%",
-        clock.epoch,
-        clock.slot,
-        epoch_progress
-    );
-
-    Ok(epoch_progress)
-}
@@ -5,6 +5,8 @@ use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use solana_program::{instruction::Instruction, program::invoke_signed};
 
 use crate::error::GlamError;
+use crate::instructions::delegate_limits::*;
+use crate::instructions::risk_limits::require_risk_limits_check;
 use crate::instructions::stake_pool::StakePoolProgramInterface;
 use crate::{constants::*, state::*};
 
@@ -131,6 +133,20 @@ pub struct JupiterSwap<'info> {
     /// CHECK: manually check in handler
     pub output_stake_pool: Option<AccountInfo<'info>>,
 
+    #[account(
+        mut,
+        seeds = [SEED_DELEGATE_USAGE.as_bytes(), glam_state.key().as_ref(), glam_signer.key().as_ref()],
+        bump
+    )]
+    pub delegate_usage: Option<Box<Account<'info, DelegateUsageAccount>>>,
+    /// CHECK: checked against the input mint's pricing account
+    pub input_pricing: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against SOL's pricing account
+    pub sol_pricing: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar, to find the risk limits check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
     // programs
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub input_token_program: Interface<'info, TokenInterface>,
@@ -233,6 +249,7 @@ fn is_lst<'info>(mint: &Pubkey, stake_pool_account: Option<&AccountInfo<'info>>)
 #[access_control(
     acl::check_integration(&ctx.accounts.glam_state, Integration::JupiterSwap)
 )]
+#[access_control(require_risk_limits_check(&ctx.accounts.glam_state, &ctx.accounts.sysvar_instructions))]
 #[glam_macros::glam_vault_signer_seeds]
 pub fn swap_handler<'c: 'info, 'info>(
     ctx: Context<'_, '_, 'c, 'info, JupiterSwap<'info>>,
@@ -273,7 +290,14 @@ pub fn swap_handler<'c: 'info, 'info>(
     if input_is_lst && output_is_lst {
         accepted_permissions.push(Permission::JupiterSwapLst);
     }
-    acl::check_access_any(&state, &ctx.accounts.glam_signer.key, accepted_permissions)?;
+    acl::check_access_any(&state, &ctx.accounts.glam_signer.key, &accepted_permissions)?;
+
+    // Both mints must be in the delegate's assets, if scoped
+    for mint in [ctx.accounts.input_mint.key(), ctx.accounts.output_mint.key()] {
+        acl::check_delegate_scope(&state, &ctx.accounts.glam_signer.key, &mint, |scope| {
+            &scope.assets
+        })?;
+    }
 
     // TODO: should we add missing assets to the list after permission check?
     // This will gradually expand the assets allowlist and auto escalate JupiterSwapAllowlisted privilege over time
@@ -312,6 +336,7 @@ pub fn swap_handler<'c: 'info, 'info>(
         .collect();
 
     // Swap
+    let input_amount_before = ctx.accounts.input_vault_ata.amount;
     invoke_signed(
         &Instruction {
             program_id: Jupiter::id(),
@@ -322,5 +347,18 @@ pub fn swap_handler<'c: 'info, 'info>(
         glam_vault_signer_seeds,
     )?;
 
+    // Check the notional swapped against the signer's limits, if any
+    ctx.accounts.input_vault_ata.reload()?;
+    let input_amount = input_amount_before.saturating_sub(ctx.accounts.input_vault_ata.amount);
+    check_delegate_limits(
+        &ctx.accounts.glam_state,
+        ctx.accounts.glam_signer.key,
+        ctx.accounts.delegate_usage.as_mut(),
+        &ctx.accounts.input_mint.key(),
+        input_amount,
+        ctx.accounts.input_pricing.as_ref(),
+        ctx.accounts.sol_pricing.as_ref(),
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, error::GlamError, ID};
+
+/// GLAM-native KYC attestation, issued by an identity provider (the issuer)
+/// to a wallet (the subject). Share classes requiring KYC via GLAM attestations
+/// use the issuer as gatekeeper network.
+#[account]
+pub struct AttestationAccount {
+    pub issuer: Pubkey,
+    pub subject: Pubkey,
+    pub issued_at: i64,
+    pub expires_at: i64, // unix timestamp, 0 = never expires
+}
+impl AttestationAccount {
+    pub const INIT_SIZE: usize = 32 + 32 + 8 + 8;
+}
+
+#[derive(Accounts)]
+#[instruction(subject: Pubkey)]
+pub struct IssueAttestation<'info> {
+    #[account(
+        init_if_needed,
+        payer = issuer,
+        space = 8 + AttestationAccount::INIT_SIZE,
+        seeds = [SEED_ATTESTATION.as_bytes(), issuer.key().as_ref(), subject.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, AttestationAccount>,
+
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn issue_attestation_handler(
+    ctx: Context<IssueAttestation>,
+    subject: Pubkey,
+    expires_at: i64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at == 0 || expires_at > now, GlamError::KycExpired);
+
+    let attestation = &mut ctx.accounts.attestation;
+    attestation.issuer = ctx.accounts.issuer.key();
+    attestation.subject = subject;
+    attestation.issued_at = now;
+    attestation.expires_at = expires_at;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(mut, close = issuer, has_one = issuer @ GlamError::NotAuthorized)]
+    pub attestation: Account<'info, AttestationAccount>,
+
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+}
+
+pub fn revoke_attestation_handler(_ctx: Context<RevokeAttestation>) -> Result<()> {
+    Ok(())
+}
+
+// Civic gateway token, see:
+// https://github.com/identity-com/on-chain-identity-gateway/blob/develop/solana/program/src/state.rs
+#[derive(AnchorDeserialize)]
+struct CivicGatewayToken {
+    _features: u8,
+    _parent_gateway_token: Option<Pubkey>,
+    owner_wallet: Pubkey,
+    _owner_identity: Option<Pubkey>,
+    gatekeeper_network: Pubkey,
+    _issuing_gatekeeper: Pubkey,
+    state: u8,
+    expire_time: Option<i64>,
+}
+const CIVIC_GATEWAY_TOKEN_STATE_ACTIVE: u8 = 0;
+
+/// Checks that `gateway_token` is a valid, unexpired gateway token for `wallet`:
+/// - a civic gateway token issued by the gatekeeper network, or
+/// - a glam attestation issued by the issuer (gatekeeper network)
+pub fn check_gateway_token(
+    gateway_token: &AccountInfo,
+    gateway_program: &Pubkey,
+    gatekeeper_network: &Pubkey,
+    wallet: &Pubkey,
+) -> Result<()> {
+    require!(
+        gateway_token.owner == gateway_program && !gateway_token.data_is_empty(),
+        GlamError::KycRequired
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let data = gateway_token.try_borrow_data()?;
+
+    if *gateway_program == ID {
+        let attestation = AttestationAccount::try_deserialize(&mut &data[..])
+            .map_err(|_| GlamError::InvalidKycToken)?;
+        require!(
+            attestation.issuer == *gatekeeper_network && attestation.subject == *wallet,
+            GlamError::InvalidKycToken
+        );
+        require!(
+            attestation.expires_at == 0 || attestation.expires_at > now,
+            GlamError::KycExpired
+        );
+    } else {
+        let token = CivicGatewayToken::deserialize(&mut &data[..])
+            .map_err(|_| GlamError::InvalidKycToken)?;
+        require!(
+            token.gatekeeper_network == *gatekeeper_network
+                && token.owner_wallet == *wallet
+                && token.state == CIVIC_GATEWAY_TOKEN_STATE_ACTIVE,
+            GlamError::InvalidKycToken
+        );
+        if let Some(expire_time) = token.expire_time {
+            require!(expire_time > now, GlamError::KycExpired);
+        }
+    }
+
+    Ok(())
+}
@@ -1,3 +1,6 @@
+use crate::constants::{SEED_DELEGATE_USAGE, WSOL};
+use crate::instructions::delegate_limits::*;
+use crate::instructions::risk_limits::require_risk_limits_check;
 use crate::state::*;
 use anchor_lang::prelude::*;
 use marinade::program::MarinadeFinance as Marinade;
@@ -40,6 +43,17 @@ pub struct MarinadeDeposit<'info> {
     pub system_program: Program<'info, System>,
     /// CHECK: should be validated by target program
     pub token_program: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [SEED_DELEGATE_USAGE.as_bytes(), glam_state.key().as_ref(), glam_signer.key().as_ref()],
+        bump
+    )]
+    pub delegate_usage: Option<Box<Account<'info, DelegateUsageAccount>>>,
+    /// CHECK: checked against SOL's pricing account
+    pub sol_pricing: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar, to find the risk limits check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
 }
 #[derive(Accounts)]
 pub struct MarinadeDepositStakeAccount<'info> {
@@ -191,8 +205,19 @@ pub struct MarinadeClaim<'info> {
 #[access_control(
     acl::check_integration(&ctx.accounts.glam_state, Integration::Marinade)
 )]
+#[access_control(require_risk_limits_check(&ctx.accounts.glam_state, &ctx.accounts.sysvar_instructions))]
 #[glam_macros::glam_vault_signer_seeds]
 pub fn marinade_deposit(ctx: Context<MarinadeDeposit>, lamports: u64) -> Result<()> {
+    check_delegate_limits(
+        &ctx.accounts.glam_state,
+        ctx.accounts.glam_signer.key,
+        ctx.accounts.delegate_usage.as_mut(),
+        &WSOL,
+        lamports,
+        ctx.accounts.sol_pricing.as_ref(),
+        None,
+    )?;
+
     marinade::cpi::deposit(
         CpiContext::new_with_signer(
             ctx.accounts.cpi_program.to_account_info(),
@@ -278,6 +303,9 @@ pub fn marinade_liquid_unstake(
     ctx: Context<MarinadeLiquidUnstake>,
     msol_amount: u64,
 ) -> Result<()> {
+    let accounts = ctx.accounts.to_account_infos();
+    let guard = VaultBalanceGuard::new(&ctx.accounts.glam_vault, &accounts);
+
     marinade::cpi::liquid_unstake(
         CpiContext::new_with_signer(
             ctx.accounts.cpi_program.to_account_info(),
@@ -296,6 +324,14 @@ pub fn marinade_liquid_unstake(
             glam_vault_signer_seeds,
         ),
         msol_amount,
+    )?;
+
+    // Only mSOL is debited, SOL is paid to the vault
+    guard.check(
+        &ctx.accounts.glam_vault,
+        &accounts,
+        &[ctx.accounts.get_msol_from.key()],
+        0,
     )
 }
 #[access_control(
@@ -346,6 +382,9 @@ pub fn marinade_order_unstake(ctx: Context<MarinadeOrderUnstake>, msol_amount: u
 )]
 #[glam_macros::glam_vault_signer_seeds]
 pub fn marinade_claim<'info>(ctx: Context<'_, '_, '_, 'info, MarinadeClaim<'info>>) -> Result<()> {
+    let accounts = ctx.accounts.to_account_infos();
+    let guard = VaultBalanceGuard::new(&ctx.accounts.glam_vault, &accounts);
+
     let glam_state = &mut ctx.accounts.glam_state;
 
     // Process the main ticket account
@@ -387,5 +426,6 @@ pub fn marinade_claim<'info>(ctx: Context<'_, '_, '_, 'info, MarinadeClaim<'info
         ))?;
     }
 
-    Ok(())
+    // Claims only pay SOL to the vault
+    guard.check(&ctx.accounts.glam_vault, &accounts, &[], 0)
 }
@@ -1,6 +1,11 @@
 use crate::{
-    constants::*, error::GlamError, gen_mint_signer_seeds,
-    policy_hook::TRANSFER_HOOK_EXTRA_ACCOUNTS, state::*, ID,
+    compliance::ComplianceListAccount, constants::*, error::GlamError, gen_mint_signer_seeds,
+    policy_hook::{
+        TRANSFER_HOOK_COMPLIANCE_LIST_EXTRA_ACCOUNTS, TRANSFER_HOOK_EXTRA_ACCOUNTS,
+        TRANSFER_HOOK_KYC_EXTRA_ACCOUNTS,
+    },
+    state::*,
+    ID,
 };
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
@@ -48,9 +53,15 @@ pub struct NewMint<'info> {
     pub new_mint: AccountInfo<'info>,
 
     /// CHECK: Token2022 Transfer Hook, we manually create it
+    /// Always sized for the KYC and compliance lists extra accounts, even if the share
+    /// class doesn't use them
     #[account(
         init,
-        space = ExtraAccountMetaList::size_of(TRANSFER_HOOK_EXTRA_ACCOUNTS).unwrap(),
+        space = ExtraAccountMetaList::size_of(
+            TRANSFER_HOOK_EXTRA_ACCOUNTS
+                + TRANSFER_HOOK_KYC_EXTRA_ACCOUNTS
+                + TRANSFER_HOOK_COMPLIANCE_LIST_EXTRA_ACCOUNTS * MAX_MINT_COMPLIANCE_LISTS
+        ).unwrap(),
         seeds = [b"extra-account-metas", new_mint.key().as_ref()],
         bump,
         payer = glam_signer,
@@ -128,6 +139,94 @@ pub fn add_mint_handler<'c: 'info, 'info>(
         raw_openfunds.has_lock_up_for_redemption = Some(policy_has_lock_up);
     }
 
+    // Launch price
+    // Input:
+    // - launch_price (engine): share price when the supply is zero, in base asset units
+    //   (default: 1 SOL for SOL denominated funds, 100 USD otherwise)
+    // Output:
+    // - launch_price, launch_price_currency, launch_price_date (openfunds)
+    // The base asset can't change afterwards (see update_state), so the launch
+    // price keeps its denomination.
+    if let Some(launch_price) = mint_model.launch_price {
+        require!(launch_price > 0, GlamError::InvalidLaunchPrice);
+        let base_asset = *state.assets.first().ok_or(GlamError::InvalidLaunchPrice)?;
+        mint_params.push(EngineField {
+            name: EngineFieldName::LaunchPrice,
+            value: EngineFieldValue::U64 { val: launch_price },
+        });
+        let asset_meta = AssetMeta::get(base_asset.to_string().as_str());
+        if let Ok(asset_meta) = asset_meta {
+            let one = 10u64.pow(asset_meta.decimals as u32);
+            raw_openfunds.launch_price = Some(format!(
+                "{}.{:0width$}",
+                launch_price / one,
+                launch_price % one,
+                width = asset_meta.decimals as usize
+            ));
+        }
+        raw_openfunds.launch_price_currency = Some(match asset_meta {
+            _ if base_asset == WSOL => "SOL".to_string(),
+            Ok(asset_meta) if asset_meta.is_stable_coin => "USD".to_string(),
+            _ => base_asset.to_string(),
+        });
+        raw_openfunds.launch_price_date = Some(format_date(Clock::get()?.unix_timestamp));
+    }
+
+    // Policy: KYC
+    // Input:
+    // - kyc_gateway_program (engine): civic gateway program, or glam for glam attestations
+    // - kyc_gatekeeper_network (engine): gatekeeper network, or attestation issuer
+    // Subscriptions and transfers require a valid gateway token (or attestation)
+    // owned by the recipient.
+    let kyc_gateway = match (
+        mint_model.kyc_gateway_program,
+        mint_model.kyc_gatekeeper_network,
+    ) {
+        (Some(gateway_program), Some(gatekeeper_network)) => {
+            require!(
+                gateway_program == CIVIC_GATEWAY_PROGRAM || gateway_program == ID,
+                GlamError::InvalidKycGateway
+            );
+            mint_params.push(EngineField {
+                name: EngineFieldName::KycGatewayProgram,
+                value: EngineFieldValue::Pubkey {
+                    val: gateway_program,
+                },
+            });
+            mint_params.push(EngineField {
+                name: EngineFieldName::KycGatekeeperNetwork,
+                value: EngineFieldValue::Pubkey {
+                    val: gatekeeper_network,
+                },
+            });
+            transfer_hook_active = true;
+            Some((gateway_program, gatekeeper_network))
+        }
+        (None, None) => None,
+        _ => return err!(GlamError::InvalidKycGateway),
+    };
+
+    // Policy: Compliance lists
+    // Shared allowlists/blocklists, checked by subscribe and the transfer hook.
+    // They can't be changed after the mint is created, as they're part of the
+    // transfer hook extra accounts. The lists are passed as writable remaining
+    // accounts, and can't be closed until the share class is closed.
+    let compliance_lists = mint_model.compliance_lists.clone().unwrap_or_default();
+    require!(
+        compliance_lists.len() <= MAX_MINT_COMPLIANCE_LISTS,
+        GlamError::InvalidComplianceListsLen
+    );
+    if !compliance_lists.is_empty() {
+        ComplianceListAccount::update_references(&compliance_lists, ctx.remaining_accounts, true)?;
+        mint_params.push(EngineField {
+            name: EngineFieldName::ComplianceLists,
+            value: EngineFieldValue::VecPubkey {
+                val: compliance_lists.clone(),
+            },
+        });
+        transfer_hook_active = true;
+    }
+
     mint_model.raw_openfunds = Some(raw_openfunds);
     state.params.push(mint_params);
 
@@ -264,7 +363,7 @@ pub fn add_mint_handler<'c: 'info, 'info>(
     .unwrap();
 
     // Init transfer hook ExtraAccountMetaList
-    let account_metas = vec![
+    let mut account_metas = vec![
         // index 5, state
         ExtraAccountMeta::new_with_pubkey(&state_key, false, false)?,
         // index 6, src_account_policy
@@ -290,6 +389,85 @@ pub fn add_mint_handler<'c: 'info, 'info>(
             false, // is_writable
         )?,
     ];
+    if let Some((gateway_program, gatekeeper_network)) = kyc_gateway {
+        // dst_account.owner
+        let dst_owner = Seed::AccountData {
+            account_index: 2,
+            data_index: 32,
+            length: 32,
+        };
+        let seeds = if gateway_program == ID {
+            vec![
+                Seed::Literal {
+                    bytes: SEED_ATTESTATION.as_bytes().to_vec(),
+                },
+                Seed::Literal {
+                    bytes: gatekeeper_network.to_bytes().to_vec(),
+                },
+                dst_owner,
+            ]
+        } else {
+            // civic gateway token: [wallet, "gateway", seed (default 0), gatekeeper_network]
+            vec![
+                dst_owner,
+                Seed::Literal {
+                    bytes: "gateway".as_bytes().to_vec(),
+                },
+                Seed::Literal { bytes: vec![0; 8] },
+                Seed::Literal {
+                    bytes: gatekeeper_network.to_bytes().to_vec(),
+                },
+            ]
+        };
+        // index 8, kyc gateway program
+        account_metas.push(ExtraAccountMeta::new_with_pubkey(
+            &gateway_program,
+            false,
+            false,
+        )?);
+        // index 9, dst owner's gateway token
+        account_metas.push(ExtraAccountMeta::new_external_pda_with_seeds(
+            8,
+            &seeds,
+            false, // is_signer
+            false, // is_writable
+        )?);
+    }
+    for compliance_list in compliance_lists.iter() {
+        let list_index = (5 + account_metas.len()) as u8;
+        let member_record_seeds = |owner_account_index: u8| {
+            vec![
+                Seed::Literal {
+                    bytes: SEED_COMPLIANCE_MEMBER.as_bytes().to_vec(),
+                },
+                Seed::AccountKey { index: list_index },
+                // token account owner
+                Seed::AccountData {
+                    account_index: owner_account_index,
+                    data_index: 32,
+                    length: 32,
+                },
+            ]
+        };
+        // compliance list
+        account_metas.push(ExtraAccountMeta::new_with_pubkey(
+            compliance_list,
+            false,
+            false,
+        )?);
+        // src owner's member record
+        account_metas.push(ExtraAccountMeta::new_with_seeds(
+            &member_record_seeds(0),
+            false, // is_signer
+            false, // is_writable
+        )?);
+        // dst owner's member record
+        account_metas.push(ExtraAccountMeta::new_with_seeds(
+            &member_record_seeds(2),
+            false, // is_signer
+            false, // is_writable
+        )?);
+    }
     let extra_account_meta_list = &ctx.accounts.extra_account_meta_list;
     let mut data = extra_account_meta_list.try_borrow_mut_data()?;
     ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &account_metas)?;
@@ -454,8 +632,8 @@ pub struct ForceTransferTokens<'info> {
     )]
     pub to_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// CHECK: any address owned by system program
-    pub from: SystemAccount<'info>,
+    /// CHECK: any address, wallet or PDA, owning from_ata
+    pub from: UncheckedAccount<'info>,
 
     /// CHECK: any address owned by system program, or the system program
     pub to: AccountInfo<'info>,
@@ -658,6 +836,70 @@ pub fn update_mint_handler(
     Ok(())
 }
 
+#[derive(Accounts)]
+pub struct UpdateMintList<'info> {
+    #[account(mut)]
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    pub glam_signer: Signer<'info>,
+}
+
+#[access_control(acl::check_access(&ctx.accounts.glam_state, &ctx.accounts.glam_signer.key, Permission::ManageAllowlist))]
+pub fn update_mint_allowlist_handler(
+    ctx: Context<UpdateMintList>,
+    mint_id: u8,
+    add: Vec<Pubkey>,
+    remove: Vec<Pubkey>,
+) -> Result<()> {
+    let allowlist = ctx
+        .accounts
+        .glam_state
+        .mint_allowlist_mut(mint_id as usize)
+        .ok_or(GlamError::NoShareClass)?;
+    update_list(allowlist, add, remove);
+    Ok(())
+}
+
+#[access_control(acl::check_access(&ctx.accounts.glam_state, &ctx.accounts.glam_signer.key, Permission::ManageBlocklist))]
+pub fn update_mint_blocklist_handler(
+    ctx: Context<UpdateMintList>,
+    mint_id: u8,
+    add: Vec<Pubkey>,
+    remove: Vec<Pubkey>,
+) -> Result<()> {
+    let blocklist = ctx
+        .accounts
+        .glam_state
+        .mint_blocklist_mut(mint_id as usize)
+        .ok_or(GlamError::NoShareClass)?;
+    update_list(blocklist, add, remove);
+    Ok(())
+}
+
+/// Formats a unix timestamp as YYYY-MM-DD (UTC), the date format used by openfunds.
+fn format_date(unix_timestamp: i64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = unix_timestamp.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn update_list(list: &mut Vec<Pubkey>, add: Vec<Pubkey>, remove: Vec<Pubkey>) {
+    list.retain(|key| !remove.contains(key));
+    for key in add {
+        if !list.contains(&key) {
+            list.push(key);
+        }
+    }
+}
+
 #[derive(Accounts)]
 #[instruction(mint_id: u8)]
 pub struct CloseMint<'info> {
@@ -722,6 +964,10 @@ pub fn close_mint_handler(ctx: Context<CloseMint>, mint_id: u8) -> Result<()> {
         mint_signer_seeds,
     ))?;
 
+    // Release the compliance lists, passed as writable remaining accounts
+    let compliance_lists = ctx.accounts.glam_state.mint_compliance_lists(mint_id as usize);
+    ComplianceListAccount::update_references(&compliance_lists, ctx.remaining_accounts, false)?;
+
     ctx.accounts.glam_state.mints.remove(mint_id as usize);
 
     if let Some(metadata) = ctx.accounts.glam_state.metadata.clone() {
@@ -741,3 +987,15 @@ pub fn close_mint_handler(ctx: Context<CloseMint>, mint_id: u8) -> Result<()> {
     msg!("Mint closed: {}", ctx.accounts.glam_mint.key());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openfunds_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_735_689_599), "2024-12-31");
+    }
+}
@@ -1,22 +1,36 @@
+pub mod compliance;
+pub mod cpi_policy;
+pub mod delegate_limits;
 pub mod drift;
 pub mod investor;
 pub mod jupiter;
 pub mod jupiter_vote;
+pub mod kamino_lending;
+pub mod kyc;
 pub mod marinade;
+pub mod meteora_dlmm;
 pub mod mint;
 pub mod policy_hook;
+pub mod risk_limits;
 pub mod stake;
 pub mod stake_pool;
 pub mod state;
 pub mod wsol;
 
+pub use compliance::*;
+pub use cpi_policy::*;
+pub use delegate_limits::*;
 pub use drift::*;
 pub use investor::*;
 pub use jupiter::*;
 pub use jupiter_vote::*;
+pub use kamino_lending::*;
+pub use kyc::*;
 pub use marinade::*;
+pub use meteora_dlmm::*;
 pub use mint::*;
 pub use policy_hook::*;
+pub use risk_limits::*;
 pub use stake::*;
 pub use stake_pool::*;
 pub use state::*;
@@ -1,14 +1,21 @@
 use {
-    crate::{error::GlamError, state::*},
-    anchor_lang::{prelude::*, system_program},
+    crate::{
+        compliance::{ComplianceListAccount, ComplianceListType},
+        error::GlamError,
+        kyc::check_gateway_token,
+        state::*,
+    },
+    anchor_lang::{
+        prelude::*, solana_program::program_option::COption, system_program,
+    },
     anchor_spl::{
         token_2022::{
             spl_token_2022::{
                 extension::{
-                    transfer_hook::TransferHookAccount, BaseStateWithExtensions,
-                    StateWithExtensions,
+                    permanent_delegate::PermanentDelegate, transfer_hook::TransferHookAccount,
+                    BaseStateWithExtensions, StateWithExtensions,
                 },
-                state::Account as Token2022Account,
+                state::{Account as Token2022Account, Mint as Token2022Mint},
             },
             ID as TOKEN_2022_PROGRAM_ID,
         },
@@ -22,8 +29,13 @@ use {
 };
 
 pub const TRANSFER_HOOK_EXTRA_ACCOUNTS: usize = 3;
+pub const TRANSFER_HOOK_KYC_EXTRA_ACCOUNTS: usize = 2;
+pub const TRANSFER_HOOK_COMPLIANCE_LIST_EXTRA_ACCOUNTS: usize = 3; // per compliance list
 
-pub fn execute(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
+pub fn execute<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, TransferHook<'info>>,
+    amount: u64,
+) -> Result<()> {
     let src_account = &ctx.accounts.src_account;
     let dst_account = &ctx.accounts.dst_account;
 
@@ -31,13 +43,99 @@ pub fn execute(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
     assert_token_account_is_transferring(&dst_account.to_account_info().try_borrow_data()?)?;
 
     let data = ctx.accounts.extra_account_meta_list.try_borrow_data()?;
+    // KYC extra accounts (gateway program and dst owner's gateway token) and
+    // compliance lists extra accounts, if any, are passed as remaining accounts
     ExtraAccountMetaList::check_account_infos::<ExecuteInstruction>(
-        &ctx.accounts.to_account_infos(),
+        &[
+            ctx.accounts.to_account_infos(),
+            ctx.remaining_accounts.to_vec(),
+        ]
+        .concat(),
         &TransferHookInstruction::Execute { amount }.pack(),
         &ctx.program_id,
         &data,
     )?;
 
+    let state = &ctx.accounts.state;
+    let mint_id = state
+        .mints
+        .iter()
+        .position(|mint| *mint == ctx.accounts.mint.key())
+        .ok_or(GlamError::InvalidShareClass)?;
+
+    // The owner account is the authority token2022 has already verified for this
+    // transfer: the source owner (wallet or PDA), its approved delegate, or the
+    // mint's permanent delegate. We only need to figure out which one it is.
+    let authority = ctx.accounts.owner.key();
+    let is_owner_or_delegate =
+        src_account.owner == authority || src_account.delegate == COption::Some(authority);
+    let is_permanent_delegate =
+        get_permanent_delegate(&ctx.accounts.mint.to_account_info())? == Some(authority);
+    require!(
+        is_owner_or_delegate || is_permanent_delegate,
+        GlamError::InvalidTransferAuthority
+    );
+
+    // Transfers by the permanent delegate are forced transfers (e.g. force_transfer_tokens),
+    // they skip the lock-up and can move shares out of a blocklisted account.
+    let is_forced_transfer = !is_owner_or_delegate;
+
+    if let Some(blocklist) = state.mint_blocklist(mint_id) {
+        require!(
+            !blocklist.contains(&dst_account.owner),
+            GlamError::AccountNotAllowed
+        );
+        require!(
+            is_forced_transfer || !blocklist.contains(&src_account.owner),
+            GlamError::AccountNotAllowed
+        );
+    }
+
+    if let Some(allowlist) = state.mint_allowlist(mint_id) {
+        require!(
+            allowlist.is_empty() || allowlist.contains(&dst_account.owner),
+            GlamError::AccountNotAllowed
+        );
+    }
+
+    let kyc_gateway = state.mint_kyc_gateway(mint_id);
+    if let Some((gateway_program, gatekeeper_network)) = kyc_gateway {
+        let dst_gateway_token = ctx.remaining_accounts.get(1).ok_or(GlamError::KycRequired)?;
+        check_gateway_token(
+            dst_gateway_token,
+            &gateway_program,
+            &gatekeeper_network,
+            &dst_account.owner,
+        )?;
+    }
+
+    // Each compliance list comes with 3 extra accounts: the list, and the
+    // member records of the src and dst owners.
+    let compliance_accounts = if kyc_gateway.is_some() {
+        ctx.remaining_accounts
+            .get(TRANSFER_HOOK_KYC_EXTRA_ACCOUNTS..)
+            .unwrap_or_default()
+    } else {
+        ctx.remaining_accounts
+    };
+    for (i, list_key) in state.mint_compliance_lists(mint_id).iter().enumerate() {
+        let idx = i * TRANSFER_HOOK_COMPLIANCE_LIST_EXTRA_ACCOUNTS;
+        let accounts = compliance_accounts
+            .get(idx..idx + TRANSFER_HOOK_COMPLIANCE_LIST_EXTRA_ACCOUNTS)
+            .ok_or(GlamError::InvalidComplianceList)?;
+        let list = ComplianceListAccount::load(&accounts[0], list_key)?;
+        list.check(list_key, &accounts[2], &dst_account.owner)?;
+        // Same as the share class blocklist, forced transfers can move shares
+        // out of a blocklisted account
+        if !is_forced_transfer && list.list_type == ComplianceListType::Blocklist {
+            list.check(list_key, &accounts[1], &src_account.owner)?;
+        }
+    }
+
+    if is_forced_transfer {
+        return Ok(());
+    }
+
     // It's responsibility of subscribe() to create the policy account
     // with the proper lock-up timestamp.
     // If a user doesn't have a policy account, it means that his tokens
@@ -59,6 +157,15 @@ pub fn execute(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
     Ok(())
 }
 
+fn get_permanent_delegate(mint_info: &AccountInfo) -> Result<Option<Pubkey>> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(&mint_data)?;
+    Ok(mint
+        .get_extension::<PermanentDelegate>()
+        .ok()
+        .and_then(|extension| Option::<Pubkey>::from(extension.delegate)))
+}
+
 fn assert_token_account_is_transferring(account_data: &[u8]) -> Result<()> {
     let token_account = StateWithExtensions::<Token2022Account>::unpack(account_data)?;
     let extension = token_account.get_extension::<TransferHookAccount>()?;
@@ -79,7 +186,6 @@ fn assert_token_account_is_transferring(account_data: &[u8]) -> Result<()> {
 pub struct TransferHook<'info> {
     #[account(
         token::mint = mint,
-        token::authority = owner,
         token::token_program = TOKEN_2022_PROGRAM_ID,
     )]
     pub src_account: Box<InterfaceAccount<'info, TokenAccount>>,
@@ -95,8 +201,9 @@ pub struct TransferHook<'info> {
     )]
     pub dst_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    //TODO: Change to unchecked if we want to allow PDAs to transfer
-    pub owner: SystemAccount<'info>,
+    /// CHECK: transfer authority (src owner, delegate or permanent delegate), may be a PDA,
+    /// validated in the handler
+    pub owner: UncheckedAccount<'info>,
 
     /// CHECK: ExtraAccountMetaList Account
     #[account(
@@ -0,0 +1,145 @@
+use std::cmp::Ordering;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::system_program;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::Token2022;
+
+use crate::error::GlamError;
+use crate::instructions::investor::{get_aum_components, get_total_value, AumComponent};
+use crate::{constants::*, state::*};
+
+#[derive(Accounts)]
+pub struct CheckRiskLimits<'info> {
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    #[account(seeds = [SEED_VAULT.as_bytes(), glam_state.key().as_ref()], bump)]
+    pub glam_vault: SystemAccount<'info>,
+
+    pub glam_signer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+/// Checks the portfolio of the vault against its risk limits.
+///
+/// The remaining accounts are the same as subscribe: vault ata and pricing account
+/// of each asset, then the external vault accounts (stake accounts, marinade tickets).
+pub fn check_risk_limits_handler<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, CheckRiskLimits<'info>>,
+) -> Result<()> {
+    let state = &ctx.accounts.glam_state;
+    let risk_limits = match state.risk_limits() {
+        Some(risk_limits) => risk_limits,
+        None => return Ok(()),
+    };
+
+    // System program is only a marker of disabled subscriptions
+    let external_vault_accounts: Vec<Pubkey> = state
+        .get_pubkeys_from_engine_field(EngineFieldName::ExternalVaultAccounts)
+        .into_iter()
+        .filter(|account| *account != system_program::ID)
+        .collect();
+
+    let aum_components = get_aum_components(
+        Action::Subscribe,
+        &state.assets,
+        ctx.remaining_accounts,
+        &ctx.accounts.glam_vault,
+        &external_vault_accounts,
+        &ctx.accounts.glam_signer,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_2022_program,
+        false,
+        0,
+    )?;
+
+    check_weights(&risk_limits, &state.assets, &aum_components)
+}
+
+/// Checks the weights of the aum components. The base asset (first asset) is cash:
+/// it's only subject to the min weight, and other assets to the max weight.
+fn check_weights(
+    risk_limits: &RiskLimits,
+    assets: &[Pubkey],
+    aum_components: &[AumComponent],
+) -> Result<()> {
+    let total_value = get_total_value(aum_components)?;
+    if total_value == 0 {
+        return Ok(());
+    }
+
+    // Compares the weight of value, value / total_value, to bps / 10_000
+    let cmp_weight = |value: u128, bps: u16| -> Result<Ordering> {
+        let weight = value.checked_mul(10_000).ok_or(GlamError::MathOverflow)?;
+        let limit = total_value
+            .checked_mul(bps as u128)
+            .ok_or(GlamError::MathOverflow)?;
+        Ok(weight.cmp(&limit))
+    };
+
+    let mut lst_value = 0u128;
+    for (i, (asset, att)) in assets.iter().zip(aum_components.iter()).enumerate() {
+        if i == 0 {
+            if risk_limits.min_base_asset_weight_bps > 0 {
+                require!(
+                    cmp_weight(att.asset_value, risk_limits.min_base_asset_weight_bps)?
+                        != Ordering::Less,
+                    GlamError::RiskLimitExceeded
+                );
+            }
+        } else if risk_limits.max_asset_weight_bps > 0 {
+            require!(
+                cmp_weight(att.asset_value, risk_limits.max_asset_weight_bps)?
+                    != Ordering::Greater,
+                GlamError::RiskLimitExceeded
+            );
+        }
+
+        if AssetMeta::get(&asset.to_string())?.get_price_denom() == PriceDenom::SOL {
+            lst_value = lst_value
+                .checked_add(att.asset_value)
+                .ok_or(GlamError::MathOverflow)?;
+        }
+    }
+
+    if risk_limits.max_lst_weight_bps > 0 {
+        require!(
+            cmp_weight(lst_value, risk_limits.max_lst_weight_bps)? != Ordering::Greater,
+            GlamError::RiskLimitExceeded
+        );
+    }
+
+    Ok(())
+}
+
+/// Vaults with risk limits need a `check_risk_limits` of the vault later in the
+/// transaction, so that the limits are checked once all its actions are done.
+pub fn require_risk_limits_check(
+    glam_state: &Account<StateAccount>,
+    sysvar_instructions: &AccountInfo,
+) -> Result<()> {
+    if glam_state.risk_limits().is_none() {
+        return Ok(());
+    }
+
+    let current_index = load_current_index_checked(sysvar_instructions)? as usize;
+    let mut index = current_index + 1;
+    while let Ok(ix) = load_instruction_at_checked(index, sysvar_instructions) {
+        if ix.program_id == crate::ID
+            && ix
+                .data
+                .starts_with(&crate::instruction::CheckRiskLimits::DISCRIMINATOR)
+            && ix.accounts.first().map(|meta| meta.pubkey) == Some(glam_state.key())
+        {
+            return Ok(());
+        }
+        index += 1;
+    }
+
+    err!(GlamError::RiskLimitsCheckRequired)
+}
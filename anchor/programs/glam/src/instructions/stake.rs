@@ -1,4 +1,4 @@
-use crate::{constants::*, state::*};
+use crate::{constants::*, instructions::delegate_limits::*, state::*};
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::stake::{
     deactivate_stake, withdraw, DeactivateStake, Stake, StakeAccount, Withdraw,
@@ -31,6 +31,15 @@ pub struct InitializeAndDelegateStake<'info> {
 
     pub stake_program: Program<'info, Stake>,
     pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [SEED_DELEGATE_USAGE.as_bytes(), glam_state.key().as_ref(), glam_signer.key().as_ref()],
+        bump
+    )]
+    pub delegate_usage: Option<Box<Account<'info, DelegateUsageAccount>>>,
+    /// CHECK: checked against SOL's pricing account
+    pub sol_pricing: Option<UncheckedAccount<'info>>,
 }
 
 #[access_control(
@@ -44,6 +53,16 @@ pub fn initialize_and_delegate_stake_handler<'c: 'info, 'info>(
     ctx: Context<InitializeAndDelegateStake>,
     lamports: u64,
 ) -> Result<()> {
+    check_delegate_limits(
+        &ctx.accounts.glam_state,
+        ctx.accounts.glam_signer.key,
+        ctx.accounts.delegate_usage.as_mut(),
+        &WSOL,
+        lamports,
+        ctx.accounts.sol_pricing.as_ref(),
+        None,
+    )?;
+
     // Fund the stake account with lamports from the vault
     system_program::transfer(
         CpiContext::new_with_signer(
@@ -1,4 +1,6 @@
-use crate::{constants::*, state::*};
+use crate::{
+    constants::*, instructions::delegate_limits::*, instructions::risk_limits::*, state::*,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -65,17 +67,40 @@ pub struct StakePoolDepositSol<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(
+        mut,
+        seeds = [SEED_DELEGATE_USAGE.as_bytes(), glam_state.key().as_ref(), glam_signer.key().as_ref()],
+        bump
+    )]
+    pub delegate_usage: Option<Box<Account<'info, DelegateUsageAccount>>>,
+    /// CHECK: checked against SOL's pricing account
+    pub sol_pricing: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar, to find the risk limits check
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
 }
 
 #[access_control(
     acl::check_access(&ctx.accounts.glam_state, &ctx.accounts.glam_signer.key, Permission::Stake)
 )]
 #[access_control(acl::check_stake_pool_integration(&ctx.accounts.glam_state, &ctx.accounts.stake_pool_program.key))]
+#[access_control(require_risk_limits_check(&ctx.accounts.glam_state, &ctx.accounts.sysvar_instructions))]
 #[glam_macros::glam_vault_signer_seeds]
 pub fn deposit_sol_handler<'c: 'info, 'info>(
     ctx: Context<StakePoolDepositSol>,
     lamports: u64,
 ) -> Result<()> {
+    check_delegate_limits(
+        &ctx.accounts.glam_state,
+        ctx.accounts.glam_signer.key,
+        ctx.accounts.delegate_usage.as_mut(),
+        &WSOL,
+        lamports,
+        ctx.accounts.sol_pricing.as_ref(),
+        None,
+    )?;
+
     let ix = deposit_sol(
         ctx.accounts.stake_pool_program.key,
         ctx.accounts.stake_pool.key,
@@ -1,6 +1,6 @@
-use std::collections::HashMap;
 
 use crate::{constants::*, error::GlamError, state::*};
+use crate::instructions::delegate_limits::{apply_delegate_limits, DelegateUsageAccount};
 use anchor_lang::{prelude::*, solana_program, system_program};
 use anchor_spl::{
     token::{close_account as close_token_account, CloseAccount as CloseTokenAccount, Token},
@@ -95,8 +95,19 @@ pub fn initialize_state_handler<'c: 'info, 'info>(
     state.enabled = model.enabled.unwrap_or(true);
     state.assets = model.assets.unwrap_or_default();
     state.integrations = model.integrations.unwrap_or_default();
-    state.delegate_acls = model.delegate_acls.unwrap_or_default();
     state.params = vec![vec![]];
+    state.set_delegate_permissions(
+        model
+            .delegate_acls
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|da| !da.permissions.is_empty())
+            .map(DelegatePermissions::from)
+            .collect(),
+    );
+    if let Some(timelock_duration) = model.timelock_duration {
+        state.set_timelock_duration(timelock_duration);
+    }
 
     msg!("State account created: {}", ctx.accounts.glam_state.key());
     Ok(())
@@ -115,8 +126,26 @@ pub fn update_state_handler<'c: 'info, 'info>(
     ctx: Context<'_, '_, 'c, 'info, UpdateState<'info>>,
     state_model: StateModel,
 ) -> Result<()> {
+    let state_key = ctx.accounts.glam_state.key();
     let state = &mut ctx.accounts.glam_state;
 
+    // With a timelock, sensitive changes must go through queue_state_update
+    require!(
+        state.timelock_duration() == 0 || !state_model.has_timelocked_changes(state),
+        GlamError::TimelockActive
+    );
+
+    apply_state_model(state, state_key, state_model, ctx.remaining_accounts)
+}
+
+/// Applies the state model. Delegate limits are set in the delegate usage accounts,
+/// which must be in the remaining accounts.
+fn apply_state_model<'info>(
+    state: &mut StateAccount,
+    state_key: Pubkey,
+    state_model: StateModel,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
     if let Some(name) = state_model.name {
         require!(
             name.as_bytes().len() <= MAX_SIZE_NAME,
@@ -129,13 +158,27 @@ pub fn update_state_handler<'c: 'info, 'info>(
         state.uri = uri;
     }
 
+    // Ownership transfer is two-step: the new owner is only proposed here
+    // and takes over once it accepts (a typo can't lock the owner out)
     if let Some(manager_model) = state_model.owner {
         if let Some(manager) = manager_model.pubkey {
-            state.owner = manager
+            let new_owner = match manager_model.kind {
+                Some(ManagerKind::Squads) => get_squads_vault_pda(&manager),
+                _ => manager,
+            };
+            state.set_pending_owner(Some(new_owner));
+            msg!("Ownership transfer proposed to {}", new_owner);
         }
     }
 
     if let Some(assets) = state_model.assets {
+        // Launch prices are in base asset units
+        if assets.first() != state.assets.first() {
+            require!(
+                (0..state.mints.len()).all(|i| state.mint_launch_price(i).is_none()),
+                GlamError::BaseAssetLocked
+            );
+        }
         state.assets = assets;
     }
 
@@ -146,20 +189,76 @@ pub fn update_state_handler<'c: 'info, 'info>(
     // Update or add delegate acls
     // If permissions is empty, delete the entry
     if let Some(delegate_acls) = state_model.delegate_acls {
-        let mut existing_pubkeys: HashMap<_, _> = state
-            .delegate_acls
-            .iter()
-            .map(|da| (da.pubkey, da.clone()))
-            .collect();
-
+        let mut delegate_permissions = state.delegate_permissions();
         for da in delegate_acls {
-            existing_pubkeys.insert(da.pubkey, da.clone());
+            delegate_permissions.retain(|dp| dp.pubkey != da.pubkey);
+            if !da.permissions.is_empty() {
+                delegate_permissions.push(DelegatePermissions::from(da));
+            }
         }
+        state.set_delegate_permissions(delegate_permissions);
+    }
 
-        state.delegate_acls = existing_pubkeys
-            .into_values()
-            .filter(|da| !da.permissions.is_empty())
-            .collect();
+    // Update or add roles, by name
+    // If permissions is empty, delete the role
+    if let Some(roles) = state_model.roles {
+        let mut existing_roles = state.roles();
+        for role in roles {
+            require!(
+                role.name.as_bytes().len() <= MAX_SIZE_ROLE_NAME,
+                GlamError::InvalidRole
+            );
+            existing_roles.retain(|r| r.name != role.name);
+            if !role.permissions.is_empty() {
+                existing_roles.push(RolePermissions::from(role));
+            }
+        }
+
+        // Unassign deleted roles from delegates
+        let mut delegate_roles = state.delegate_roles();
+        for dr in delegate_roles.iter_mut() {
+            dr.roles
+                .retain(|name| existing_roles.iter().any(|r| r.name == *name));
+        }
+        delegate_roles.retain(|dr| !dr.roles.is_empty());
+
+        state.set_roles(existing_roles);
+        state.set_delegate_roles(delegate_roles);
+    }
+
+    // Update or add delegate roles
+    // If roles is empty, delete the entry
+    if let Some(delegate_roles) = state_model.delegate_roles {
+        let role_names: Vec<String> = state.roles().into_iter().map(|r| r.name).collect();
+        let mut existing_delegate_roles = state.delegate_roles();
+        for dr in delegate_roles {
+            require!(
+                dr.roles.iter().all(|name| role_names.contains(name)),
+                GlamError::InvalidRole
+            );
+            existing_delegate_roles.retain(|d| d.pubkey != dr.pubkey);
+            if !dr.roles.is_empty() {
+                existing_delegate_roles.push(dr);
+            }
+        }
+        state.set_delegate_roles(existing_delegate_roles);
+    }
+
+    // Update or add delegate scopes
+    // If the scope is empty, delete the entry
+    if let Some(delegate_scopes) = state_model.delegate_scopes {
+        let mut existing_scopes = state.delegate_scopes();
+        for scope in delegate_scopes {
+            existing_scopes.retain(|s| s.pubkey != scope.pubkey);
+            if !scope.is_empty() {
+                existing_scopes.push(scope);
+            }
+        }
+        state.set_delegate_scopes(existing_scopes);
+    }
+
+    if let Some(guardian) = state_model.guardian {
+        state.set_guardian(Some(guardian).filter(|g| *g != Pubkey::default()));
     }
 
     if let Some(market_indexes_perp) = state_model.drift_market_indexes_perp {
@@ -214,6 +313,225 @@ pub fn update_state_handler<'c: 'info, 'info>(
         }
     }
 
+    if let Some(timelock_duration) = state_model.timelock_duration {
+        state.set_timelock_duration(timelock_duration);
+    }
+
+    if let Some(risk_limits) = state_model.risk_limits {
+        for bps in [
+            risk_limits.max_asset_weight_bps,
+            risk_limits.max_lst_weight_bps,
+            risk_limits.min_base_asset_weight_bps,
+        ] {
+            require!(bps <= 10_000, GlamError::InvalidRiskLimits);
+        }
+        state.set_risk_limits(Some(risk_limits).filter(|limits| !limits.is_empty()));
+    }
+
+    if let Some(drift_limits) = state_model.drift_limits {
+        require!(
+            drift_limits.max_leverage_bps == 0 || drift_limits.max_leverage_bps >= 10_000,
+            GlamError::InvalidDriftLimits
+        );
+        require!(
+            drift_limits.min_custom_margin_ratio <= 10_000,
+            GlamError::InvalidDriftLimits
+        );
+        state.set_drift_limits(Some(drift_limits).filter(|limits| !limits.is_empty()));
+    }
+
+    if let Some(enabled) = state_model.subscribe_redeem_enabled {
+        set_subscribe_redeem_enabled(state, enabled);
+    }
+
+    for limits in state_model.delegate_limits.unwrap_or_default() {
+        let (delegate_usage_key, _) = Pubkey::find_program_address(
+            &[
+                SEED_DELEGATE_USAGE.as_bytes(),
+                state_key.as_ref(),
+                limits.delegate.as_ref(),
+            ],
+            &crate::ID,
+        );
+        let delegate_usage_info = remaining_accounts
+            .iter()
+            .find(|account| account.key == &delegate_usage_key && account.is_writable)
+            .ok_or(GlamError::InvalidDelegateUsage)?;
+        let mut delegate_usage = Account::<DelegateUsageAccount>::try_from(delegate_usage_info)?;
+        apply_delegate_limits(
+            state,
+            state_key,
+            &mut delegate_usage,
+            limits.delegate,
+            limits.max_notional_per_tx,
+            limits.max_notional_per_day,
+        );
+        delegate_usage.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
+
+/// State update queued by the owner of a vault with a timelock. It can be applied
+/// once `executable_at` is reached, giving investors a window to redeem before.
+#[account]
+pub struct PendingStateUpdateAccount {
+    pub glam_state: Pubkey,
+    pub queued_at: i64,
+    pub executable_at: i64,
+    pub state_model: StateModel,
+}
+impl PendingStateUpdateAccount {
+    pub const INIT_SIZE: usize = 32 + 8 + 8; // + serialized state_model
+}
+
+#[derive(Accounts)]
+#[instruction(state_model: StateModel)]
+pub struct QueueStateUpdate<'info> {
+    #[account(constraint = glam_state.owner == glam_signer.key() @ GlamError::NotAuthorized)]
+    pub glam_state: Account<'info, StateAccount>,
+
+    #[account(
+        init,
+        seeds = [SEED_PENDING_STATE_UPDATE.as_bytes(), glam_state.key().as_ref()],
+        bump,
+        payer = glam_signer,
+        space = 8 + PendingStateUpdateAccount::INIT_SIZE + state_model.try_to_vec().unwrap_or_default().len()
+    )]
+    pub pending_state_update: Account<'info, PendingStateUpdateAccount>,
+
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn queue_state_update_handler(
+    ctx: Context<QueueStateUpdate>,
+    state_model: StateModel,
+) -> Result<()> {
+    let timelock_duration = ctx.accounts.glam_state.timelock_duration();
+    require!(timelock_duration > 0, GlamError::NoTimelock);
+
+    let queued_at = Clock::get()?.unix_timestamp;
+    let pending_state_update = &mut ctx.accounts.pending_state_update;
+    pending_state_update.glam_state = ctx.accounts.glam_state.key();
+    pending_state_update.queued_at = queued_at;
+    pending_state_update.executable_at = queued_at.saturating_add(timelock_duration);
+    pending_state_update.state_model = state_model;
+
+    msg!(
+        "State update queued, executable at {}",
+        pending_state_update.executable_at
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ApplyStateUpdate<'info> {
+    #[account(mut, constraint = glam_state.owner == glam_signer.key() @ GlamError::NotAuthorized)]
+    pub glam_state: Account<'info, StateAccount>,
+
+    #[account(
+        mut,
+        close = glam_signer,
+        seeds = [SEED_PENDING_STATE_UPDATE.as_bytes(), glam_state.key().as_ref()],
+        bump
+    )]
+    pub pending_state_update: Account<'info, PendingStateUpdateAccount>,
+
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+}
+
+pub fn apply_state_update_handler<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, ApplyStateUpdate<'info>>,
+) -> Result<()> {
+    let pending_state_update = &ctx.accounts.pending_state_update;
+    require!(
+        Clock::get()?.unix_timestamp >= pending_state_update.executable_at,
+        GlamError::TimelockNotExpired
+    );
+
+    let state_key = ctx.accounts.glam_state.key();
+    apply_state_model(
+        &mut ctx.accounts.glam_state,
+        state_key,
+        pending_state_update.state_model.clone(),
+        ctx.remaining_accounts,
+    )?;
+
+    msg!("State update applied");
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelStateUpdate<'info> {
+    #[account(constraint = glam_state.owner == glam_signer.key() @ GlamError::NotAuthorized)]
+    pub glam_state: Account<'info, StateAccount>,
+
+    #[account(
+        mut,
+        close = glam_signer,
+        seeds = [SEED_PENDING_STATE_UPDATE.as_bytes(), glam_state.key().as_ref()],
+        bump
+    )]
+    pub pending_state_update: Account<'info, PendingStateUpdateAccount>,
+
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+}
+
+pub fn cancel_state_update_handler(_ctx: Context<CancelStateUpdate>) -> Result<()> {
+    msg!("State update cancelled");
+    Ok(())
+}
+
+/// Default vault (index 0) of a Squads v4 multisig. Only the Squads program can sign
+/// for it, by executing a transaction approved by the multisig members.
+fn get_squads_vault_pda(multisig: &Pubkey) -> Pubkey {
+    let (vault, _) = Pubkey::find_program_address(
+        &[b"multisig", multisig.as_ref(), b"vault", &[0u8]],
+        &SQUADS_PROGRAM,
+    );
+    vault
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    #[account(mut, constraint = glam_state.pending_owner() == Some(glam_signer.key()) @ GlamError::NotAuthorized)]
+    pub glam_state: Account<'info, StateAccount>,
+
+    pub glam_signer: Signer<'info>,
+}
+
+pub fn accept_ownership_handler(ctx: Context<AcceptOwnership>) -> Result<()> {
+    let state = &mut ctx.accounts.glam_state;
+    state.owner = ctx.accounts.glam_signer.key();
+    state.set_pending_owner(None);
+
+    msg!("Ownership transferred to {}", state.owner);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelOwnershipTransfer<'info> {
+    #[account(
+        mut,
+        constraint = glam_state.owner == glam_signer.key()
+            || glam_state.pending_owner() == Some(glam_signer.key()) @ GlamError::NotAuthorized
+    )]
+    pub glam_state: Account<'info, StateAccount>,
+
+    pub glam_signer: Signer<'info>,
+}
+
+pub fn cancel_ownership_transfer_handler(ctx: Context<CancelOwnershipTransfer>) -> Result<()> {
+    let state = &mut ctx.accounts.glam_state;
+    require!(state.pending_owner().is_some(), GlamError::NoPendingOwner);
+    state.set_pending_owner(None);
+
+    msg!("Ownership transfer cancelled");
     Ok(())
 }
 
@@ -268,7 +586,7 @@ pub fn close_state_handler(ctx: Context<CloseState>) -> Result<()> {
 
 #[derive(Accounts)]
 pub struct SetSubscribeRedeemEnabled<'info> {
-    #[account(mut, constraint = glam_state.owner == glam_signer.key() @ GlamError::NotAuthorized)]
+    #[account(mut)]
     pub glam_state: Box<Account<'info, StateAccount>>,
 
     #[account(mut)]
@@ -280,16 +598,96 @@ pub fn set_subscribe_redeem_enabled_handler(
     enabled: bool,
 ) -> Result<()> {
     let state = &mut ctx.accounts.glam_state;
+    let signer = ctx.accounts.glam_signer.key();
 
     if enabled {
-        state.delete_from_engine_field(EngineFieldName::ExternalVaultAccounts, system_program::ID);
+        // The guardian and delegates can only pause
+        require!(state.owner == signer, GlamError::NotAuthorized);
+        set_subscribe_redeem_enabled(state, true);
+        return Ok(());
+    }
+
+    if state.guardian() != Some(signer) {
+        acl::check_access(state, &signer, Permission::PauseSubscribeRedeem)?;
+    }
+
+    if state.timelock_duration() == 0 {
+        set_subscribe_redeem_enabled(state, false);
     } else {
-        let external_accounts =
-            state.get_pubkeys_from_engine_field(EngineFieldName::ExternalVaultAccounts);
+        // With a timelock, investors must be able to redeem until queued updates apply,
+        // so only subscriptions are paused here and pausing redemptions must be queued
+        state.set_subscriptions_paused(true);
+    }
+    Ok(())
+}
 
-        if !external_accounts.contains(&system_program::ID) {
-            state.add_to_engine_field(EngineFieldName::ExternalVaultAccounts, system_program::ID);
-        }
+fn set_subscribe_redeem_enabled(state: &mut StateAccount, enabled: bool) {
+    state.set_subscriptions_paused(false);
+
+    // The system program in the external vault accounts disables subscriptions and redemptions
+    if enabled {
+        state.delete_from_engine_field(EngineFieldName::ExternalVaultAccounts, system_program::ID);
+    } else if !state
+        .get_pubkeys_from_engine_field(EngineFieldName::ExternalVaultAccounts)
+        .contains(&system_program::ID)
+    {
+        state.add_to_engine_field(EngineFieldName::ExternalVaultAccounts, system_program::ID);
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetDelegatesPaused<'info> {
+    #[account(
+        mut,
+        constraint = glam_state.owner == glam_signer.key()
+            || glam_state.guardian() == Some(glam_signer.key()) @ GlamError::NotAuthorized
+    )]
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    #[account(mut)]
+    pub glam_signer: Signer<'info>,
+}
+
+pub fn set_delegates_paused_handler(ctx: Context<SetDelegatesPaused>, paused: bool) -> Result<()> {
+    let state = &mut ctx.accounts.glam_state;
+
+    // The guardian can only pause
+    require!(
+        paused || state.owner == ctx.accounts.glam_signer.key(),
+        GlamError::NotAuthorized
+    );
+    state.set_delegates_paused(paused);
+
+    msg!("Delegate permissions paused: {}", paused);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateStateMetadata<'info> {
+    #[account(mut)]
+    pub glam_state: Box<Account<'info, StateAccount>>,
+
+    pub glam_signer: Signer<'info>,
+}
+
+#[access_control(acl::check_access(&ctx.accounts.glam_state, &ctx.accounts.glam_signer.key, Permission::UpdateMetadata))]
+pub fn update_state_metadata_handler(
+    ctx: Context<UpdateStateMetadata>,
+    name: Option<String>,
+    uri: Option<String>,
+) -> Result<()> {
+    let state = &mut ctx.accounts.glam_state;
+
+    if let Some(name) = name {
+        require!(
+            name.as_bytes().len() <= MAX_SIZE_NAME,
+            GlamError::InvalidName
+        );
+        state.name = name;
+    }
+    if let Some(uri) = uri {
+        require!(uri.as_bytes().len() <= MAX_SIZE_URI, GlamError::InvalidUri);
+        state.uri = uri;
     }
 
     Ok(())
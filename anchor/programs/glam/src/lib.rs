@@ -7,7 +7,7 @@ pub mod state;
 pub mod utils;
 
 use anchor_lang::prelude::*;
-use cpi_autogen::{drift::*, jupiter_gov::*, jupiter_vote::*, kamino_lending::*, meteora_dlmm::*};
+use cpi_autogen::{drift::*, jupiter_gov::*, jupiter_vote::*, kamino_lending::*};
 use instructions::{state as glam_state, *};
 
 pub use constants::*;
@@ -45,6 +45,12 @@ pub mod glam {
 
     /// Updates an existing state account with new parameters.
     ///
+    /// Setting `owner` proposes an ownership transfer, completed by `accept_ownership`.
+    /// If the vault has a timelock, changes to the owner, assets, integrations, delegates,
+    /// guardian, Drift markets and order types, limits, and to the timelock itself must be
+    /// queued with `queue_state_update` instead, as must pausing subscriptions and redemptions.
+    /// Delegate limits are set in the delegate usage accounts, passed as remaining accounts.
+    ///
     /// # Parameters
     /// - `ctx`: The context for the instruction.
     /// - `state`: An instance of `StateModel` containing the updated details of the state.
@@ -58,6 +64,96 @@ pub mod glam {
         glam_state::update_state_handler(ctx, state)
     }
 
+    /// Queues a state update, applied with `apply_state_update` once the vault timelock
+    /// has expired. Only one update can be queued at a time.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `state`: An instance of `StateModel` containing the updated details of the state.
+    ///
+    /// # Permission required
+    /// - Owner only, delegates not allowed
+    pub fn queue_state_update(ctx: Context<QueueStateUpdate>, state: StateModel) -> Result<()> {
+        glam_state::queue_state_update_handler(ctx, state)
+    }
+
+    /// Applies the queued state update, once the timelock has expired.
+    ///
+    /// The usage accounts of the delegates with queued limits must be passed as remaining accounts.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    ///
+    /// # Permission required
+    /// - Owner only, delegates not allowed
+    pub fn apply_state_update<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ApplyStateUpdate<'info>>,
+    ) -> Result<()> {
+        glam_state::apply_state_update_handler(ctx)
+    }
+
+    /// Cancels the queued state update.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    ///
+    /// # Permission required
+    /// - Owner only, delegates not allowed
+    pub fn cancel_state_update(ctx: Context<CancelStateUpdate>) -> Result<()> {
+        glam_state::cancel_state_update_handler(ctx)
+    }
+
+    /// Accepts a pending ownership transfer, proposed by the owner with `update_state`.
+    ///
+    /// For Squads owners, the signer is the default vault of the multisig.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    ///
+    /// # Permission required
+    /// - Pending owner only
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        glam_state::accept_ownership_handler(ctx)
+    }
+
+    /// Cancels a pending ownership transfer.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    ///
+    /// # Permission required
+    /// - Owner or pending owner
+    pub fn cancel_ownership_transfer(ctx: Context<CancelOwnershipTransfer>) -> Result<()> {
+        glam_state::cancel_ownership_transfer_handler(ctx)
+    }
+
+    /// Sets the notional limits of a delegate, enforced in swap, drift and staking instructions.
+    ///
+    /// Notionals are in USD with 6 decimals. Setting both limits to 0 removes them.
+    /// If the vault has a timelock, relaxing the limits must be queued with `queue_state_update`.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `delegate`: The delegate to limit.
+    /// - `max_notional_per_tx`: Max notional per transaction, 0 for no limit.
+    /// - `max_notional_per_day`: Max notional over a rolling 24h window, 0 for no limit.
+    ///
+    /// # Permission required
+    /// - Owner only, delegates not allowed
+    pub fn set_delegate_limits(
+        ctx: Context<SetDelegateLimits>,
+        delegate: Pubkey,
+        max_notional_per_tx: u64,
+        max_notional_per_day: u64,
+    ) -> Result<()> {
+        delegate_limits::set_delegate_limits_handler(
+            ctx,
+            delegate,
+            max_notional_per_tx,
+            max_notional_per_day,
+        )
+    }
+
     /// Closes a state account and releases its resources.
     ///
     /// # Parameters
@@ -72,13 +168,15 @@ pub mod glam {
     /// Enables or disables the subscribe and redeem functionality.
     ///
     /// This allows the owner to pause/unpause subscription and redemption of a fund.
+    /// If the vault has a timelock, pausing only pauses subscriptions; pausing redemptions
+    /// must be queued with `queue_state_update`.
     ///
     /// # Parameters
     /// - `ctx`: The context for the instruction.
     /// - `enabled`: A boolean indicating whether to enable or disable the subscribe and redeem functionality.
     ///
     /// # Permission required
-    /// - Owner only, delegates not allowed
+    /// - Owner, or guardian or Permission::PauseSubscribeRedeem to disable only
     pub fn set_subscribe_redeem_enabled(
         ctx: Context<SetSubscribeRedeemEnabled>,
         enabled: bool,
@@ -86,6 +184,53 @@ pub mod glam {
         glam_state::set_subscribe_redeem_enabled_handler(ctx, enabled)
     }
 
+    /// Pauses or unpauses all delegate permissions.
+    ///
+    /// While paused, only the owner can act, and the guardian can cancel drift orders.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `paused`: Whether to pause delegate permissions.
+    ///
+    /// # Permission required
+    /// - Owner, or guardian to pause only
+    pub fn set_delegates_paused(ctx: Context<SetDelegatesPaused>, paused: bool) -> Result<()> {
+        glam_state::set_delegates_paused_handler(ctx, paused)
+    }
+
+    /// Updates the name and uri of a state, without any other change.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `name`: The new name, if any.
+    /// - `uri`: The new uri, if any.
+    ///
+    /// # Permission required
+    /// - Permission::UpdateMetadata
+    pub fn update_state_metadata(
+        ctx: Context<UpdateStateMetadata>,
+        name: Option<String>,
+        uri: Option<String>,
+    ) -> Result<()> {
+        glam_state::update_state_metadata_handler(ctx, name, uri)
+    }
+
+    /// Checks the portfolio weights of the vault against its risk limits.
+    ///
+    /// Vaults with risk limits require this instruction after swaps and withdraws, in the same
+    /// transaction. Drift and Kamino deposits are exempt, their positions aren't part of the AUM.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    ///
+    /// # Permission required
+    /// - Any signer
+    pub fn check_risk_limits<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, CheckRiskLimits<'info>>,
+    ) -> Result<()> {
+        risk_limits::check_risk_limits_handler(ctx)
+    }
+
     /// Closes token accounts owned by the vault.
     ///
     /// # Parameters
@@ -139,6 +284,44 @@ pub mod glam {
         mint::update_mint_handler(ctx, mint_id, mint_model)
     }
 
+    /// Adds and removes members of the allowlist of a mint.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `mint_id`: The id of the mint.
+    /// - `add`: The pubkeys to add to the allowlist.
+    /// - `remove`: The pubkeys to remove from the allowlist.
+    ///
+    /// # Permission required
+    /// - Permission::ManageAllowlist
+    pub fn update_mint_allowlist(
+        ctx: Context<UpdateMintList>,
+        mint_id: u8,
+        add: Vec<Pubkey>,
+        remove: Vec<Pubkey>,
+    ) -> Result<()> {
+        mint::update_mint_allowlist_handler(ctx, mint_id, add, remove)
+    }
+
+    /// Adds and removes members of the blocklist of a mint.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `mint_id`: The id of the mint.
+    /// - `add`: The pubkeys to add to the blocklist.
+    /// - `remove`: The pubkeys to remove from the blocklist.
+    ///
+    /// # Permission required
+    /// - Permission::ManageBlocklist
+    pub fn update_mint_blocklist(
+        ctx: Context<UpdateMintList>,
+        mint_id: u8,
+        add: Vec<Pubkey>,
+        remove: Vec<Pubkey>,
+    ) -> Result<()> {
+        mint::update_mint_blocklist_handler(ctx, mint_id, add, remove)
+    }
+
     /// Closes a mint and releases its resources.
     ///
     /// # Parameters
@@ -229,17 +412,60 @@ pub mod glam {
 
     /// Subscribes to a specified amount of shares.
     ///
+    /// The signer pays for the subscription, the shares are minted to the beneficiary
+    /// (which can be the signer itself). Allowlist, blocklist, KYC and lock-up apply
+    /// to the beneficiary, which must also sign if the share class has a lock-up.
+    /// To subscribe wSOL with native SOL, omit `signer_asset_ata`:
+    /// the lamports are wrapped into the vault wSOL account.
+    ///
+    /// The first subscription of a share class must mint at least `MIN_INITIAL_SHARES`,
+    /// of which `DEAD_SHARES` are minted to `dead_shares_ata` and can never be redeemed.
+    ///
     /// # Parameters
     /// - `ctx`: The context for the instruction.
     /// - `amount`: The amount of shares to subscribe.
+    /// - `min_shares_out`: Optional minimum amount of shares to receive, or the subscription
+    ///   fails (slippage bound).
     /// - `skip_state`: Should always be true (state check to be implemented).
     pub fn subscribe<'c: 'info, 'info>(
         ctx: Context<'_, '_, 'c, 'info, Subscribe<'info>>,
         mint_id: u8,
         amount: u64,
+        min_shares_out: Option<u64>,
         skip_state: bool,
     ) -> Result<()> {
-        investor::subscribe_handler(ctx, mint_id, amount, skip_state)
+        investor::subscribe_handler(ctx, mint_id, amount, min_shares_out, skip_state)
+    }
+
+    /// Subscribes in kind with a basket of assets.
+    ///
+    /// Each leg of the basket is valued with the same pricing used to compute AUM,
+    /// and shares are minted for the total value of the basket.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `mint_id`: The id of the share class.
+    /// - `amounts`: The amount of each asset in `state.assets` to subscribe with, 0 to skip.
+    /// - `match_weights`: Whether the basket must match the current portfolio weights.
+    /// - `min_shares_out`: Optional minimum amount of shares to receive, or the subscription
+    ///   fails (slippage bound).
+    /// - `skip_state`: Should always be true (state check to be implemented).
+    pub fn subscribe_in_kind<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, SubscribeInKind<'info>>,
+        mint_id: u8,
+        amounts: Vec<u64>,
+        match_weights: bool,
+        min_shares_out: Option<u64>,
+        skip_state: bool,
+    ) -> Result<()> {
+        investor::subscribe_in_kind_handler(
+            ctx,
+            mint_id,
+            amounts,
+            match_weights,
+            min_shares_out,
+            skip_state,
+        )
     }
 
     /// Redeems a specified amount of shares.
@@ -247,15 +473,128 @@ pub mod glam {
     /// # Parameters
     /// - `ctx`: The context for the instruction.
     /// - `amount`: The amount of shares to redeem.
-    /// - `in_kind`: Whether to redeem in kind.
+    /// - `in_kind`: Whether to redeem in kind. Vault stake accounts are split pro rata into
+    ///   new stake accounts provided by the signer (one per vault stake account, at the end of
+    ///   remaining accounts), and their staker and withdrawer authorities assigned to the signer.
+    ///   The part of a stake account that can't be split (below the minimum delegation) is paid
+    ///   in wSOL instead, if the vault holds enough.
+    /// - `output_assets`: For cash redemptions, the assets to redeem into, in order of priority.
+    ///   If the vault balance of an asset is insufficient, the next one is used.
+    ///   Defaults to the base asset if empty.
+    /// - `min_amounts_out`: Optional minimum amount of each asset in `state.assets` to receive,
+    ///   or the redemption fails (slippage bound).
     /// - `skip_state`: Should always be true (state check to be implemented).
+    ///
+    /// To receive wSOL as native SOL, pass the signer in place of its wSOL token account
+    /// in remaining accounts, together with the `wsol_unwrap` account.
+    ///
+    /// Redeeming all shares but the dead shares (`dead_shares_ata`) redeems the whole
+    /// vault and burns the dead shares.
     pub fn redeem<'c: 'info, 'info>(
         ctx: Context<'_, '_, 'c, 'info, Redeem<'info>>,
         amount: u64,
         in_kind: bool,
+        output_assets: Vec<Pubkey>,
+        min_amounts_out: Option<Vec<u64>>,
         skip_state: bool,
     ) -> Result<()> {
-        investor::redeem_handler(ctx, amount, in_kind, skip_state)
+        investor::redeem_handler(ctx, amount, in_kind, output_assets, min_amounts_out, skip_state)
+    }
+
+    /// Issues (or renews) a KYC attestation for a wallet.
+    ///
+    /// Share classes that use GLAM attestations for KYC set the issuer as gatekeeper network.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `subject`: The wallet the attestation is issued to.
+    /// - `expires_at`: Expiration unix timestamp, 0 if the attestation never expires.
+    ///
+    /// # Permission required
+    /// - Issuer only
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        subject: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        kyc::issue_attestation_handler(ctx, subject, expires_at)
+    }
+
+    /// Revokes a KYC attestation and closes the attestation account.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    ///
+    /// # Permission required
+    /// - Issuer only
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        kyc::revoke_attestation_handler(ctx)
+    }
+
+    /// Creates a compliance list, a set of pubkeys that can be shared by many share classes.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `id`: Seed for computing the compliance list PDA.
+    /// - `list_type`: Whether the list is an allowlist or a blocklist.
+    ///
+    /// # Permission required
+    /// - Compliance list owner
+    pub fn create_compliance_list(
+        ctx: Context<CreateComplianceList>,
+        id: [u8; 8],
+        list_type: ComplianceListType,
+    ) -> Result<()> {
+        compliance::create_compliance_list_handler(ctx, id, list_type)
+    }
+
+    /// Updates the pubkeys and/or the merkle root of a compliance list.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `added`: Pubkeys to add to the list.
+    /// - `removed`: Pubkeys to remove from the list.
+    /// - `merkle_root`: New merkle root for large allowlists, all zeros to disable it.
+    ///   Blocklists can't use a merkle root.
+    ///
+    /// # Permission required
+    /// - Compliance list owner
+    pub fn update_compliance_list(
+        ctx: Context<UpdateComplianceList>,
+        added: Vec<Pubkey>,
+        removed: Vec<Pubkey>,
+        merkle_root: Option<[u8; 32]>,
+    ) -> Result<()> {
+        compliance::update_compliance_list_handler(ctx, added, removed, merkle_root)
+    }
+
+    /// Closes a compliance list, once no share class references it.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    ///
+    /// # Permission required
+    /// - Compliance list owner
+    pub fn close_compliance_list(ctx: Context<CloseComplianceList>) -> Result<()> {
+        compliance::close_compliance_list_handler(ctx)
+    }
+
+    /// Proves that a pubkey is in the merkle tree of a compliance list,
+    /// and records it so that subscribe and transfers can check it.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `member`: The pubkey to prove membership for.
+    /// - `proof`: The merkle proof.
+    ///
+    /// # Permission required
+    /// - None, anyone can submit a valid proof
+    pub fn prove_compliance_membership(
+        ctx: Context<ProveComplianceMembership>,
+        member: Pubkey,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        compliance::prove_compliance_membership_handler(ctx, member, proof)
     }
 
     /// Initializes a drift account owned by vault and creates a subaccount.
@@ -280,7 +619,7 @@ pub mod glam {
         cpi_autogen::drift::drift_initialize_user(ctx, sub_account_id, name)
     }
 
-    /// Updates custom margin ratio.
+    /// Updates custom margin ratio. It can't be below the minimum of the vault's drift limits.
     ///
     /// # Parameters
     /// - `ctx`: The context for the instruction.
@@ -297,7 +636,7 @@ pub mod glam {
         sub_account_id: u16,
         margin_ratio: u32,
     ) -> Result<()> {
-        cpi_autogen::drift::drift_update_user_custom_margin_ratio(ctx, sub_account_id, margin_ratio)
+        drift::drift_update_user_custom_margin_ratio(ctx, sub_account_id, margin_ratio)
     }
 
     /// Enables/Disables margin trading.
@@ -324,7 +663,8 @@ pub mod glam {
         )
     }
 
-    /// Sets a delegate on the specified sub account.
+    /// Sets a delegate on the specified sub account. Only the owner can set it if the vault has
+    /// drift limits or delegate scopes, the drift delegate trades outside of them.
     ///
     /// # Parameters
     /// - `ctx`: The context for the instruction.
@@ -332,7 +672,7 @@ pub mod glam {
     /// - `delegate`: Delegate's wallet address.
     ///
     /// # Permission required
-    /// - Permission::DriftUpdateUser
+    /// - Permission::DriftUpdateUser, owner only with drift limits or delegate scopes
     ///
     /// # Integration required
     /// - Integration::Drift
@@ -341,7 +681,7 @@ pub mod glam {
         sub_account_id: u16,
         delegate: Pubkey,
     ) -> Result<()> {
-        cpi_autogen::drift::drift_update_user_delegate(ctx, sub_account_id, delegate)
+        drift::drift_update_user_delegate(ctx, sub_account_id, delegate)
     }
 
     /// Deposits to drift.
@@ -362,7 +702,7 @@ pub mod glam {
         amount: u64,
         reduce_only: bool,
     ) -> Result<()> {
-        cpi_autogen::drift::drift_deposit(ctx, market_index, amount, reduce_only)
+        drift::drift_deposit(ctx, market_index, amount, reduce_only)
     }
 
     /// Withdraws from drift.
@@ -383,7 +723,7 @@ pub mod glam {
         amount: u64,
         reduce_only: bool,
     ) -> Result<()> {
-        cpi_autogen::drift::drift_withdraw(ctx, market_index, amount, reduce_only)
+        drift::drift_withdraw(ctx, market_index, amount, reduce_only)
     }
 
     /// Deletes a drift user (sub account).
@@ -401,6 +741,7 @@ pub mod glam {
     }
 
     /// Places orders on drift.
+    /// Perp order sizes and the user's custom margin ratio are checked against the vault's drift limits.
     ///
     /// # Parameters
     /// - `ctx`: The context for the instruction.
@@ -419,7 +760,7 @@ pub mod glam {
         drift::drift_place_orders(ctx, params)
     }
 
-    /// Modifies an existing drift order.
+    /// Modifies an existing drift order, checked against the vault's drift limits like new orders.
     ///
     /// # Parameters
     /// - `ctx`: The context for the instruction.
@@ -436,7 +777,7 @@ pub mod glam {
         order_id: Option<u32>,
         modify_order_params: ModifyOrderParams,
     ) -> Result<()> {
-        cpi_autogen::drift::drift_modify_order(ctx, order_id, modify_order_params)
+        drift::drift_modify_order(ctx, order_id, modify_order_params)
     }
 
     /// Cancels drift orders.
@@ -448,7 +789,7 @@ pub mod glam {
     /// - `direction`: The direction of orders to cancel (long or short).
     ///
     /// # Permission required
-    /// - Permission::DriftCancelOrders
+    /// - Permission::DriftCancelOrders, or guardian
     ///
     /// # Integration required
     /// - Integration::Drift
@@ -468,7 +809,7 @@ pub mod glam {
     /// - `order_ids`: A list of order IDs.
     ///
     /// # Permission required
-    /// - Permission::DriftCancelOrders
+    /// - Permission::DriftCancelOrders, or guardian
     ///
     /// # Integration required
     /// - Integration::Drift
@@ -867,7 +1208,7 @@ pub mod glam {
     pub fn jupiter_vote_withdraw_partial_unstaking<'info>(
         ctx: Context<JupiterVoteWithdrawPartialUnstaking>,
     ) -> Result<()> {
-        cpi_autogen::jupiter_vote::jupiter_vote_withdraw_partial_unstaking(ctx)
+        jupiter_vote::jupiter_vote_withdraw_partial_unstaking(ctx)
     }
 
     /// Withdraws all unstaked JUP.
@@ -881,7 +1222,7 @@ pub mod glam {
     /// # Integration required
     /// - Integration::JupiterVote
     pub fn jupiter_vote_withdraw<'info>(ctx: Context<JupiterVoteWithdraw>) -> Result<()> {
-        cpi_autogen::jupiter_vote::jupiter_vote_withdraw(ctx)
+        jupiter_vote::jupiter_vote_withdraw(ctx)
     }
 
     /// Creates a new vote.
@@ -957,14 +1298,17 @@ pub mod glam {
         ctx: Context<KaminoLendingInitObligationFarmsForReserve>,
         mode: u8,
     ) -> Result<()> {
-        cpi_autogen::kamino_lending::kamino_lending_init_obligation_farms_for_reserve(ctx, mode)
+        kamino_lending::kamino_lending_init_obligation_farms_for_reserve(ctx, mode)
     }
 
     pub fn kamino_lending_deposit_reserve_liquidity_and_obligation_collateral<'info>(
         ctx: Context<KaminoLendingDepositReserveLiquidityAndObligationCollateral>,
         liquidity_amount: u64,
     ) -> Result<()> {
-        cpi_autogen::kamino_lending::kamino_lending_deposit_reserve_liquidity_and_obligation_collateral(ctx, liquidity_amount)
+        kamino_lending::kamino_lending_deposit_reserve_liquidity_and_obligation_collateral(
+            ctx,
+            liquidity_amount,
+        )
     }
 
     pub fn meteora_dlmm_initialize_position<'info>(
@@ -972,24 +1316,24 @@ pub mod glam {
         lower_bin_id: i32,
         width: i32,
     ) -> Result<()> {
-        cpi_autogen::meteora_dlmm::meteora_dlmm_initialize_position(ctx, lower_bin_id, width)
+        meteora_dlmm::meteora_dlmm_initialize_position(ctx, lower_bin_id, width)
     }
 
     pub fn meteora_dlmm_close_position<'info>(
         ctx: Context<MeteoraDlmmClosePosition>,
     ) -> Result<()> {
-        cpi_autogen::meteora_dlmm::meteora_dlmm_close_position(ctx)
+        meteora_dlmm::meteora_dlmm_close_position(ctx)
     }
 
     pub fn meteora_dlmm_claim_fee<'info>(ctx: Context<MeteoraDlmmClaimFee>) -> Result<()> {
-        cpi_autogen::meteora_dlmm::meteora_dlmm_claim_fee(ctx)
+        meteora_dlmm::meteora_dlmm_claim_fee(ctx)
     }
 
     pub fn meteora_dlmm_add_liquidity_by_strategy<'info>(
         ctx: Context<MeteoraDlmmAddLiquidityByStrategy>,
         params: LiquidityParameterByStrategy,
     ) -> Result<()> {
-        cpi_autogen::meteora_dlmm::meteora_dlmm_add_liquidity_by_strategy(ctx, params)
+        meteora_dlmm::meteora_dlmm_add_liquidity_by_strategy(ctx, params)
     }
 
     pub fn meteora_dlmm_remove_liquidity_by_range<'info>(
@@ -998,7 +1342,7 @@ pub mod glam {
         to_bin_id: i32,
         bps_to_remove: u16,
     ) -> Result<()> {
-        cpi_autogen::meteora_dlmm::meteora_dlmm_remove_liquidity_by_range(
+        meteora_dlmm::meteora_dlmm_remove_liquidity_by_range(
             ctx,
             from_bin_id,
             to_bin_id,
@@ -1011,7 +1355,7 @@ pub mod glam {
         amount_in: u64,
         min_amount_out: u64,
     ) -> Result<()> {
-        cpi_autogen::meteora_dlmm::meteora_dlmm_swap(ctx, amount_in, min_amount_out)
+        meteora_dlmm::meteora_dlmm_swap(ctx, amount_in, min_amount_out)
     }
 
     /// Wraps SOL to get wSOL.
@@ -1049,11 +1393,64 @@ pub mod glam {
         wsol::unwrap_handler(ctx)
     }
 
+    /// Sets the policy of the instructions of a program that delegates can call with `execute_cpi`.
+    ///
+    /// If the vault has a timelock, the new policy can only be used once it expires.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `program_id`: The program the policy is for.
+    /// - `instructions`: The allowed instructions, by discriminator, with their account slot constraints.
+    ///
+    /// # Permission required
+    /// - Owner only, delegates not allowed
+    pub fn set_cpi_policy(
+        ctx: Context<SetCpiPolicy>,
+        program_id: Pubkey,
+        instructions: Vec<InstructionPolicy>,
+    ) -> Result<()> {
+        cpi_policy::set_cpi_policy_handler(ctx, program_id, instructions)
+    }
+
+    /// Closes the policy of a program, delegates can no longer call it with `execute_cpi`.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `program_id`: The program the policy is for.
+    ///
+    /// # Permission required
+    /// - Owner only, delegates not allowed
+    pub fn close_cpi_policy(ctx: Context<CloseCpiPolicy>, program_id: Pubkey) -> Result<()> {
+        cpi_policy::close_cpi_policy_handler(ctx, program_id)
+    }
+
+    /// Calls a program with the remaining accounts, signed by the vault, if allowed by the
+    /// policy of the program.
+    ///
+    /// # Parameters
+    /// - `ctx`: The context for the instruction.
+    /// - `data`: The instruction data.
+    ///
+    /// # Permission required
+    /// - Permission::ExecuteCpi
+    ///
+    /// # Integration required
+    /// - Integration::ExecuteCpi
+    pub fn execute_cpi<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ExecuteCpi<'info>>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        cpi_policy::execute_cpi_handler(ctx, data)
+    }
+
     //
     // Policy Transfer Hook
     //
     #[interface(spl_transfer_hook_interface::execute)]
-    pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
+    pub fn transfer_hook<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, TransferHook<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         policy_hook::execute(ctx, amount)
     }
 }
@@ -15,6 +15,22 @@ pub enum EngineFieldName {
     DriftMarketIndexesSpot,
     DriftOrderTypes,
     MaxSwapSlippageBps,
+    KycGatewayProgram,    // share class
+    KycGatekeeperNetwork, // share class
+    ComplianceLists,      // share class
+    LaunchPrice,          // share class
+    PendingOwner,
+    TimelockDuration,
+    LimitedDelegates, // delegates with notional limits
+    Roles,
+    DelegateRoles,
+    DelegateScopes,
+    Guardian,
+    DelegatesPaused,
+    RiskLimits,
+    DriftLimits,
+    SubscriptionsPaused,
+    DelegatePermissions,
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
@@ -35,6 +51,13 @@ pub enum EngineFieldValue {
     Timestamp { val: i64 },
     VecPubkey { val: Vec<Pubkey> },
     VecU32 { val: Vec<u32> },
+    VecRole { val: Vec<RolePermissions> },
+    VecDelegateRoles { val: Vec<DelegateRoles> },
+    VecDelegateScope { val: Vec<DelegateScope> },
+    RiskLimits { val: RiskLimits },
+    DriftLimits { val: DriftLimits },
+    U32 { val: u32 },
+    VecDelegatePermissions { val: Vec<DelegatePermissions> },
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
@@ -43,6 +66,42 @@ pub struct EngineField {
     pub value: EngineFieldValue,
 }
 
+/// Rust type of an engine field value, see `StateAccount::get_engine_field`.
+pub trait EngineFieldType: Sized {
+    fn from_value(value: &EngineFieldValue) -> Option<Self>;
+    fn into_value(self) -> EngineFieldValue;
+}
+
+macro_rules! impl_engine_field_type {
+    ($type:ty, $variant:ident) => {
+        impl EngineFieldType for $type {
+            fn from_value(value: &EngineFieldValue) -> Option<Self> {
+                match value {
+                    EngineFieldValue::$variant { val } => Some(val.to_owned()),
+                    _ => None,
+                }
+            }
+
+            fn into_value(self) -> EngineFieldValue {
+                EngineFieldValue::$variant { val: self }
+            }
+        }
+    };
+}
+
+impl_engine_field_type!(bool, Boolean);
+impl_engine_field_type!(u32, U32);
+impl_engine_field_type!(u64, U64);
+impl_engine_field_type!(i64, Timestamp);
+impl_engine_field_type!(Pubkey, Pubkey);
+impl_engine_field_type!(Vec<Pubkey>, VecPubkey);
+impl_engine_field_type!(Vec<RolePermissions>, VecRole);
+impl_engine_field_type!(Vec<DelegatePermissions>, VecDelegatePermissions);
+impl_engine_field_type!(Vec<DelegateRoles>, VecDelegateRoles);
+impl_engine_field_type!(Vec<DelegateScope>, VecDelegateScope);
+impl_engine_field_type!(RiskLimits, RiskLimits);
+impl_engine_field_type!(DriftLimits, DriftLimits);
+
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
 pub enum AccountType {
     Vault,
@@ -64,6 +123,75 @@ pub struct Metadata {
     pub uri: String,
 }
 
+/// Portfolio limits of the vault, in bps of the AUM, 0 = no limit.
+/// Evaluated with `check_risk_limits` after swaps and withdraws. Drift and Kamino deposits
+/// aren't checked, positions in these protocols aren't valued in the AUM.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq)]
+pub struct RiskLimits {
+    pub max_asset_weight_bps: u16, // any asset but the base asset
+    pub max_lst_weight_bps: u16,   // all LSTs
+    pub min_base_asset_weight_bps: u16,
+}
+
+impl RiskLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_asset_weight_bps == 0
+            && self.max_lst_weight_bps == 0
+            && self.min_base_asset_weight_bps == 0
+    }
+
+    /// Whether the new limits are looser than the current ones.
+    pub fn relaxes(&self, new: &RiskLimits) -> bool {
+        relaxes_max(self.max_asset_weight_bps, new.max_asset_weight_bps)
+            || relaxes_max(self.max_lst_weight_bps, new.max_lst_weight_bps)
+            || new.min_base_asset_weight_bps < self.min_base_asset_weight_bps
+    }
+}
+
+/// Drift guardrails of the vault, 0 = no limit.
+/// Checked before placing and modifying orders, and updating the custom margin ratio.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, Debug, PartialEq)]
+pub struct DriftLimits {
+    pub max_order_base_asset_amount: u64, // perp orders, base precision (1e9)
+    pub max_leverage_bps: u32,            // 10_000 = 1x
+    pub min_custom_margin_ratio: u32,     // drift margin precision (1e4)
+}
+
+impl DriftLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_order_base_asset_amount == 0
+            && self.max_leverage_bps == 0
+            && self.min_custom_margin_ratio == 0
+    }
+
+    /// Min custom margin ratio of the drift users. Drift checks the initial margin
+    /// of the user against it, which bounds the leverage to 1 / margin ratio.
+    pub fn min_margin_ratio(&self) -> u32 {
+        let leverage_margin_ratio = if self.max_leverage_bps > 0 {
+            // 1e4 (margin precision) * 1e4 (bps) / leverage, rounded up
+            (100_000_000u64 + self.max_leverage_bps as u64 - 1) / self.max_leverage_bps as u64
+        } else {
+            0
+        };
+        self.min_custom_margin_ratio.max(leverage_margin_ratio as u32)
+    }
+
+    /// Whether the new limits are looser than the current ones.
+    pub fn relaxes(&self, new: &DriftLimits) -> bool {
+        relaxes_max(
+            self.max_order_base_asset_amount,
+            new.max_order_base_asset_amount,
+        ) || relaxes_max(self.max_leverage_bps, new.max_leverage_bps)
+            || new.min_custom_margin_ratio < self.min_custom_margin_ratio
+    }
+}
+
+/// Whether `new` is looser than `current`, for a max limit where 0 = no limit
+fn relaxes_max<T: Into<u64>>(current: T, new: T) -> bool {
+    let (current, new) = (current.into(), new.into());
+    current > 0 && (new == 0 || new > current)
+}
+
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
 pub struct CreatedModel {
     pub key: [u8; 8],       // seed for computing state PDA
@@ -84,7 +212,7 @@ pub struct StateAccount {
     pub name: String,
     pub uri: String,
     pub assets: Vec<Pubkey>,
-    pub delegate_acls: Vec<DelegateAcl>,
+    pub delegate_acls: Vec<DelegateAcl>, // original layout, see delegate_permissions()
     pub integrations: Vec<Integration>,
 
     // params[0]: state params
@@ -110,6 +238,33 @@ impl StateAccount {
             .unwrap_or(0)
     }
 
+    // return the share price at launch, in base asset units, if set
+    pub fn mint_launch_price(&self, mint_id: usize) -> Option<u64> {
+        self.get_engine_field::<u64>(mint_id + 1, EngineFieldName::LaunchPrice)
+            .filter(|launch_price| *launch_price > 0)
+    }
+
+    pub fn mint_kyc_gateway(&self, mint_id: usize) -> Option<(Pubkey, Pubkey)> {
+        let find_pubkey = |field_name: EngineFieldName| {
+            self.params.get(mint_id + 1).and_then(|params| {
+                params
+                    .iter()
+                    .find(|EngineField { name, .. }| *name == field_name)
+                    .and_then(|EngineField { value, .. }| match value {
+                        EngineFieldValue::Pubkey { val } => Some(*val),
+                        _ => None,
+                    })
+            })
+        };
+        find_pubkey(EngineFieldName::KycGatewayProgram)
+            .zip(find_pubkey(EngineFieldName::KycGatekeeperNetwork))
+    }
+
+    pub fn mint_compliance_lists(&self, mint_id: usize) -> Vec<Pubkey> {
+        self.get_engine_field(mint_id + 1, EngineFieldName::ComplianceLists)
+            .unwrap_or_default()
+    }
+
     pub fn mint_allowlist(&self, mint_id: usize) -> Option<&Vec<Pubkey>> {
         self.params.get(mint_id + 1).and_then(|params| {
             params
@@ -194,6 +349,166 @@ impl StateAccount {
         })
     }
 
+    /// Value of the engine field `field_name` in `params[params_idx]`
+    /// (0 for the state, mint_id + 1 for a share class)
+    pub fn get_engine_field<T: EngineFieldType>(
+        &self,
+        params_idx: usize,
+        field_name: EngineFieldName,
+    ) -> Option<T> {
+        self.params.get(params_idx).and_then(|params| {
+            params
+                .iter()
+                .find(|EngineField { name, .. }| *name == field_name)
+                .and_then(|EngineField { value, .. }| T::from_value(value))
+        })
+    }
+
+    /// Sets the engine field `field_name` of the state params, or removes it if `value` is None
+    pub fn set_engine_field<T: EngineFieldType>(
+        &mut self,
+        field_name: EngineFieldName,
+        value: Option<T>,
+    ) {
+        self.params[0].retain(|field| field.name != field_name);
+        if let Some(value) = value {
+            self.params[0].push(EngineField {
+                name: field_name,
+                value: value.into_value(),
+            });
+        }
+    }
+
+    // return the proposed new owner, if an ownership transfer is pending
+    pub fn pending_owner(&self) -> Option<Pubkey> {
+        self.get_engine_field(0, EngineFieldName::PendingOwner)
+    }
+
+    pub fn set_pending_owner(&mut self, pending_owner: Option<Pubkey>) {
+        self.set_engine_field(EngineFieldName::PendingOwner, pending_owner);
+    }
+
+    // return the timelock of sensitive state updates in s. 0 == no timelock (default).
+    // Older states store it as a Timestamp.
+    pub fn timelock_duration(&self) -> i64 {
+        self.get_engine_field::<u32>(0, EngineFieldName::TimelockDuration)
+            .map(i64::from)
+            .or_else(|| self.get_engine_field::<i64>(0, EngineFieldName::TimelockDuration))
+            .filter(|duration| *duration > 0)
+            .unwrap_or(0)
+    }
+
+    pub fn set_timelock_duration(&mut self, timelock_duration: u32) {
+        self.set_engine_field(
+            EngineFieldName::TimelockDuration,
+            (timelock_duration > 0).then_some(timelock_duration),
+        );
+    }
+
+    // extra permissions of delegates, besides roles. States not updated since
+    // permissions are bitsets still have them in `delegate_acls`.
+    pub fn delegate_permissions(&self) -> Vec<DelegatePermissions> {
+        self.get_engine_field(0, EngineFieldName::DelegatePermissions)
+            .unwrap_or_else(|| {
+                self.delegate_acls
+                    .iter()
+                    .cloned()
+                    .map(DelegatePermissions::from)
+                    .collect()
+            })
+    }
+
+    // migrates the delegate permissions out of `delegate_acls`
+    pub fn set_delegate_permissions(&mut self, delegate_permissions: Vec<DelegatePermissions>) {
+        self.delegate_acls.clear();
+        self.set_engine_field(
+            EngineFieldName::DelegatePermissions,
+            Some(delegate_permissions).filter(|dps| !dps.is_empty()),
+        );
+    }
+
+    // roles (named sets of permissions) assignable to delegates
+    pub fn roles(&self) -> Vec<RolePermissions> {
+        self.get_engine_field(0, EngineFieldName::Roles)
+            .unwrap_or_default()
+    }
+
+    pub fn set_roles(&mut self, roles: Vec<RolePermissions>) {
+        self.set_engine_field(
+            EngineFieldName::Roles,
+            (!roles.is_empty()).then_some(roles),
+        );
+    }
+
+    pub fn delegate_roles(&self) -> Vec<DelegateRoles> {
+        self.get_engine_field(0, EngineFieldName::DelegateRoles)
+            .unwrap_or_default()
+    }
+
+    pub fn set_delegate_roles(&mut self, delegate_roles: Vec<DelegateRoles>) {
+        self.set_engine_field(
+            EngineFieldName::DelegateRoles,
+            (!delegate_roles.is_empty()).then_some(delegate_roles),
+        );
+    }
+
+    pub fn delegate_scopes(&self) -> Vec<DelegateScope> {
+        self.get_engine_field(0, EngineFieldName::DelegateScopes)
+            .unwrap_or_default()
+    }
+
+    pub fn set_delegate_scopes(&mut self, delegate_scopes: Vec<DelegateScope>) {
+        self.set_engine_field(
+            EngineFieldName::DelegateScopes,
+            (!delegate_scopes.is_empty()).then_some(delegate_scopes),
+        );
+    }
+
+    // return the guardian, who can pause the vault but not trade
+    pub fn guardian(&self) -> Option<Pubkey> {
+        self.get_engine_field(0, EngineFieldName::Guardian)
+    }
+
+    pub fn set_guardian(&mut self, guardian: Option<Pubkey>) {
+        self.set_engine_field(EngineFieldName::Guardian, guardian);
+    }
+
+    // return true if all delegate permissions are paused
+    pub fn delegates_paused(&self) -> bool {
+        self.get_engine_field(0, EngineFieldName::DelegatesPaused)
+            .unwrap_or(false)
+    }
+
+    pub fn set_delegates_paused(&mut self, paused: bool) {
+        self.set_engine_field(EngineFieldName::DelegatesPaused, paused.then_some(true));
+    }
+
+    // return true if only subscriptions are paused, redemptions are still open
+    pub fn subscriptions_paused(&self) -> bool {
+        self.get_engine_field(0, EngineFieldName::SubscriptionsPaused)
+            .unwrap_or(false)
+    }
+
+    pub fn set_subscriptions_paused(&mut self, paused: bool) {
+        self.set_engine_field(EngineFieldName::SubscriptionsPaused, paused.then_some(true));
+    }
+
+    pub fn risk_limits(&self) -> Option<RiskLimits> {
+        self.get_engine_field(0, EngineFieldName::RiskLimits)
+    }
+
+    pub fn set_risk_limits(&mut self, risk_limits: Option<RiskLimits>) {
+        self.set_engine_field(EngineFieldName::RiskLimits, risk_limits);
+    }
+
+    pub fn drift_limits(&self) -> Option<DriftLimits> {
+        self.get_engine_field(0, EngineFieldName::DriftLimits)
+    }
+
+    pub fn set_drift_limits(&mut self, drift_limits: Option<DriftLimits>) {
+        self.set_engine_field(EngineFieldName::DriftLimits, drift_limits);
+    }
+
     pub fn add_to_engine_field(&mut self, engine_field_name: EngineFieldName, pubkey: Pubkey) {
         let mut engine_field = self.params[0]
             .iter_mut()
@@ -1,13 +1,16 @@
 use anchor_lang::prelude::*;
 
-use super::{AccountType, StateAccount};
+use super::{AccountType, EngineField, EngineFieldName, EngineFieldValue, StateAccount};
 use crate::error::GlamError;
 use spl_stake_pool::ID as SPL_STAKE_POOL_PROGRAM_ID;
 
 /**
  * Delegate ACL
+ *
+ * Delegate and role permissions are stored onchain as u64 bitsets, bit `i` being the
+ * i-th variant. New variants must be appended (max 64) and become `Permission::LAST`.
  */
-#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Debug)]
 pub enum Permission {
     DriftInitialize,
     DriftUpdateUser,
@@ -40,6 +43,26 @@ pub enum Permission {
     MeteoraDlmmClosePosition,
     MeteoraDlmmLiquidity,
     MeteoraDlmmSwap,
+    ManageAllowlist,      // Add/remove share class allowlist members
+    ManageBlocklist,      // Add/remove share class blocklist members
+    PauseSubscribeRedeem, // Disable subscribe and redeem, only the owner can re-enable
+    UpdateMetadata,       // Update state name and uri
+    ExecuteCpi,           // Call programs allowed by a CPI policy
+}
+
+// Bitsets are u64, `mask()` would overflow past 64 variants
+const _: () = assert!((Permission::LAST as u8) < 64);
+
+impl Permission {
+    pub const LAST: Permission = Permission::ExecuteCpi;
+
+    pub fn mask(self) -> u64 {
+        1u64 << (self as u8)
+    }
+
+    pub fn to_mask(permissions: &[Permission]) -> u64 {
+        permissions.iter().fold(0, |mask, p| mask | p.mask())
+    }
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
@@ -49,6 +72,77 @@ pub struct DelegateAcl {
     pub expires_at: i64, // Unix timestamp in seconds, 0 means no expiration
 }
 
+/// Onchain storage of a `DelegateAcl`
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
+pub struct DelegatePermissions {
+    pub pubkey: Pubkey,
+    pub permissions: u64, // bitset of Permission
+    pub expires_at: i64,
+}
+
+impl From<DelegateAcl> for DelegatePermissions {
+    fn from(acl: DelegateAcl) -> Self {
+        DelegatePermissions {
+            pubkey: acl.pubkey,
+            permissions: Permission::to_mask(&acl.permissions),
+            expires_at: acl.expires_at,
+        }
+    }
+}
+
+/// Named set of permissions, defined once on the state and assigned to delegates
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
+pub struct Role {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
+/// Onchain storage of a `Role`
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
+pub struct RolePermissions {
+    pub name: String,
+    pub permissions: u64, // bitset of Permission
+}
+
+impl From<Role> for RolePermissions {
+    fn from(role: Role) -> Self {
+        RolePermissions {
+            name: role.name,
+            permissions: Permission::to_mask(&role.permissions),
+        }
+    }
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
+pub struct DelegateRoles {
+    pub pubkey: Pubkey,
+    pub roles: Vec<String>, // role names
+}
+
+/// Restrictions of a delegate, enforced on top of the vault-wide ones.
+/// An empty list doesn't restrict.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug, PartialEq)]
+pub struct DelegateScope {
+    pub pubkey: Pubkey,
+    pub assets: Vec<Pubkey>, // swap input and output mints
+    pub drift_market_indexes_perp: Vec<u32>,
+    pub drift_market_indexes_spot: Vec<u32>,
+    pub drift_order_types: Vec<u32>,
+    pub meteora_pairs: Vec<Pubkey>, // DLMM lb pairs
+    pub kamino_reserves: Vec<Pubkey>,
+}
+
+impl DelegateScope {
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+            && self.drift_market_indexes_perp.is_empty()
+            && self.drift_market_indexes_spot.is_empty()
+            && self.drift_order_types.is_empty()
+            && self.meteora_pairs.is_empty()
+            && self.kamino_reserves.is_empty()
+    }
+}
+
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Debug)]
 pub enum Integration {
     Drift,
@@ -60,6 +154,57 @@ pub enum Integration {
     JupiterVote, // Jupiter Vote
     KaminoLending,
     MeteoraDlmm,
+    ExecuteCpi, // Programs called through CPI policies
+}
+
+fn find_engine_field<'a>(
+    state: &'a StateAccount,
+    engine_field_name: EngineFieldName,
+) -> Option<&'a EngineFieldValue> {
+    state.params.get(0).and_then(|params| {
+        params
+            .iter()
+            .find(|EngineField { name, .. }| *name == engine_field_name)
+            .map(|EngineField { value, .. }| value)
+    })
+}
+
+/// Permissions bitset of a delegate: its extra permissions, and the permissions
+/// of its roles. Reads the state in place, without allocating.
+///
+/// States not updated since permissions are bitsets still have them in `delegate_acls`,
+/// with the original layout (a list of permissions), and are folded into the bitset here.
+pub fn get_delegate_permissions(state: &StateAccount, signer: &Pubkey) -> u64 {
+    let mut permissions = match find_engine_field(state, EngineFieldName::DelegatePermissions) {
+        Some(EngineFieldValue::VecDelegatePermissions { val }) => val
+            .iter()
+            .find(|dp| dp.pubkey == *signer)
+            .map_or(0, |dp| dp.permissions),
+        _ => state
+            .delegate_acls
+            .iter()
+            .find(|acl| acl.pubkey == *signer)
+            .map_or(0, |acl| Permission::to_mask(&acl.permissions)),
+    };
+
+    let delegate_roles = match find_engine_field(state, EngineFieldName::DelegateRoles) {
+        Some(EngineFieldValue::VecDelegateRoles { val }) => {
+            val.iter().find(|dr| dr.pubkey == *signer)
+        }
+        _ => None,
+    };
+    if let (Some(delegate_roles), Some(EngineFieldValue::VecRole { val: roles })) = (
+        delegate_roles,
+        find_engine_field(state, EngineFieldName::Roles),
+    ) {
+        for role in roles {
+            if delegate_roles.roles.contains(&role.name) {
+                permissions |= role.permissions;
+            }
+        }
+    }
+
+    permissions
 }
 
 pub fn check_access(state: &StateAccount, signer: &Pubkey, permission: Permission) -> Result<()> {
@@ -67,6 +212,13 @@ pub fn check_access(state: &StateAccount, signer: &Pubkey, permission: Permissio
         return Ok(());
     }
 
+    // The guardian can cancel drift orders, even with delegates paused
+    if permission == Permission::DriftCancelOrders && state.guardian() == Some(*signer) {
+        return Ok(());
+    }
+
+    require!(!state.delegates_paused(), GlamError::DelegatesPaused);
+
     #[cfg(not(feature = "mainnet"))]
     msg!(
         "Checking signer {:?} has permission {:?}",
@@ -74,10 +226,8 @@ pub fn check_access(state: &StateAccount, signer: &Pubkey, permission: Permissio
         permission
     );
 
-    for acl in state.delegate_acls.clone() {
-        if acl.pubkey == *signer && acl.permissions.contains(&permission) {
-            return Ok(());
-        }
+    if get_delegate_permissions(state, signer) & permission.mask() != 0 {
+        return Ok(());
     }
 
     return Err(GlamError::NotAuthorized.into());
@@ -86,12 +236,14 @@ pub fn check_access(state: &StateAccount, signer: &Pubkey, permission: Permissio
 pub fn check_access_any(
     state: &StateAccount,
     signer: &Pubkey,
-    allowed_permissions: Vec<Permission>,
+    allowed_permissions: &[Permission],
 ) -> Result<()> {
     if state.owner == *signer {
         return Ok(());
     }
 
+    require!(!state.delegates_paused(), GlamError::DelegatesPaused);
+
     #[cfg(not(feature = "mainnet"))]
     msg!(
         "Checking signer {:?} has any of {:?}",
@@ -99,20 +251,46 @@ pub fn check_access_any(
         allowed_permissions
     );
 
-    for acl in state.delegate_acls.clone() {
-        if acl.pubkey == *signer
-            && acl
-                .permissions
-                .iter()
-                .any(|p| allowed_permissions.contains(p))
-        {
-            return Ok(());
-        }
+    if get_delegate_permissions(state, signer) & Permission::to_mask(allowed_permissions) != 0 {
+        return Ok(());
     }
 
     return Err(GlamError::NotAuthorized.into());
 }
 
+/// Scope of the signer, None if it's the owner or an unscoped delegate
+pub fn get_delegate_scope<'a>(state: &'a StateAccount, signer: &Pubkey) -> Option<&'a DelegateScope> {
+    if state.owner == *signer {
+        return None;
+    }
+    match find_engine_field(state, EngineFieldName::DelegateScopes) {
+        Some(EngineFieldValue::VecDelegateScope { val }) => {
+            val.iter().find(|scope| scope.pubkey == *signer)
+        }
+        _ => None,
+    }
+}
+
+pub fn in_scope<T: PartialEq>(allowed: &[T], item: &T) -> bool {
+    allowed.is_empty() || allowed.contains(item)
+}
+
+/// Checks `item` is in the list selected by `allowed` from the signer's scope, if any.
+pub fn check_delegate_scope<T: PartialEq + std::fmt::Debug>(
+    state: &StateAccount,
+    signer: &Pubkey,
+    item: &T,
+    allowed: fn(&DelegateScope) -> &Vec<T>,
+) -> Result<()> {
+    if let Some(scope) = get_delegate_scope(state, signer) {
+        #[cfg(not(feature = "mainnet"))]
+        msg!("Checking {:?} is in scope of signer {:?}", item, signer);
+
+        require!(in_scope(allowed(scope), item), GlamError::OutOfDelegateScope);
+    }
+    Ok(())
+}
+
 pub fn check_state_type(state: &StateAccount, accont_type: AccountType) -> Result<()> {
     #[cfg(not(feature = "mainnet"))]
     msg!("Checking state account type {:?}", accont_type);
@@ -146,3 +324,65 @@ pub fn check_stake_pool_integration(
     };
     check_integration(state, integration)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn baseline_delegate_acls() {
+        let delegate = Pubkey::new_unique();
+
+        // State account serialized with the original layout, delegate acls as lists of permissions
+        let mut data = StateAccount::DISCRIMINATOR.to_vec();
+        data.push(0); // account_type: Vault
+        data.extend_from_slice(Pubkey::new_unique().as_ref()); // owner
+        data.extend_from_slice(Pubkey::new_unique().as_ref()); // vault
+        data.push(1); // enabled
+        data.extend_from_slice(&[0u8; 8]); // created.key
+        data.extend_from_slice(Pubkey::new_unique().as_ref()); // created.created_by
+        data.extend_from_slice(&0i64.to_le_bytes()); // created.created_at
+        data.extend_from_slice(Pubkey::default().as_ref()); // engine
+        data.extend_from_slice(&0u32.to_le_bytes()); // mints
+        data.push(0); // metadata: None
+        data.extend_from_slice(&4u32.to_le_bytes()); // name
+        data.extend_from_slice(b"Test");
+        data.extend_from_slice(&0u32.to_le_bytes()); // uri
+        data.extend_from_slice(&0u32.to_le_bytes()); // assets
+        data.extend_from_slice(&1u32.to_le_bytes()); // delegate_acls
+        data.extend_from_slice(delegate.as_ref());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.push(Permission::DriftDeposit as u8);
+        data.push(Permission::WSolUnwrap as u8);
+        data.extend_from_slice(&0i64.to_le_bytes()); // expires_at
+        data.extend_from_slice(&0u32.to_le_bytes()); // integrations
+        data.extend_from_slice(&1u32.to_le_bytes()); // params
+        data.extend_from_slice(&0u32.to_le_bytes()); // params[0]
+
+        let mut state = StateAccount::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(
+            state.delegate_acls[0].permissions,
+            vec![Permission::DriftDeposit, Permission::WSolUnwrap]
+        );
+        assert_eq!(
+            get_delegate_permissions(&state, &delegate),
+            Permission::DriftDeposit.mask() | Permission::WSolUnwrap.mask()
+        );
+        assert!(check_access(&state, &delegate, Permission::WSolUnwrap).is_ok());
+        assert!(check_access(&state, &delegate, Permission::WSolWrap).is_err());
+
+        // and serializes back to the same layout
+        let mut serialized = Vec::new();
+        state.try_serialize(&mut serialized).unwrap();
+        assert_eq!(serialized, data);
+
+        // and migrates to bitsets on the next update
+        state.set_delegate_permissions(state.delegate_permissions());
+        assert!(state.delegate_acls.is_empty());
+        assert_eq!(
+            get_delegate_permissions(&state, &delegate),
+            Permission::DriftDeposit.mask() | Permission::WSolUnwrap.mask()
+        );
+    }
+}
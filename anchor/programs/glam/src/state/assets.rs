@@ -14,6 +14,7 @@ pub const MAXIMUM_AGE: u64 = 60; // One minute
 pub enum Action {
     Subscribe,
     Redeem,
+    SubscribeInKind,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -108,7 +109,9 @@ impl<'a> AssetMeta<'a> {
 
         // Scale price to expected decimals
         let asset_expo = -(self.decimals as i32);
-        asset_price = asset_price.scale_to_exponent(asset_expo).unwrap();
+        asset_price = asset_price
+            .scale_to_exponent(asset_expo)
+            .ok_or(GlamError::InvalidAssetPrice)?;
 
         // Stable coin: return 1.0 if price is in (0.99..1.01)
         let one = 10i64.pow(self.decimals as u32);
@@ -116,7 +119,7 @@ impl<'a> AssetMeta<'a> {
         if self.is_stable_coin {
             if one.abs_diff(asset_price.price) < one_percent {
                 asset_price.price = one;
-            } else if action == Action::Subscribe {
+            } else if action != Action::Redeem {
                 return Err(GlamError::InvalidStableCoinPriceForSubscribe.into());
             }
         }
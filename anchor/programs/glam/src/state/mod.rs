@@ -13,4 +13,9 @@ pub use assets::*;
 pub mod acl;
 pub use acl::*;
 
+pub mod vault_guard;
+pub use vault_guard::*;
+
 pub mod pyth_price;
+
+pub mod nav;
@@ -24,17 +24,116 @@ pub struct StateModel {
     pub created: Option<CreatedModel>,
 
     // ACLs
-    pub delegate_acls: Option<Vec<DelegateAcl>>,
+    pub delegate_acls: Option<Vec<DelegateAcl>>, // extra permissions, besides roles
+    pub roles: Option<Vec<Role>>,
+    pub delegate_roles: Option<Vec<DelegateRoles>>,
+    pub delegate_scopes: Option<Vec<DelegateScope>>,
+    pub guardian: Option<Pubkey>, // Pubkey::default() removes the guardian
     pub integrations: Option<Vec<Integration>>,
     pub drift_market_indexes_perp: Option<Vec<u32>>,
     pub drift_market_indexes_spot: Option<Vec<u32>>,
     pub drift_order_types: Option<Vec<u32>>,
+    pub timelock_duration: Option<u32>, // seconds, 0 = no timelock
+    pub risk_limits: Option<RiskLimits>, // all zeros removes the limits
+    pub drift_limits: Option<DriftLimits>, // all zeros removes the limits
+    pub subscribe_redeem_enabled: Option<bool>,
+    pub delegate_limits: Option<Vec<DelegateLimits>>, // updates existing delegate usage accounts
 
     // Metadata
     pub metadata: Option<Metadata>,
     pub raw_openfunds: Option<FundOpenfundsModel>,
 }
 
+impl StateModel {
+    /// Whether the model changes fields that can put investor funds at new risk.
+    /// If the vault has a timelock, these changes must be queued. Changes that only
+    /// remove permissions or integrations, or tighten limits, apply immediately.
+    pub fn has_timelocked_changes(&self, state: &StateAccount) -> bool {
+        self.owner.as_ref().map_or(false, |owner| owner.pubkey.is_some())
+            || self.assets.is_some()
+            || self.integrations.as_ref().map_or(false, |integrations| {
+                integrations.iter().any(|i| !state.integrations.contains(i))
+            })
+            || self.delegate_acls.as_ref().map_or(false, |delegate_acls| {
+                delegate_acls.iter().any(|da| grants_delegate_acl(state, da))
+            })
+            || self.roles.as_ref().map_or(false, |roles| {
+                roles.iter().any(|role| grants_role(state, role))
+            })
+            || self.delegate_roles.as_ref().map_or(false, |delegate_roles| {
+                delegate_roles.iter().any(|dr| grants_delegate_roles(state, dr))
+            })
+            || self.delegate_scopes.is_some()
+            || self.guardian.is_some()
+            || self.drift_market_indexes_perp.is_some()
+            || self.drift_market_indexes_spot.is_some()
+            || self.drift_order_types.is_some()
+            || self.timelock_duration.is_some()
+            || self.risk_limits.map_or(false, |risk_limits| {
+                state
+                    .risk_limits()
+                    .map_or(false, |current| current.relaxes(&risk_limits))
+            })
+            || self.drift_limits.map_or(false, |drift_limits| {
+                state
+                    .drift_limits()
+                    .map_or(false, |current| current.relaxes(&drift_limits))
+            })
+            || self.subscribe_redeem_enabled == Some(false)
+            || self.delegate_limits.is_some()
+    }
+}
+
+/// Whether the delegate acl grants permissions the delegate doesn't have,
+/// or extends their expiration
+fn grants_delegate_acl(state: &StateAccount, delegate_acl: &DelegateAcl) -> bool {
+    let mask = Permission::to_mask(&delegate_acl.permissions);
+    mask != 0
+        && state
+            .delegate_permissions()
+            .iter()
+            .find(|dp| dp.pubkey == delegate_acl.pubkey)
+            .map_or(true, |current| {
+                let extends_expiration = current.expires_at > 0
+                    && (delegate_acl.expires_at == 0
+                        || delegate_acl.expires_at > current.expires_at);
+                extends_expiration || mask & !current.permissions != 0
+            })
+}
+
+/// Whether the role is new or gains permissions
+fn grants_role(state: &StateAccount, role: &Role) -> bool {
+    let mask = Permission::to_mask(&role.permissions);
+    mask != 0
+        && state
+            .roles()
+            .iter()
+            .find(|r| r.name == role.name)
+            .map_or(true, |current| mask & !current.permissions != 0)
+}
+
+/// Whether the delegate is assigned roles it doesn't have
+fn grants_delegate_roles(state: &StateAccount, delegate_roles: &DelegateRoles) -> bool {
+    let current = state
+        .delegate_roles()
+        .into_iter()
+        .find(|dr| dr.pubkey == delegate_roles.pubkey)
+        .map(|dr| dr.roles)
+        .unwrap_or_default();
+    delegate_roles
+        .roles
+        .iter()
+        .any(|name| !current.contains(name))
+}
+
+/// Notional limits of a delegate, as set by `set_delegate_limits`
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
+pub struct DelegateLimits {
+    pub delegate: Pubkey,
+    pub max_notional_per_tx: u64,  // 0 = no limit
+    pub max_notional_per_day: u64, // 0 = no limit
+}
+
 // Subset of the Openfunds v2 modeled by Glam
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
 pub struct FundOpenfundsModel {
@@ -73,15 +172,19 @@ pub struct MintModel {
     pub state_pubkey: Option<Pubkey>,
     pub asset: Option<Pubkey>,
     pub image_uri: Option<String>, // TODO: remove?
+    pub launch_price: Option<u64>, // share price at launch, in base asset units
 
     // Acls
     pub allowlist: Option<Vec<Pubkey>>,
     pub blocklist: Option<Vec<Pubkey>>,
+    pub compliance_lists: Option<Vec<Pubkey>>, // shared compliance list accounts
 
     // Policies
     pub lock_up_period_in_seconds: Option<i32>,
     pub permanent_delegate: Option<Pubkey>,
     pub default_account_state_frozen: Option<bool>,
+    pub kyc_gateway_program: Option<Pubkey>, // civic gateway or glam attestations
+    pub kyc_gatekeeper_network: Option<Pubkey>, // gatekeeper network or attestation issuer
 
     // Metadata
     pub is_raw_openfunds: Option<bool>,
@@ -190,7 +293,7 @@ pub struct CompanyModel {
 // Implemented:
 // - Openfunds fields: name
 // - Single manager, regular wallet
-// - Squads
+// - Squads (v4): pubkey is the multisig, the owner is its default vault
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Debug)]
 pub struct ManagerModel {
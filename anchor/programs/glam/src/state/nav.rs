@@ -0,0 +1,323 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::Price;
+use solana_program::stake::state::StakeStateV2;
+
+use crate::error::GlamError;
+
+//
+// Checked fixed-point math for NAV, share and asset amounts.
+//
+// Amounts, prices and values are unsigned integers scaled by 10^expo. Every
+// operation is checked: overflows, non-positive prices and out of range exponents
+// return an error instead of panicking. Divisions round in an explicit direction,
+// always in favour of the fund: shares minted and assets paid out are rounded down.
+//
+
+/// Exponent of the values computed for the AUM (in USD or SOL, depending on the base asset)
+pub const VALUE_EXPO: i32 = -12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+fn pow10(exp: i32) -> Result<u128> {
+    let exp = u32::try_from(exp).map_err(|_| GlamError::MathOverflow)?;
+    Ok(10u128.checked_pow(exp).ok_or(GlamError::MathOverflow)?)
+}
+
+/// Computes `a * b / c`, rounded.
+pub fn mul_div(a: u128, b: u128, c: u128, rounding: Rounding) -> Result<u128> {
+    require!(c > 0, GlamError::MathOverflow);
+    let product = a.checked_mul(b).ok_or(GlamError::MathOverflow)?;
+    let quotient = product / c;
+    if rounding == Rounding::Up && product % c != 0 {
+        return Ok(quotient.checked_add(1).ok_or(GlamError::MathOverflow)?);
+    }
+    Ok(quotient)
+}
+
+/// Rescales `value` from 10^`from_expo` to 10^`to_expo`, rounded.
+pub fn rescale(value: u128, from_expo: i32, to_expo: i32, rounding: Rounding) -> Result<u128> {
+    let diff = from_expo
+        .checked_sub(to_expo)
+        .ok_or(GlamError::MathOverflow)?;
+    if diff >= 0 {
+        Ok(value
+            .checked_mul(pow10(diff)?)
+            .ok_or(GlamError::MathOverflow)?)
+    } else {
+        mul_div(value, 1, pow10(-diff)?, rounding)
+    }
+}
+
+/// Computes `amount * shares / total_shares`, rounded (e.g. the amount of an asset
+/// paid out for redeemed shares).
+pub fn pro_rata(amount: u64, shares: u64, total_shares: u64, rounding: Rounding) -> Result<u64> {
+    to_u64(mul_div(
+        amount as u128,
+        shares as u128,
+        total_shares as u128,
+        rounding,
+    )?)
+}
+
+pub fn to_u64(value: u128) -> Result<u64> {
+    Ok(u64::try_from(value).map_err(|_| GlamError::MathOverflow)?)
+}
+
+fn positive_price(price: &Price) -> Result<u128> {
+    require!(price.price > 0, GlamError::InvalidAssetPrice);
+    Ok(price.price as u128)
+}
+
+/// Value in 10^`value_expo` of `amount` tokens with `decimals`, at `price`.
+pub fn value_of(
+    amount: u64,
+    decimals: u8,
+    price: &Price,
+    value_expo: i32,
+    rounding: Rounding,
+) -> Result<u128> {
+    if amount == 0 {
+        return Ok(0);
+    }
+    // amount * price is in 10^(price.exponent - decimals)
+    let product = (amount as u128)
+        .checked_mul(positive_price(price)?)
+        .ok_or(GlamError::MathOverflow)?;
+    let product_expo = price
+        .exponent
+        .checked_sub(decimals as i32)
+        .ok_or(GlamError::MathOverflow)?;
+    rescale(product, product_expo, value_expo, rounding)
+}
+
+/// Amount of tokens with `decimals` worth `value` (in 10^`value_expo`), at `price`.
+pub fn amount_of(
+    value: u128,
+    value_expo: i32,
+    price: &Price,
+    decimals: u8,
+    rounding: Rounding,
+) -> Result<u64> {
+    // amount = value * 10^(value_expo - price.exponent + decimals) / price
+    let expo = value_expo
+        .checked_sub(price.exponent)
+        .and_then(|expo| expo.checked_add(decimals as i32))
+        .ok_or(GlamError::MathOverflow)?;
+    let price = positive_price(price)?;
+    let amount = if expo >= 0 {
+        mul_div(value, pow10(expo)?, price, rounding)?
+    } else {
+        let denominator = price
+            .checked_mul(pow10(-expo)?)
+            .ok_or(GlamError::MathOverflow)?;
+        mul_div(value, 1, denominator, rounding)?
+    };
+    to_u64(amount)
+}
+
+/// Divides `price` by `quote` (e.g. USD price / SOL-USD price = SOL price),
+/// keeping the exponent of `price`.
+pub fn div_price(price: &Price, quote: &Price, rounding: Rounding) -> Result<Price> {
+    // p * 10^pe / (q * 10^qe) = p * 10^-qe / q, in 10^pe
+    let value = if quote.exponent <= 0 {
+        mul_div(
+            positive_price(price)?,
+            pow10(-quote.exponent)?,
+            positive_price(quote)?,
+            rounding,
+        )?
+    } else {
+        let denominator = positive_price(quote)?
+            .checked_mul(pow10(quote.exponent)?)
+            .ok_or(GlamError::MathOverflow)?;
+        mul_div(positive_price(price)?, 1, denominator, rounding)?
+    };
+    to_price(value, price, quote)
+}
+
+/// Multiplies `price` by `quote` (e.g. SOL price * SOL-USD price = USD price),
+/// keeping the exponent of `price`.
+pub fn mul_price(price: &Price, quote: &Price, rounding: Rounding) -> Result<Price> {
+    // p * 10^pe * q * 10^qe = p * q * 10^qe, in 10^pe
+    let product = positive_price(price)?
+        .checked_mul(positive_price(quote)?)
+        .ok_or(GlamError::MathOverflow)?;
+    let value = rescale(product, quote.exponent, 0, rounding)?;
+    to_price(value, price, quote)
+}
+
+fn to_price(value: u128, price: &Price, quote: &Price) -> Result<Price> {
+    Ok(Price {
+        price: i64::try_from(value).map_err(|_| GlamError::MathOverflow)?,
+        conf: 0,
+        exponent: price.exponent,
+        publish_time: price.publish_time.min(quote.publish_time),
+    })
+}
+
+/// Value in lamports of a stake account, from its delegation state.
+///
+/// Delegated stake is worth the lamports of the account whatever its delegation state
+/// (activating, active, deactivating or inactive). The stake program credits the
+/// rewards of an epoch at the epoch boundary, so the lamports include the rewards paid
+/// so far, and rewards accrued in the current epoch are excluded. Uninitialized
+/// accounts have no value.
+pub fn stake_value(lamports: u64, stake_state: &StakeStateV2) -> Result<u64> {
+    match stake_state {
+        StakeStateV2::Stake(_, stake, _) => {
+            require!(lamports >= stake.delegation.stake, GlamError::InvalidStakeAccount);
+            Ok(lamports)
+        }
+        StakeStateV2::Initialized(_) => Ok(lamports),
+        StakeStateV2::Uninitialized | StakeStateV2::RewardsPool => Ok(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::stake::state::{Delegation, Meta, Stake, StakeFlags};
+
+    const RENT: u64 = 2_282_880;
+    const SOL: u64 = 1_000_000_000;
+
+    fn price(price: i64, exponent: i32) -> Price {
+        Price {
+            price,
+            conf: 0,
+            exponent,
+            publish_time: 0,
+        }
+    }
+
+    #[test]
+    fn rounding_direction() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down).unwrap(), 3);
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up).unwrap(), 4);
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up).unwrap(), 3);
+
+        assert_eq!(pro_rata(100, 1, 3, Rounding::Down).unwrap(), 33);
+        assert_eq!(pro_rata(100, 1, 3, Rounding::Up).unwrap(), 34);
+
+        // 1 token with 6 decimals at 1/3 USD
+        let third = price(333_333_333, -9);
+        assert_eq!(
+            value_of(1_000_000, 6, &third, -6, Rounding::Down).unwrap(),
+            333_333
+        );
+        assert_eq!(
+            value_of(1_000_000, 6, &third, -6, Rounding::Up).unwrap(),
+            333_334
+        );
+        // 1 USD buys 3.000000003 tokens, paid out rounded down
+        assert_eq!(
+            amount_of(1_000_000_000, -9, &third, 9, Rounding::Down).unwrap(),
+            3_000_000_003
+        );
+        assert_eq!(
+            amount_of(1_000_000_000, -9, &third, 9, Rounding::Up).unwrap(),
+            3_000_000_004
+        );
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert!(mul_div(u128::MAX, 2, 1, Rounding::Down).is_err());
+        assert!(mul_div(1, 1, 0, Rounding::Down).is_err());
+        assert!(to_u64(u64::MAX as u128 + 1).is_err());
+        assert!(pro_rata(u64::MAX, 2, 1, Rounding::Down).is_err());
+        assert!(rescale(1, 39, 0, Rounding::Down).is_err());
+        assert!(rescale(u128::MAX, 1, 0, Rounding::Down).is_err());
+        let max_price = price(i64::MAX, 30);
+        assert!(value_of(u64::MAX, 0, &max_price, VALUE_EXPO, Rounding::Down).is_err());
+        assert!(amount_of(u128::MAX, 0, &price(1, -30), 0, Rounding::Down).is_err());
+        let max_price = price(i64::MAX, 0);
+        assert!(mul_price(&max_price, &max_price, Rounding::Down).is_err());
+
+        // non-positive prices
+        assert!(value_of(1, 0, &price(0, -8), VALUE_EXPO, Rounding::Down).is_err());
+        assert!(value_of(1, 0, &price(-1, -8), VALUE_EXPO, Rounding::Down).is_err());
+        assert!(div_price(&price(1, -8), &price(0, -8), Rounding::Down).is_err());
+    }
+
+    #[test]
+    fn expo_normalization() {
+        assert_eq!(rescale(1234, -6, -12, Rounding::Down).unwrap(), 1_234_000_000);
+        assert_eq!(rescale(1_234_567, -12, -6, Rounding::Down).unwrap(), 1);
+        assert_eq!(rescale(1_234_567, -12, -6, Rounding::Up).unwrap(), 2);
+        assert_eq!(rescale(5, 2, 0, Rounding::Down).unwrap(), 500);
+
+        // 1.5 tokens with 6 decimals at 2 USD, whatever the price exponent
+        for p in [price(2, 0), price(200, -2), price(200_000_000, -8)] {
+            assert_eq!(
+                value_of(1_500_000, 6, &p, VALUE_EXPO, Rounding::Down).unwrap(),
+                3_000_000_000_000
+            );
+            assert_eq!(
+                amount_of(3_000_000_000_000, VALUE_EXPO, &p, 6, Rounding::Down).unwrap(),
+                1_500_000
+            );
+        }
+        // positive price exponent
+        assert_eq!(
+            value_of(1_000, 3, &price(2, 3), 0, Rounding::Down).unwrap(),
+            2_000
+        );
+
+        // 150 USD / 100 USD = 1.5, in the exponent of the price
+        let usd = price(15_000, -2);
+        let sol_usd = price(1_000_000, -4);
+        let sol = div_price(&usd, &sol_usd, Rounding::Down).unwrap();
+        assert_eq!((sol.price, sol.exponent), (150, -2));
+        let usd = mul_price(&sol, &sol_usd, Rounding::Down).unwrap();
+        assert_eq!((usd.price, usd.exponent), (15_000, -2));
+    }
+
+    fn delegated(stake: u64, activation_epoch: u64, deactivation_epoch: u64) -> StakeStateV2 {
+        StakeStateV2::Stake(
+            Meta {
+                rent_exempt_reserve: RENT,
+                ..Meta::default()
+            },
+            Stake {
+                delegation: Delegation {
+                    stake,
+                    activation_epoch,
+                    deactivation_epoch,
+                    ..Delegation::default()
+                },
+                credits_observed: 0,
+            },
+            StakeFlags::empty(),
+        )
+    }
+
+    #[test]
+    fn stake_value_is_lamports_whatever_the_delegation_state() {
+        // 10 SOL delegated, rewards paid at past epoch boundaries
+        let lamports = 10 * SOL + RENT + 12_345;
+        let activating = delegated(10 * SOL, 10, u64::MAX);
+        let deactivating = delegated(10 * SOL, 5, 20);
+
+        for stake_state in [&activating, &deactivating] {
+            assert_eq!(stake_value(lamports, stake_state).unwrap(), lamports);
+        }
+    }
+
+    #[test]
+    fn stake_value_of_undelegated_accounts() {
+        let initialized = StakeStateV2::Initialized(Meta::default());
+        assert_eq!(stake_value(RENT + SOL, &initialized).unwrap(), RENT + SOL);
+        assert_eq!(stake_value(RENT, &StakeStateV2::Uninitialized).unwrap(), 0);
+    }
+
+    #[test]
+    fn stake_value_rejects_inconsistent_delegation() {
+        let stake_state = delegated(10 * SOL, 5, u64::MAX);
+        assert!(stake_value(SOL, &stake_state).is_err());
+    }
+}
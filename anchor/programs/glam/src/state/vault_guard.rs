@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::error::GlamError;
+
+//
+// Guard of the vault balances around CPIs signed by the vault.
+//
+// The accounts of most integrations are only validated by the target program, so
+// a bad account could move value out of the vault. The guard snapshots the vault
+// lamports and the vault token accounts passed to the CPI, and checks afterwards
+// that only the expected token accounts were debited, and that the vault keeps
+// control of its token accounts (same owner, delegate and close authority).
+//
+
+struct VaultTokenBalance {
+    key: Pubkey,
+    amount: u64,
+    delegate: Option<Pubkey>,
+    close_authority: Option<Pubkey>,
+}
+
+pub struct VaultBalanceGuard {
+    vault: Pubkey,
+    lamports: u64,
+    token_balances: Vec<VaultTokenBalance>,
+}
+
+impl VaultBalanceGuard {
+    /// Snapshots the vault lamports and the balances of the vault token accounts in `accounts`.
+    pub fn new(vault: &AccountInfo, accounts: &[AccountInfo]) -> Self {
+        let mut token_balances: Vec<VaultTokenBalance> = Vec::new();
+        for account in accounts.iter() {
+            if token_balances.iter().any(|b| b.key == *account.key) {
+                continue;
+            }
+            if let Some(token_account) = load_vault_token_account(account, vault.key) {
+                token_balances.push(VaultTokenBalance {
+                    key: *account.key,
+                    amount: token_account.amount,
+                    delegate: token_account.delegate.into(),
+                    close_authority: token_account.close_authority.into(),
+                });
+            }
+        }
+
+        Self {
+            vault: *vault.key,
+            lamports: vault.lamports(),
+            token_balances,
+        }
+    }
+
+    /// Checks the vault balances after the CPI: only the token accounts in `outflows`
+    /// can be debited, and the vault can spend at most `max_lamports_out`.
+    pub fn check(
+        &self,
+        vault: &AccountInfo,
+        accounts: &[AccountInfo],
+        outflows: &[Pubkey],
+        max_lamports_out: u64,
+    ) -> Result<()> {
+        require!(
+            vault.lamports() >= self.lamports.saturating_sub(max_lamports_out),
+            GlamError::VaultBalanceViolation
+        );
+
+        for balance in self.token_balances.iter() {
+            let account = accounts
+                .iter()
+                .find(|account| account.key == &balance.key)
+                .ok_or(GlamError::VaultBalanceViolation)?;
+            // Closed or transferred token accounts fail to load
+            let token_account = load_vault_token_account(account, &self.vault)
+                .ok_or(GlamError::VaultBalanceViolation)?;
+
+            require!(
+                token_account.amount >= balance.amount || outflows.contains(&balance.key),
+                GlamError::VaultBalanceViolation
+            );
+            require!(
+                Option::<Pubkey>::from(token_account.delegate) == balance.delegate
+                    && Option::<Pubkey>::from(token_account.close_authority)
+                        == balance.close_authority,
+                GlamError::VaultBalanceViolation
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that the destinations of the value moved by a CPI are vault token accounts.
+pub fn check_vault_destinations(vault: &Pubkey, destinations: &[&AccountInfo]) -> Result<()> {
+    for destination in destinations.iter() {
+        require!(
+            is_vault_token_account(destination, vault),
+            GlamError::VaultBalanceViolation
+        );
+    }
+    Ok(())
+}
+
+pub fn is_vault_token_account(account: &AccountInfo, vault: &Pubkey) -> bool {
+    load_vault_token_account(account, vault).is_some()
+}
+
+pub fn is_token_account(account: &AccountInfo) -> bool {
+    load_token_account(account).is_some()
+}
+
+fn load_vault_token_account(account: &AccountInfo, vault: &Pubkey) -> Option<TokenAccount> {
+    load_token_account(account).filter(|token_account| token_account.owner == *vault)
+}
+
+fn load_token_account(account: &AccountInfo) -> Option<TokenAccount> {
+    if account.owner != &anchor_spl::token::ID && account.owner != &anchor_spl::token_2022::ID {
+        return None;
+    }
+    let data = account.try_borrow_data().ok()?;
+    TokenAccount::try_deserialize(&mut &data[..]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::{Account as SplTokenAccount, AccountState};
+
+    fn pack_token_account(data: &mut [u8], owner: Pubkey, amount: u64, delegate: Option<Pubkey>) {
+        SplTokenAccount {
+            mint: Pubkey::new_from_array([1; 32]),
+            owner,
+            amount,
+            delegate: delegate.into(),
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(data);
+    }
+
+    #[test]
+    fn cpi_draining_the_vault_reverts() {
+        let vault = Pubkey::new_unique();
+        let token_key = Pubkey::new_unique();
+        let (mut vault_lamports, mut token_lamports) = (1_000_000_000, 2_039_280);
+        let mut vault_data: Vec<u8> = vec![];
+        let mut token_data = vec![0u8; SplTokenAccount::LEN];
+        pack_token_account(&mut token_data, vault, 1_000, None);
+
+        let vault_info = AccountInfo::new(
+            &vault,
+            true,
+            true,
+            &mut vault_lamports,
+            &mut vault_data,
+            &anchor_lang::system_program::ID,
+            false,
+            0,
+        );
+        let token_info = AccountInfo::new(
+            &token_key,
+            false,
+            true,
+            &mut token_lamports,
+            &mut token_data,
+            &anchor_spl::token::ID,
+            false,
+            0,
+        );
+        let accounts = vec![vault_info.clone(), token_info.clone()];
+        let guard = VaultBalanceGuard::new(&vault_info, &accounts);
+        assert!(guard.check(&vault_info, &accounts, &[], 0).is_ok());
+
+        // The CPI moves tokens out of an account that isn't an expected outflow
+        pack_token_account(&mut token_info.try_borrow_mut_data().unwrap(), vault, 400, None);
+        assert!(guard.check(&vault_info, &accounts, &[], 0).is_err());
+        assert!(guard.check(&vault_info, &accounts, &[token_key], 0).is_ok());
+
+        // The CPI spends more lamports than allowed
+        **vault_info.try_borrow_mut_lamports().unwrap() -= 10;
+        assert!(guard.check(&vault_info, &accounts, &[token_key], 5).is_err());
+        assert!(guard.check(&vault_info, &accounts, &[token_key], 10).is_ok());
+
+        // The CPI hands over a delegate, even on an expected outflow
+        let delegate = Some(Pubkey::new_unique());
+        pack_token_account(&mut token_info.try_borrow_mut_data().unwrap(), vault, 400, delegate);
+        assert!(guard.check(&vault_info, &accounts, &[token_key], 10).is_err());
+
+        // The CPI closes the token account
+        token_info.try_borrow_mut_data().unwrap().fill(0);
+        assert!(guard.check(&vault_info, &accounts, &[token_key], 10).is_err());
+    }
+}
@@ -33,6 +33,13 @@ import {
   SEED_VAULT,
   SEED_METADATA,
   SEED_MINT,
+  SEED_ATTESTATION,
+  SEED_COMPLIANCE_LIST,
+  SEED_COMPLIANCE_MEMBER,
+  SEED_WSOL_UNWRAP,
+  SEED_PENDING_STATE_UPDATE,
+  SEED_DELEGATE_USAGE,
+  SEED_CPI_POLICY,
 } from "../constants";
 
 import { GlamProgram, getGlamProgram } from "../glamExports";
@@ -442,6 +449,139 @@ export class BaseClient {
     return pda;
   }
 
+  /**
+   * Temporary wSOL account used by redeem to pay native SOL
+   */
+  getWsolUnwrapPda(statePda: PublicKey): PublicKey {
+    const [pda, _bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from(SEED_WSOL_UNWRAP), this.getVaultPda(statePda).toBuffer()],
+      this.program.programId,
+    );
+    return pda;
+  }
+
+  getPendingStateUpdatePda(statePda: PublicKey): PublicKey {
+    const [pda, _bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from(SEED_PENDING_STATE_UPDATE), statePda.toBuffer()],
+      this.program.programId,
+    );
+    return pda;
+  }
+
+  getCpiPolicyPda(statePda: PublicKey, programId: PublicKey): PublicKey {
+    const [pda, _bump] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(SEED_CPI_POLICY),
+        statePda.toBuffer(),
+        programId.toBuffer(),
+      ],
+      this.program.programId,
+    );
+    return pda;
+  }
+
+  getDelegateUsagePda(statePda: PublicKey, delegate: PublicKey): PublicKey {
+    const [pda, _bump] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(SEED_DELEGATE_USAGE),
+        statePda.toBuffer(),
+        delegate.toBuffer(),
+      ],
+      this.program.programId,
+    );
+    return pda;
+  }
+
+  /**
+   * Accounts to check the notional limits of a delegate, or nulls if the signer
+   * has no limits (no delegate usage account)
+   *
+   * @param glamState State PDA
+   * @param signer Signer of the instruction
+   * @param assetMint Mint of the asset moved by the instruction
+   */
+  async getDelegateLimitsAccounts(
+    glamState: PublicKey,
+    signer: PublicKey,
+    assetMint: PublicKey = WSOL,
+  ): Promise<{
+    delegateUsage: PublicKey | null;
+    assetPricing: PublicKey | null;
+    solPricing: PublicKey | null;
+  }> {
+    const delegateUsage = this.getDelegateUsagePda(glamState, signer);
+    const accountInfo =
+      await this.provider.connection.getAccountInfo(delegateUsage);
+    if (!accountInfo) {
+      return { delegateUsage: null, assetPricing: null, solPricing: null };
+    }
+    const assetMeta = this.getAssetMeta(assetMint.toBase58());
+    const solMeta = this.getAssetMeta(WSOL.toBase58());
+    return {
+      delegateUsage,
+      assetPricing: assetMeta.stateAccount || assetMeta.pricingAccount || null,
+      solPricing: solMeta.pricingAccount || null,
+    };
+  }
+
+  /**
+   * Instruction to check the risk limits of the vault, or null if the vault has none.
+   * It must follow swaps, deposits and withdraws of vaults with risk limits.
+   *
+   * @param glamState State PDA
+   * @param signer Signer of the instruction
+   */
+  async getCheckRiskLimitsIx(
+    glamState: PublicKey,
+    signer?: PublicKey,
+  ): Promise<TransactionInstruction | null> {
+    const stateModel = await this.fetchState(glamState);
+    if (!stateModel.riskLimits) {
+      return null;
+    }
+
+    // Same as subscribe: vault ata and pricing account of each asset, then external vault accounts
+    const remainingAccounts = (stateModel.assets || [])
+      .flatMap((asset) => {
+        const assetMeta = this.getAssetMeta(asset.toBase58());
+        return [
+          this.getVaultAta(glamState, asset, assetMeta?.programId),
+          assetMeta.stateAccount || assetMeta.pricingAccount!,
+        ];
+      })
+      .map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }))
+      .concat(
+        this.getExternalVaultAccountMetas(
+          (stateModel.externalVaultAccounts || []).filter(
+            (address) => !address.equals(SystemProgram.programId),
+          ),
+        ),
+      );
+
+    return await this.program.methods
+      .checkRiskLimits()
+      .accounts({
+        glamState,
+        glamSigner: signer || this.getSigner(),
+      })
+      .remainingAccounts(remainingAccounts)
+      .instruction();
+  }
+
+  /**
+   * External vault accounts (stake accounts, marinade tickets) to compute AUM
+   */
+  getExternalVaultAccountMetas(
+    externalVaultAccounts: PublicKey[],
+    isWritable: boolean = false,
+  ) {
+    return externalVaultAccounts.map((pubkey) => ({
+      pubkey,
+      isSigner: false,
+      isWritable,
+    }));
+  }
+
   getVaultAta(
     glamState: PublicKey,
     mint: PublicKey,
@@ -551,6 +691,62 @@ export class BaseClient {
     return this.getAta(mintPda, user, TOKEN_2022_PROGRAM_ID);
   }
 
+  getComplianceListPda(owner: PublicKey, id: Uint8Array): PublicKey {
+    const [pda, _] = PublicKey.findProgramAddressSync(
+      [Buffer.from(SEED_COMPLIANCE_LIST), owner.toBuffer(), Buffer.from(id)],
+      this.program.programId,
+    );
+    return pda;
+  }
+
+  getComplianceMemberPda(list: PublicKey, member: PublicKey): PublicKey {
+    const [pda, _] = PublicKey.findProgramAddressSync(
+      [Buffer.from(SEED_COMPLIANCE_MEMBER), list.toBuffer(), member.toBuffer()],
+      this.program.programId,
+    );
+    return pda;
+  }
+
+  /**
+   * Compliance lists of a share class, as writable remaining accounts of add_mint
+   * and close_mint (which count the share classes referencing each list)
+   */
+  getComplianceListsAccounts(complianceLists: PublicKey[] | null | undefined) {
+    return (complianceLists || []).map((pubkey) => ({
+      pubkey,
+      isSigner: false,
+      isWritable: true,
+    }));
+  }
+
+  /**
+   * Gateway token (civic) or attestation (glam) of a wallet
+   *
+   * @param wallet
+   * @param gatewayProgram Civic gateway program or glam program
+   * @param gatekeeperNetwork Gatekeeper network or attestation issuer
+   */
+  getKycTokenPda(
+    wallet: PublicKey,
+    gatewayProgram: PublicKey,
+    gatekeeperNetwork: PublicKey,
+  ): PublicKey {
+    const seeds = gatewayProgram.equals(this.program.programId)
+      ? [
+          Buffer.from(SEED_ATTESTATION),
+          gatekeeperNetwork.toBuffer(),
+          wallet.toBuffer(),
+        ]
+      : [
+          wallet.toBuffer(),
+          Buffer.from("gateway"),
+          Buffer.alloc(8),
+          gatekeeperNetwork.toBuffer(),
+        ];
+    const [pda, _] = PublicKey.findProgramAddressSync(seeds, gatewayProgram);
+    return pda;
+  }
+
   getName(stateModel: Partial<StateModel>) {
     const name =
       stateModel.name ||
@@ -715,7 +911,10 @@ export class BaseClient {
           (!owner && !delegate) ||
           (owner && s.account.owner.equals(owner)) ||
           (delegate &&
-            s.account.delegateAcls.some((acl) => acl.pubkey.equals(delegate))),
+            StateModel.fromOnchainAccounts(
+              s.publicKey,
+              s.account,
+            ).delegateAcls?.some((acl) => acl.pubkey.equals(delegate))),
       );
 
     let openfundsCache = new Map<string, OpenfundsMetadataAccount>();
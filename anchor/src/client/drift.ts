@@ -639,6 +639,11 @@ export class DriftClient {
       }
     }
 
+    const delegateLimitsAccounts = await this.base.getDelegateLimitsAccounts(
+      glamState,
+      glamSigner,
+      new PublicKey(mint),
+    );
     const tx = await this.base.program.methods
       .driftDeposit(marketIndex, amount, false)
       .accounts({
@@ -650,6 +655,7 @@ export class DriftClient {
         userTokenAccount: this.base.getVaultAta(glamState, new PublicKey(mint)),
         glamSigner,
         tokenProgram: TOKEN_PROGRAM_ID,
+        ...delegateLimitsAccounts,
       })
       .remainingAccounts([
         { pubkey: new PublicKey(oracle), isSigner: false, isWritable: false },
@@ -701,6 +707,16 @@ export class DriftClient {
       ),
     ];
 
+    const delegateLimitsAccounts = await this.base.getDelegateLimitsAccounts(
+      statePda,
+      glamSigner,
+      mint,
+    );
+    const checkRiskLimitsIx = await this.base.getCheckRiskLimitsIx(
+      statePda,
+      glamSigner,
+    );
+
     const tx = await this.base.program.methods
       .driftWithdraw(marketIndex, amount, false)
       .accounts({
@@ -713,9 +729,11 @@ export class DriftClient {
         userTokenAccount: vaultAta,
         driftSigner: DRIFT_VAULT,
         tokenProgram: TOKEN_PROGRAM_ID,
+        ...delegateLimitsAccounts,
       })
       .remainingAccounts(remainingAccounts)
       .preInstructions(preInstructions)
+      .postInstructions(checkRiskLimitsIx ? [checkRiskLimitsIx] : [])
       .transaction();
 
     return await this.base.intoVersionedTransaction(tx, txOptions);
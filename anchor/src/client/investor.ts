@@ -1,7 +1,10 @@
 import { BN } from "@coral-xyz/anchor";
 import {
+  Keypair,
   PublicKey,
+  StakeProgram,
   SystemProgram,
+  Transaction,
   TransactionInstruction,
   TransactionSignature,
   VersionedTransaction,
@@ -9,7 +12,6 @@ import {
 import {
   getAssociatedTokenAddressSync,
   createAssociatedTokenAccountIdempotentInstruction,
-  createSyncNativeInstruction,
   TOKEN_2022_PROGRAM_ID,
 } from "@solana/spl-token";
 
@@ -32,7 +34,12 @@ export class InvestorClient {
     mintId: number = 0,
     skipState: boolean = true,
     txOptions: TxOptions = {},
+    beneficiary?: PublicKey | Keypair,
+    minSharesOut?: BN,
   ): Promise<TransactionSignature> {
+    // a beneficiary keypair co-signs, required if the share class has a lock-up
+    const beneficiarySigners =
+      beneficiary instanceof Keypair ? [beneficiary] : [];
     const tx = await this.subscribeTx(
       statePda,
       asset,
@@ -41,8 +48,35 @@ export class InvestorClient {
       mintId,
       skipState,
       txOptions,
+      beneficiary instanceof Keypair ? beneficiary.publicKey : beneficiary,
+      minSharesOut,
     );
-    return await this.base.sendAndConfirm(tx);
+    return await this.base.sendAndConfirm(tx, beneficiarySigners);
+  }
+
+  public async subscribeInKind(
+    statePda: PublicKey,
+    amounts: BN[],
+    matchWeights: boolean = false,
+    stateModel?: StateModel,
+    mintId: number = 0,
+    txOptions: TxOptions = {},
+    beneficiary?: PublicKey | Keypair,
+    minSharesOut?: BN,
+  ): Promise<TransactionSignature> {
+    const beneficiarySigners =
+      beneficiary instanceof Keypair ? [beneficiary] : [];
+    const tx = await this.subscribeInKindTx(
+      statePda,
+      amounts,
+      matchWeights,
+      stateModel,
+      mintId,
+      txOptions,
+      beneficiary instanceof Keypair ? beneficiary.publicKey : beneficiary,
+      minSharesOut,
+    );
+    return await this.base.sendAndConfirm(tx, beneficiarySigners);
   }
 
   public async redeem(
@@ -53,6 +87,9 @@ export class InvestorClient {
     mintId: number = 0,
     skipState: boolean = true,
     txOptions: TxOptions = {},
+    outputAssets: PublicKey[] = [],
+    nativeSol: boolean = false,
+    minAmountsOut?: BN[],
   ): Promise<TransactionSignature> {
     const tx = await this.redeemTx(
       statePda,
@@ -62,6 +99,9 @@ export class InvestorClient {
       mintId,
       skipState,
       txOptions,
+      outputAssets,
+      nativeSol,
+      minAmountsOut,
     );
     return await this.base.sendAndConfirm(tx);
   }
@@ -78,12 +118,15 @@ export class InvestorClient {
     mintId: number = 0,
     skipState: boolean = true,
     txOptions: TxOptions = {},
+    beneficiary?: PublicKey,
+    minSharesOut?: BN,
   ): Promise<VersionedTransaction> {
     const signer = txOptions.signer || this.base.getSigner();
+    // shares are minted to the beneficiary, by default the signer
+    beneficiary = beneficiary || signer;
 
     // glam mint token to receive
     const mintPda = this.base.getMintPda(statePda, mintId);
-    const signerShareAta = this.base.getMintAta(signer, mintPda);
 
     // asset token to transfer
     const assetMeta = this.base.getAssetMeta(asset.toBase58());
@@ -123,79 +166,197 @@ export class InvestorClient {
     });
 
     remainingAccounts = remainingAccounts.concat(
-      (stateModel.externalVaultAccounts || []).map((address) => ({
-        pubkey: address,
-        isSigner: false,
-        isWritable: false,
-      })),
+      this.base.getExternalVaultAccountMetas(
+        stateModel.externalVaultAccounts || [],
+      ),
     );
 
-    // SOL -> wSOL
-    // If the user doesn't have enough wSOL but does have SOL, we auto wrap
-    let preInstructions: TransactionInstruction[] = [
-      createAssociatedTokenAccountIdempotentInstruction(
-        signer,
-        signerAssetAta,
-        signer,
-        asset,
-        assetMeta?.programId,
-      ),
-      createAssociatedTokenAccountIdempotentInstruction(
-        signer,
-        vaultAta,
-        vault,
-        asset,
-        assetMeta?.programId,
-      ),
-      createAssociatedTokenAccountIdempotentInstruction(
-        signer,
-        signerShareAta,
-        signer,
-        mintPda,
-        TOKEN_2022_PROGRAM_ID,
-      ),
-    ];
+    const { complianceAccounts, beneficiaryKycToken } =
+      this.getBeneficiaryAccounts(stateModel, mintId, beneficiary);
+    remainingAccounts = remainingAccounts.concat(complianceAccounts);
 
+    // SOL -> wSOL
+    // If the user doesn't have enough wSOL, we subscribe with native SOL
+    // (wrapped by the program into the vault wSOL account)
+    let nativeSol = false;
     if (WSOL.equals(asset)) {
-      const connection = this.base.provider.connection;
       let wsolBalance = new BN(0);
       try {
         wsolBalance = new BN(
           (
-            await connection.getTokenAccountBalance(signerAssetAta)
+            await this.base.provider.connection.getTokenAccountBalance(
+              signerAssetAta,
+            )
           ).value.amount,
         );
       } catch (err) {
         // ignore
       }
-      const delta = amount.sub(wsolBalance);
-      if (delta.gt(new BN(0))) {
-        preInstructions = preInstructions.concat([
-          SystemProgram.transfer({
-            fromPubkey: signer,
-            toPubkey: signerAssetAta,
-            lamports: delta.toNumber(),
-          }),
-          createSyncNativeInstruction(signerAssetAta),
-        ]);
-      }
+      nativeSol = wsolBalance.lt(amount);
     }
 
+    const preInstructions: TransactionInstruction[] = [
+      ...(nativeSol
+        ? []
+        : [
+            createAssociatedTokenAccountIdempotentInstruction(
+              signer,
+              signerAssetAta,
+              signer,
+              asset,
+              assetMeta?.programId,
+            ),
+          ]),
+      createAssociatedTokenAccountIdempotentInstruction(
+        signer,
+        vaultAta,
+        vault,
+        asset,
+        assetMeta?.programId,
+      ),
+    ];
+
     const tx = await this.base.program.methods
-      .subscribe(0, amount, skipState)
+      .subscribe(0, amount, minSharesOut || null, skipState)
       .accounts({
         glamState: statePda,
         glamMint: mintPda,
         asset,
         vaultAta,
-        signerAssetAta,
+        signerAssetAta: nativeSol ? null : signerAssetAta,
+        // dead shares are minted by the first subscription only
+        deadSharesAta: this.base.getMintAta(mintPda, mintPda),
         //TODO: only add if the fund has lock-up? (just for efficiency)
         // signerAccountPolicy: null,
+        beneficiary,
+        beneficiaryKycToken,
         signer,
       })
       .remainingAccounts(remainingAccounts)
       .preInstructions(preInstructions)
       .transaction();
+    this.markBeneficiarySigner(tx, stateModel, mintId, beneficiary, signer);
+
+    return await this.base.intoVersionedTransaction(tx, txOptions);
+  }
+
+  /**
+   * Subscribe in kind, amounts[i] is the amount of stateModel.assets[i]
+   */
+  public async subscribeInKindTx(
+    statePda: PublicKey,
+    amounts: BN[],
+    matchWeights: boolean = false,
+    stateModel?: StateModel,
+    mintId: number = 0,
+    txOptions: TxOptions = {},
+    beneficiary?: PublicKey,
+    minSharesOut?: BN,
+  ): Promise<VersionedTransaction> {
+    const signer = txOptions.signer || this.base.getSigner();
+    beneficiary = beneficiary || signer;
+
+    const glamMint = this.base.getMintPda(statePda, mintId);
+    const vault = this.base.getVaultPda(statePda);
+
+    if (!stateModel) {
+      stateModel = await this.base.fetchState(statePda);
+    }
+    const assets = stateModel.assets || [];
+    if (amounts.length !== assets.length) {
+      throw new Error("amounts must match the state assets");
+    }
+
+    // remaining accounts = for each asset: vault ata, pricing, asset, signer ata
+    let remainingAccounts = assets.flatMap((asset) => {
+      const assetMeta = this.base.getAssetMeta(asset.toBase58());
+      const vaultAta = this.base.getVaultAta(
+        statePda,
+        asset,
+        assetMeta?.programId,
+      );
+      const signerAta = getAssociatedTokenAddressSync(
+        asset,
+        signer,
+        true,
+        assetMeta?.programId,
+      );
+      return [
+        { pubkey: vaultAta, isSigner: false, isWritable: true },
+        {
+          pubkey: assetMeta.stateAccount || assetMeta.pricingAccount!,
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: asset, isSigner: false, isWritable: false },
+        {
+          pubkey: isNativeSol(asset) ? signer : signerAta,
+          isSigner: false,
+          isWritable: true,
+        },
+      ];
+    });
+    remainingAccounts = remainingAccounts.concat(
+      this.base.getExternalVaultAccountMetas(
+        stateModel.externalVaultAccounts || [],
+      ),
+    );
+
+    const { complianceAccounts, beneficiaryKycToken } =
+      this.getBeneficiaryAccounts(stateModel, mintId, beneficiary);
+    remainingAccounts = remainingAccounts.concat(complianceAccounts);
+
+    // signer atas of all assets must exist, and vault atas of the assets we transfer
+    const preInstructions = assets.flatMap((asset, i) => {
+      const assetMeta = this.base.getAssetMeta(asset.toBase58());
+      const ixs = [
+        createAssociatedTokenAccountIdempotentInstruction(
+          signer,
+          getAssociatedTokenAddressSync(
+            asset,
+            signer,
+            true,
+            assetMeta?.programId,
+          ),
+          signer,
+          asset,
+          assetMeta?.programId,
+        ),
+      ];
+      if (amounts[i].gt(new BN(0))) {
+        ixs.push(
+          createAssociatedTokenAccountIdempotentInstruction(
+            signer,
+            this.base.getVaultAta(statePda, asset, assetMeta?.programId),
+            vault,
+            asset,
+            assetMeta?.programId,
+          ),
+        );
+      }
+      return ixs;
+    });
+
+    const tx = await this.base.program.methods
+      .subscribeInKind(
+        mintId,
+        amounts,
+        matchWeights,
+        minSharesOut || null,
+        true,
+      )
+      .accounts({
+        glamState: statePda,
+        glamMint,
+        deadSharesAta: this.base.getMintAta(glamMint, glamMint),
+        beneficiary,
+        beneficiaryKycToken,
+        signer,
+      })
+      .remainingAccounts(remainingAccounts)
+      .preInstructions(preInstructions)
+      .transaction();
+    this.markBeneficiarySigner(tx, stateModel, mintId, beneficiary, signer);
 
     return await this.base.intoVersionedTransaction(tx, txOptions);
   }
@@ -208,8 +369,14 @@ export class InvestorClient {
     mintId: number = 0,
     skipState: boolean = true,
     txOptions: TxOptions = {},
+    outputAssets: PublicKey[] = [],
+    nativeSol: boolean = false,
+    // slippage bounds, minAmountsOut[i] is the min amount of stateModel.assets[i]
+    minAmountsOut?: BN[],
   ): Promise<VersionedTransaction> {
     const signer = txOptions.signer || this.base.getSigner();
+    // wSOL is paid as native SOL to the signer, no wSOL account needed
+    const isNativeSol = (asset: PublicKey) => nativeSol && WSOL.equals(asset);
 
     // share class token to receive
     const glamMint = this.base.getMintPda(statePda, mintId);
@@ -247,19 +414,62 @@ export class InvestorClient {
       ];
     });
 
+    // external vault accounts are writable, stake accounts are split (in kind)
+    // or handed over (redeeming everything) to the signer
+    const externalVaultAccounts = stateModel.externalVaultAccounts || [];
     remainingAccounts = remainingAccounts.concat(
-      (stateModel.externalVaultAccounts || []).map((address: PublicKey) => ({
-        pubkey: address,
-        isSigner: false,
-        isWritable: false,
-      })),
+      this.base.getExternalVaultAccountMetas(externalVaultAccounts, true),
     );
 
+    // in kind, one new stake account per vault stake account is created to
+    // receive the split stake, and passed as last remaining accounts
+    const stakeAccountInstructions: TransactionInstruction[] = [];
+    if (inKind && externalVaultAccounts.length > 0) {
+      const connection = this.base.provider.connection;
+      const accountsInfo =
+        await connection.getMultipleAccountsInfo(externalVaultAccounts);
+      const numStakeAccounts = accountsInfo.filter((info) =>
+        info?.owner.equals(StakeProgram.programId),
+      ).length;
+      const lamports = await connection.getMinimumBalanceForRentExemption(
+        StakeProgram.space,
+      );
+      const timestamp = Date.now().toString();
+      for (let i = 0; i < numStakeAccounts; i++) {
+        const seed = `${timestamp}${i}`;
+        const stakeAccount = await PublicKey.createWithSeed(
+          signer,
+          seed,
+          StakeProgram.programId,
+        );
+        stakeAccountInstructions.push(
+          SystemProgram.createAccountWithSeed({
+            fromPubkey: signer,
+            newAccountPubkey: stakeAccount,
+            basePubkey: signer,
+            seed,
+            lamports,
+            space: StakeProgram.space,
+            programId: StakeProgram.programId,
+          }),
+        );
+        remainingAccounts.push({
+          pubkey: stakeAccount,
+          isSigner: false,
+          isWritable: true,
+        });
+      }
+    }
+
     const preInstructions = (
       await Promise.all(
         (stateModel.assets || []).map(async (asset: any, j: number) => {
-          // not in kind, we only need the base asset ATA
-          if (!inKind && j > 0) {
+          // not in kind, we only need the ATAs of the output assets (default: base asset)
+          const isOutputAsset =
+            outputAssets.length > 0
+              ? outputAssets.some((a) => a.equals(asset))
+              : j === 0;
+          if ((!inKind && !isOutputAsset) || isNativeSol(asset)) {
             return null;
           }
 
@@ -281,15 +491,19 @@ export class InvestorClient {
         }),
       )
     ).filter((x: any) => !!x) as TransactionInstruction[];
+    preInstructions.push(...stakeAccountInstructions);
 
     const tx = await this.base.program.methods
-      .redeem(amount, inKind, skipState)
+      .redeem(amount, inKind, outputAssets, minAmountsOut || null, skipState)
       .accounts({
         glamState: statePda,
         glamMint,
         signerShareAta,
+        // redeeming all shares but the dead shares redeems the whole vault
+        deadSharesAta: this.base.getMintAta(glamMint, glamMint),
         //TODO: only add if the fund has lock-up? (just for efficiency)
         // signerAccountPolicy: null,
+        wsolUnwrap: nativeSol ? this.base.getWsolUnwrapPda(statePda) : null,
         signer,
       })
       .remainingAccounts(remainingAccounts)
@@ -298,4 +512,66 @@ export class InvestorClient {
 
     return await this.base.intoVersionedTransaction(tx, txOptions);
   }
+
+  /*
+   * Helpers
+   */
+
+  /**
+   * Compliance lists (list + beneficiary's member record, passed as last remaining
+   * accounts) and KYC gateway token of the beneficiary, if the share class requires them
+   */
+  getBeneficiaryAccounts(
+    stateModel: StateModel,
+    mintId: number,
+    beneficiary: PublicKey,
+  ) {
+    const mintModel = (stateModel.mints || [])[mintId];
+
+    const complianceAccounts = (mintModel?.complianceLists || []).flatMap(
+      (list) => [
+        { pubkey: list, isSigner: false, isWritable: false },
+        {
+          pubkey: this.base.getComplianceMemberPda(list, beneficiary),
+          isSigner: false,
+          isWritable: false,
+        },
+      ],
+    );
+
+    const beneficiaryKycToken =
+      mintModel?.kycGatewayProgram && mintModel?.kycGatekeeperNetwork
+        ? this.base.getKycTokenPda(
+            beneficiary,
+            mintModel.kycGatewayProgram,
+            mintModel.kycGatekeeperNetwork,
+          )
+        : null;
+
+    return { complianceAccounts, beneficiaryKycToken };
+  }
+
+  /**
+   * The beneficiary must co-sign the subscription if it's not the signer
+   * and the share class has a lock-up
+   */
+  markBeneficiarySigner(
+    tx: Transaction,
+    stateModel: StateModel,
+    mintId: number,
+    beneficiary: PublicKey,
+    signer: PublicKey,
+  ) {
+    const lockUp = (stateModel.mints || [])[mintId]?.lockUpPeriodInSeconds;
+    if (!lockUp || beneficiary.equals(signer)) {
+      return;
+    }
+    tx.instructions
+      .filter((ix) => ix.programId.equals(this.base.program.programId))
+      .forEach((ix) =>
+        ix.keys
+          .filter((key) => key.pubkey.equals(beneficiary))
+          .forEach((key) => (key.isSigner = true)),
+      );
+  }
 }
@@ -173,6 +173,15 @@ export class JupiterSwapClient {
       ASSETS_MAINNET.get(inputMint.toBase58())?.stateAccount || null;
     const outputStakePool =
       ASSETS_MAINNET.get(outputMint.toBase58())?.stateAccount || null;
+    const {
+      delegateUsage,
+      assetPricing: inputPricing,
+      solPricing,
+    } = await this.base.getDelegateLimitsAccounts(
+      glamState,
+      glamSigner,
+      inputMint,
+    );
 
     const preInstructions = await this.getPreInstructions(
       glamState,
@@ -183,6 +192,10 @@ export class JupiterSwapClient {
       inputTokenProgram,
       outputTokenProgram,
     );
+    const checkRiskLimitsIx = await this.base.getCheckRiskLimitsIx(
+      glamState,
+      glamSigner,
+    );
     const tx = await this.base.program.methods
       .jupiterSwap(amount, swapIx.data)
       .accounts({
@@ -194,9 +207,13 @@ export class JupiterSwapClient {
         outputTokenProgram,
         inputStakePool,
         outputStakePool,
+        delegateUsage,
+        inputPricing,
+        solPricing,
       })
       .remainingAccounts(swapIx.keys)
       .preInstructions(preInstructions)
+      .postInstructions(checkRiskLimitsIx ? [checkRiskLimitsIx] : [])
       .transaction();
 
     return this.base.intoVersionedTransaction(tx, {
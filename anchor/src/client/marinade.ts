@@ -207,6 +207,13 @@ export class MarinadeClient {
       marinadeState.msolMintAddress,
     );
 
+    const { delegateUsage, solPricing } =
+      await this.base.getDelegateLimitsAccounts(glamState, glamSigner);
+    const checkRiskLimitsIx = await this.base.getCheckRiskLimitsIx(
+      glamState,
+      glamSigner,
+    );
+
     const tx = await this.base.program.methods
       .marinadeDeposit(amount)
       .accounts({
@@ -221,8 +228,11 @@ export class MarinadeClient {
         liqPoolSolLegPda: marinadeState.solLeg,
         mintTo: vaultMsolAta,
         tokenProgram: TOKEN_PROGRAM_ID,
+        delegateUsage,
+        solPricing,
       })
       .preInstructions([createMsolAtaIx])
+      .postInstructions(checkRiskLimitsIx ? [checkRiskLimitsIx] : [])
       .transaction();
 
     return await this.base.intoVersionedTransaction(tx, txOptions);
@@ -59,6 +59,7 @@ export class MintClient {
 
   public async closeMintIx(glamState: PublicKey, mintId: number = 0) {
     const glamMint = this.base.getMintPda(glamState, mintId);
+    const stateModel = await this.base.fetchState(glamState);
 
     return await this.base.program.methods
       .closeMint(mintId)
@@ -66,6 +67,11 @@ export class MintClient {
         glamState,
         glamMint,
       })
+      .remainingAccounts(
+        this.base.getComplianceListsAccounts(
+          (stateModel.mints || [])[mintId]?.complianceLists,
+        ),
+      )
       .instruction();
   }
 
@@ -76,6 +82,7 @@ export class MintClient {
   ) {
     const glamSigner = txOptions.signer || this.base.getSigner();
     const glamMint = this.base.getMintPda(glamState, mintId);
+    const stateModel = await this.base.fetchState(glamState);
 
     const tx = await this.base.program.methods
       .closeMint(mintId)
@@ -84,6 +91,11 @@ export class MintClient {
         glamSigner,
         glamMint,
       })
+      .remainingAccounts(
+        this.base.getComplianceListsAccounts(
+          (stateModel.mints || [])[mintId]?.complianceLists,
+        ),
+      )
       .transaction();
 
     const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
@@ -167,6 +179,52 @@ export class MintClient {
     return await this.base.sendAndConfirm(vTx);
   }
 
+  /**
+   * Adds and removes members of the allowlist of a share class
+   */
+  public async updateAllowlist(
+    glamState: PublicKey,
+    mintId: number,
+    add: PublicKey[],
+    remove: PublicKey[],
+    txOptions: TxOptions = {},
+  ) {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .updateMintAllowlist(mintId, add, remove)
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .transaction();
+
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Adds and removes members of the blocklist of a share class
+   */
+  public async updateBlocklist(
+    glamState: PublicKey,
+    mintId: number,
+    add: PublicKey[],
+    remove: PublicKey[],
+    txOptions: TxOptions = {},
+  ) {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .updateMintBlocklist(mintId, add, remove)
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .transaction();
+
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
   /**
    * Mint share to recipient
    *
@@ -397,6 +397,13 @@ export class StakingClient {
 
     console.log(`stakePool ${stakePool}, programId: ${stakePoolProgram}`);
 
+    const { delegateUsage, solPricing } =
+      await this.base.getDelegateLimitsAccounts(glamState, glamSigner);
+    const checkRiskLimitsIx = await this.base.getCheckRiskLimitsIx(
+      glamState,
+      glamSigner,
+    );
+
     const tx = await this.base.program.methods
       .stakePoolDepositSol(amount)
       .accountsPartial({
@@ -411,7 +418,10 @@ export class StakingClient {
         withdrawAuthority,
         feeAccount,
         tokenProgram, // TODO: glam program instruction currently doesn't support token2022
+        delegateUsage,
+        solPricing,
       })
+      .postInstructions(checkRiskLimitsIx ? [checkRiskLimitsIx] : [])
       .transaction();
 
     return await this.base.intoVersionedTransaction(tx, txOptions);
@@ -574,6 +584,9 @@ export class StakingClient {
     const [stakeAccount, createStakeAccountIx] =
       await this.createStakeAccount(glamSigner);
 
+    const { delegateUsage, solPricing } =
+      await this.base.getDelegateLimitsAccounts(glamState, glamSigner);
+
     const tx = await this.base.program.methods
       .initializeAndDelegateStake(amount)
       .accountsPartial({
@@ -585,6 +598,8 @@ export class StakingClient {
         stakeConfig: STAKE_CONFIG_ID,
         stakeHistory: SYSVAR_STAKE_HISTORY_PUBKEY,
         stakeProgram: StakeProgram.programId,
+        delegateUsage,
+        solPricing,
       })
       .preInstructions([createStakeAccountIx])
       .transaction();
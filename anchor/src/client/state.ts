@@ -18,6 +18,10 @@ import {
 import {
   CompanyModel,
   DelegateAcl,
+  DelegateRoles,
+  DelegateScope,
+  InstructionPolicy,
+  Role,
   StateModel,
   FundOpenfundsModel,
   ManagerModel,
@@ -82,6 +86,9 @@ export class StateClient {
           glamSigner,
           newMint,
         })
+        .remainingAccounts(
+          this.base.getComplianceListsAccounts(mints[0].complianceLists),
+        )
         .preInstructions([initStateIx])
         .rpc();
       return [txSig, glamState];
@@ -111,6 +118,9 @@ export class StateClient {
             glamSigner,
             newMint,
           })
+          .remainingAccounts(
+            this.base.getComplianceListsAccounts(mint.complianceLists),
+          )
           .preInstructions([
             // FIXME: estimate compute units
             ComputeBudgetProgram.setComputeUnitLimit({ units: 500_000 }),
@@ -155,6 +165,254 @@ export class StateClient {
     return await this.base.intoVersionedTransaction(tx, txOptions);
   }
 
+  /**
+   * Queues a state update, for vaults with a timelock.
+   * It can be applied with applyStateUpdate once the timelock has expired.
+   */
+  public async queueStateUpdate(
+    glamState: PublicKey,
+    updated: Partial<StateModel>,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .queueStateUpdate(new StateModel(updated))
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Applies the queued state update, with the usage accounts of the delegates whose limits are updated
+   */
+  public async applyStateUpdate(
+    glamState: PublicKey,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const { stateModel } =
+      await this.base.program.account.pendingStateUpdateAccount.fetch(
+        this.base.getPendingStateUpdatePda(glamState),
+      );
+    const remainingAccounts = (stateModel.delegateLimits || []).map(
+      ({ delegate }) => ({
+        pubkey: this.base.getDelegateUsagePda(glamState, delegate),
+        isSigner: false,
+        isWritable: true,
+      }),
+    );
+    const tx = await this.base.program.methods
+      .applyStateUpdate()
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .remainingAccounts(remainingAccounts)
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  public async cancelStateUpdate(
+    glamState: PublicKey,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .cancelStateUpdate()
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Accepts the ownership transfer proposed by the owner (updateState with owner set)
+   */
+  public async acceptOwnership(
+    glamState: PublicKey,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .acceptOwnership()
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Cancels the pending ownership transfer, signed by the owner or the pending owner
+   */
+  public async cancelOwnershipTransfer(
+    glamState: PublicKey,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .cancelOwnershipTransfer()
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Sets the notional limits (USD, 6 decimals) of a delegate, 0 for no limit
+   */
+  public async setDelegateLimits(
+    glamState: PublicKey,
+    delegate: PublicKey,
+    maxNotionalPerTx: BN,
+    maxNotionalPerDay: BN,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .setDelegateLimits(delegate, maxNotionalPerTx, maxNotionalPerDay)
+      .accountsPartial({
+        glamState,
+        glamSigner,
+        delegateUsage: this.base.getDelegateUsagePda(glamState, delegate),
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Pauses or unpauses all delegate permissions. The guardian can pause, only the owner can unpause.
+   */
+  public async setDelegatesPaused(
+    glamState: PublicKey,
+    paused: boolean,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .setDelegatesPaused(paused)
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Updates the name and uri of a state, the only change allowed with the updateMetadata permission
+   */
+  public async updateStateMetadata(
+    glamState: PublicKey,
+    name: string | null,
+    uri: string | null,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .updateStateMetadata(name, uri)
+      .accounts({
+        glamState,
+        glamSigner,
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Sets the instructions of a program that delegates with the executeCpi permission can call
+   */
+  public async setCpiPolicy(
+    glamState: PublicKey,
+    programId: PublicKey,
+    instructions: InstructionPolicy[],
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .setCpiPolicy(programId, instructions)
+      .accountsPartial({
+        glamState,
+        glamSigner,
+        cpiPolicy: this.base.getCpiPolicyPda(glamState, programId),
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Closes the CPI policy of a program, delegates can no longer call it
+   */
+  public async closeCpiPolicy(
+    glamState: PublicKey,
+    programId: PublicKey,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const tx = await this.base.program.methods
+      .closeCpiPolicy(programId)
+      .accountsPartial({
+        glamState,
+        glamSigner,
+        cpiPolicy: this.base.getCpiPolicyPda(glamState, programId),
+      })
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
+  /**
+   * Executes an instruction signed by the vault, if allowed by the CPI policy of its program
+   *
+   * @param glamState
+   * @param ix Instruction with the vault as signer
+   * @param txOptions
+   */
+  public async executeCpi(
+    glamState: PublicKey,
+    ix: TransactionInstruction,
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    const glamSigner = txOptions.signer || this.base.getSigner();
+    const glamVault = this.base.getVaultPda(glamState);
+    const tx = await this.base.program.methods
+      .executeCpi(ix.data)
+      .accountsPartial({
+        glamState,
+        glamVault,
+        glamSigner,
+        cpiPolicy: this.base.getCpiPolicyPda(glamState, ix.programId),
+        targetProgram: ix.programId,
+      })
+      .remainingAccounts(
+        ix.keys.map((key) => ({
+          ...key,
+          // the vault signs in the program
+          isSigner: key.pubkey.equals(glamVault) ? false : key.isSigner,
+        })),
+      )
+      .transaction();
+    const vTx = await this.base.intoVersionedTransaction(tx, txOptions);
+    return await this.base.sendAndConfirm(vTx);
+  }
+
   public async closeState(
     glamState: PublicKey,
     txOptions: TxOptions = {},
@@ -291,6 +549,39 @@ export class StateClient {
     return await this.updateState(glamState, { delegateAcls }, txOptions);
   }
 
+  /**
+   * Define or update roles. A role with no permissions is deleted.
+   */
+  public async upsertRoles(
+    glamState: PublicKey,
+    roles: Role[],
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    return await this.updateState(glamState, { roles }, txOptions);
+  }
+
+  /**
+   * Assign roles to delegates. A delegate with no roles is unassigned.
+   */
+  public async upsertDelegateRoles(
+    glamState: PublicKey,
+    delegateRoles: DelegateRoles[],
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    return await this.updateState(glamState, { delegateRoles }, txOptions);
+  }
+
+  /**
+   * Restrict delegates to some assets and markets. An empty scope is deleted.
+   */
+  public async upsertDelegateScopes(
+    glamState: PublicKey,
+    delegateScopes: DelegateScope[],
+    txOptions: TxOptions = {},
+  ): Promise<TransactionSignature> {
+    return await this.updateState(glamState, { delegateScopes }, txOptions);
+  }
+
   public async setSubscribeRedeemEnabled(
     glamState: PublicKey,
     enabled: boolean,
@@ -10,9 +10,34 @@ export const SEED_MINT = (
 export const SEED_STATE = (
   GlamIDLJson.constants.find((x) => x.name === "SEED_STATE")?.value || ""
 ).replace(/"/g, "");
+export const SEED_ATTESTATION = (
+  GlamIDLJson.constants.find((x) => x.name === "SEED_ATTESTATION")?.value || ""
+).replace(/"/g, "");
+export const SEED_COMPLIANCE_LIST = (
+  GlamIDLJson.constants.find((x) => x.name === "SEED_COMPLIANCE_LIST")?.value ||
+  ""
+).replace(/"/g, "");
+export const SEED_COMPLIANCE_MEMBER = (
+  GlamIDLJson.constants.find((x) => x.name === "SEED_COMPLIANCE_MEMBER")
+    ?.value || ""
+).replace(/"/g, "");
 export const SEED_VAULT = (
   GlamIDLJson.constants.find((x) => x.name === "SEED_VAULT")?.value || ""
 ).replace(/"/g, "");
+export const SEED_WSOL_UNWRAP = (
+  GlamIDLJson.constants.find((x) => x.name === "SEED_WSOL_UNWRAP")?.value || ""
+).replace(/"/g, "");
+export const SEED_PENDING_STATE_UPDATE = (
+  GlamIDLJson.constants.find((x) => x.name === "SEED_PENDING_STATE_UPDATE")
+    ?.value || ""
+).replace(/"/g, "");
+export const SEED_DELEGATE_USAGE = (
+  GlamIDLJson.constants.find((x) => x.name === "SEED_DELEGATE_USAGE")?.value ||
+  ""
+).replace(/"/g, "");
+export const SEED_CPI_POLICY = (
+  GlamIDLJson.constants.find((x) => x.name === "SEED_CPI_POLICY")?.value || ""
+).replace(/"/g, "");
 
 /**
  * Token mints. If no devnet version is defined, assume mainnet and devnet addresses are the same.
@@ -55,6 +80,9 @@ export const DRIFT_PROGRAM_ID = new PublicKey(
 export const JUPITER_PROGRAM_ID = new PublicKey(
   "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4",
 );
+export const CIVIC_GATEWAY_PROGRAM = new PublicKey(
+  "gatem74V238djXdzWnJf94Wo1DcnuGkfijbf3AuBhfs",
+);
 export const SANCTUM_STAKE_POOL_PROGRAM_ID = new PublicKey(
   "SP12tWFxD9oJsVWNavTTBZvMbA6gkAmxtVgxdqvyvhY",
 );
@@ -16,6 +16,15 @@ export const GlamPermissions =
     ?.find((t) => t.name === "Permission")
     ?.type?.variants?.map((v) => v.name) ?? [];
 
+/**
+ * Decodes a role permissions bitset stored onchain, bit i being the i-th Permission variant
+ */
+export function permissionsFromBitset(bitset: BN): Permission[] {
+  return GlamPermissions.filter((_, i) => bitset.testn(i)).map(
+    (p) => ({ [p.slice(0, 1).toLowerCase() + p.slice(1)]: {} }) as Permission,
+  );
+}
+
 const GLAM_PROGRAM_ID_DEFAULT = new PublicKey(GlamIDLJson.address);
 
 export type StateAccountType = { vault: {} } | { mint: {} } | { fund: {} };
@@ -40,13 +49,26 @@ export class StateIdlModel implements StateModelType {
   mints: MintModel[] | null;
   company: CompanyModel | null;
   owner: ManagerModel | null;
+  pendingOwner: PublicKey | null;
   created: CreatedModel | null;
 
   delegateAcls: DelegateAcl[] | null;
+  roles: Role[] | null;
+  delegateRoles: DelegateRoles[] | null;
+  delegateScopes: DelegateScope[] | null;
+  limitedDelegates: PublicKey[] | null;
+  guardian: PublicKey | null;
+  delegatesPaused: boolean | null;
   integrations: Integration[] | null;
   driftMarketIndexesPerp: number[] | null;
   driftMarketIndexesSpot: number[] | null;
   driftOrderTypes: number[] | null;
+  timelockDuration: number | null;
+  riskLimits: RiskLimits | null;
+  driftLimits: DriftLimits | null;
+  subscribeRedeemEnabled: boolean | null;
+  subscriptionsPaused: boolean | null;
+  delegateLimits: DelegateLimits[] | null;
 
   metadata: Metadata | null;
   rawOpenfunds: FundOpenfundsModel | null;
@@ -62,12 +84,29 @@ export class StateIdlModel implements StateModelType {
     this.mints = data.mints ?? null;
     this.company = data.company ?? null;
     this.owner = data.owner ?? null;
+    // @ts-ignore not part of the idl model, set from state params
+    this.pendingOwner = data.pendingOwner ?? null;
     this.created = data.created ?? null;
     this.delegateAcls = data.delegateAcls ?? null;
+    this.roles = data.roles ?? null;
+    this.delegateRoles = data.delegateRoles ?? null;
+    this.delegateScopes = data.delegateScopes ?? null;
+    // @ts-ignore not part of the idl model, set from state params
+    this.limitedDelegates = data.limitedDelegates ?? null;
+    this.guardian = data.guardian ?? null;
+    // @ts-ignore not part of the idl model, set from state params
+    this.delegatesPaused = data.delegatesPaused ?? null;
     this.integrations = data.integrations ?? null;
     this.driftMarketIndexesPerp = data.driftMarketIndexesPerp ?? null;
     this.driftMarketIndexesSpot = data.driftMarketIndexesSpot ?? null;
     this.driftOrderTypes = data.driftOrderTypes ?? null;
+    this.timelockDuration = data.timelockDuration ?? null;
+    this.riskLimits = data.riskLimits ?? null;
+    this.driftLimits = data.driftLimits ?? null;
+    this.subscribeRedeemEnabled = data.subscribeRedeemEnabled ?? null;
+    // @ts-ignore not part of the idl model, set from state params
+    this.subscriptionsPaused = data.subscriptionsPaused ?? null;
+    this.delegateLimits = data.delegateLimits ?? null;
     this.metadata = data.metadata ?? null;
     this.rawOpenfunds = data.rawOpenfunds ?? null;
   }
@@ -172,7 +211,23 @@ export class StateModel extends StateIdlModel {
       const name = Object.keys(param.name)[0];
       // @ts-ignore
       const value = Object.values(param.value)[0].val;
-      if (new StateIdlModel({}).hasOwnProperty(name)) {
+      if (name === "roles") {
+        stateModel.roles = value.map(
+          (role: { name: string; permissions: BN }) =>
+            new Role({
+              name: role.name,
+              permissions: permissionsFromBitset(role.permissions),
+            }),
+        );
+      } else if (name === "delegatePermissions") {
+        stateModel.delegateAcls = value.map(
+          (dp: { pubkey: PublicKey; permissions: BN; expiresAt: BN }) => ({
+            pubkey: dp.pubkey,
+            permissions: permissionsFromBitset(dp.permissions),
+            expiresAt: dp.expiresAt,
+          }),
+        );
+      } else if (new StateIdlModel({}).hasOwnProperty(name)) {
         // @ts-ignore
         stateModel[name] = value;
       } else {
@@ -305,13 +360,17 @@ export class MintIdlModel implements MintModelType {
   statePubkey: PublicKey | null;
   asset: PublicKey | null;
   imageUri: string | null;
+  launchPrice: BN | null;
 
   allowlist: PublicKey[] | null;
   blocklist: PublicKey[] | null;
+  complianceLists: PublicKey[] | null;
 
   lockUpPeriodInSeconds: number | null;
   permanentDelegate: PublicKey | null;
   defaultAccountStateFrozen: boolean | null;
+  kycGatewayProgram: PublicKey | null;
+  kycGatekeeperNetwork: PublicKey | null;
 
   isRawOpenfunds: boolean | null;
   rawOpenfunds: MintOpenfundsModel | null;
@@ -323,13 +382,17 @@ export class MintIdlModel implements MintModelType {
     this.statePubkey = data.statePubkey ?? null;
     this.asset = data.asset ?? null;
     this.imageUri = data.imageUri ?? null;
+    this.launchPrice = data.launchPrice ?? null;
     this.isRawOpenfunds = data.isRawOpenfunds ?? null;
     this.rawOpenfunds = data.rawOpenfunds ?? null;
     this.allowlist = data.allowlist ?? null;
     this.blocklist = data.blocklist ?? null;
+    this.complianceLists = data.complianceLists ?? null;
     this.lockUpPeriodInSeconds = data.lockUpPeriodInSeconds ?? null;
     this.permanentDelegate = data.permanentDelegate ?? null;
     this.defaultAccountStateFrozen = data.defaultAccountStateFrozen ?? null;
+    this.kycGatewayProgram = data.kycGatewayProgram ?? null;
+    this.kycGatekeeperNetwork = data.kycGatekeeperNetwork ?? null;
   }
 }
 export class MintModel extends MintIdlModel {
@@ -497,4 +560,40 @@ export class DelegateAcl implements DelegateAclType {
   }
 }
 
+export type RoleType = IdlTypes<Glam>["role"];
+export class Role implements RoleType {
+  name: string;
+  permissions: Permission[];
+
+  constructor(obj: Partial<RoleType>) {
+    this.name = obj.name!;
+    this.permissions = obj.permissions ?? [];
+  }
+}
+
+export type DelegateRolesType = IdlTypes<Glam>["delegateRoles"];
+export class DelegateRoles implements DelegateRolesType {
+  pubkey: PublicKey;
+  roles: string[];
+
+  constructor(obj: Partial<DelegateRolesType>) {
+    this.pubkey = obj.pubkey!;
+    this.roles = obj.roles ?? [];
+  }
+}
+
+// Restrictions of a delegate on top of the vault-wide ones, empty lists don't restrict
+export type DelegateScope = IdlTypes<Glam>["delegateScope"];
+
+export type InstructionPolicy = IdlTypes<Glam>["instructionPolicy"];
+
+// Weights in bps of the vault AUM, 0 for no limit
+export type RiskLimits = IdlTypes<Glam>["riskLimits"];
+
+// Max perp order size and leverage, min custom margin ratio of drift users, 0 for no limit
+export type DriftLimits = IdlTypes<Glam>["driftLimits"];
+
+// Notional limits of a delegate (USD, 6 decimals), 0 for no limit
+export type DelegateLimits = IdlTypes<Glam>["delegateLimits"];
+
 export type Integration = IdlTypes<Glam>["integration"];
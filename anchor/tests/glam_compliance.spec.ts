@@ -0,0 +1,224 @@
+import { BN, Wallet } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+
+import {
+  stateModelForTest,
+  createGlamStateForTest,
+  str2seed,
+  airdrop,
+} from "./setup";
+import { GlamClient, GlamError, WSOL } from "../src";
+
+// Merkle trees of the allowlist, with leaves keccak(0x00 || pubkey) and nodes
+// keccak(0x01 || min(a, b) || max(a, b)), computed offline:
+// - root 1: alice, bob
+// - root 2: bob, carol
+const ROOT_ALICE_BOB = Buffer.from(
+  "2dbac92a53f3ad29b0c76e8651872b544bb57b8eae64bf8cdc52547b44423545",
+  "hex",
+);
+const ROOT_BOB_CAROL = Buffer.from(
+  "12e3192c0482d56248d669be71786552924a0267618015b49d8beffe01d2b65b",
+  "hex",
+);
+const LEAF_BOB = Buffer.from(
+  "fb2f09fc68afa57fdc82a42a9aaeaace006784239337cfa9aacd497c6a59d7f2",
+  "hex",
+);
+const LEAF_CAROL = Buffer.from(
+  "87535a1b19cf702089605862bac7488873aa4dae889f79b211584ef04ba57720",
+  "hex",
+);
+
+const alice = Keypair.fromSeed(str2seed("compliance-alice"));
+const bob = Keypair.fromSeed(str2seed("compliance-bob"));
+
+describe("glam_compliance", () => {
+  const glamClient = new GlamClient();
+  const connection = glamClient.provider.connection;
+  const owner = glamClient.getSigner();
+  const clientOf = (keypair: Keypair) =>
+    new GlamClient({ wallet: new Wallet(keypair) });
+
+  const allowlistId = str2seed("allowlist").slice(0, 8);
+  const blocklistId = str2seed("blocklist").slice(0, 8);
+  const allowlist = glamClient.getComplianceListPda(owner, allowlistId);
+  const blocklist = glamClient.getComplianceListPda(owner, blocklistId);
+
+  const stateModel = {
+    ...stateModelForTest,
+    name: "Glam Compliance Lists",
+    assets: [WSOL],
+    mints: [
+      {
+        ...stateModelForTest.mints![0],
+        asset: WSOL,
+        complianceLists: [allowlist, blocklist],
+      },
+    ],
+  };
+  const statePda = glamClient.getStatePda(stateModel);
+
+  const amount = new BN(1_000_000_000);
+
+  const updateList = (
+    list: PublicKey,
+    added: PublicKey[],
+    removed: PublicKey[],
+    merkleRoot: Buffer | null,
+  ) =>
+    glamClient.program.methods
+      .updateComplianceList(
+        added,
+        removed,
+        merkleRoot ? Array.from(merkleRoot) : null,
+      )
+      .accounts({ complianceList: list, owner })
+      .rpc();
+
+  const proveMembership = (member: PublicKey, proof: Buffer[]) =>
+    glamClient.program.methods
+      .proveComplianceMembership(
+        member,
+        proof.map((node) => Array.from(node)),
+      )
+      .accounts({ complianceList: allowlist, payer: owner })
+      .rpc();
+
+  const expectSubscribeError = async (keypair: Keypair, message: string) => {
+    try {
+      const txSig = await clientOf(keypair).investor.subscribe(
+        statePda,
+        WSOL,
+        amount,
+      );
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(message);
+    }
+  };
+
+  beforeAll(async () => {
+    await airdrop(connection, alice.publicKey, 10_000_000_000);
+    await airdrop(connection, bob.publicKey, 10_000_000_000);
+
+    await glamClient.program.methods
+      .createComplianceList(Array.from(allowlistId), { allowlist: {} })
+      .accounts({ owner })
+      .rpc();
+    await glamClient.program.methods
+      .createComplianceList(Array.from(blocklistId), { blocklist: {} })
+      .accounts({ owner })
+      .rpc();
+    await updateList(allowlist, [], [], ROOT_ALICE_BOB);
+
+    await createGlamStateForTest(glamClient, stateModel);
+  }, 30_000);
+
+  it("[blocklist] merkle roots are rejected", async () => {
+    try {
+      const txSig = await updateList(blocklist, [], [], ROOT_ALICE_BOB);
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Merkle roots are only supported by allowlists.",
+      );
+    }
+  });
+
+  it("[allowlist] member must prove membership before subscribing", async () => {
+    await expectSubscribeError(alice, "Policy violation: account not allowed.");
+
+    try {
+      await proveMembership(alice.publicKey, [LEAF_BOB]);
+      await clientOf(alice).investor.subscribe(statePda, WSOL, amount);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+  });
+
+  it("[blocklist] blocked wallet can't subscribe until removed", async () => {
+    try {
+      await updateList(blocklist, [alice.publicKey], [], null);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    await expectSubscribeError(alice, "Policy violation: account not allowed.");
+
+    try {
+      await updateList(blocklist, [], [alice.publicKey], null);
+      await clientOf(alice).investor.subscribe(statePda, WSOL, amount);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+  });
+
+  it("[allowlist] root update invalidates proofs against the old root", async () => {
+    try {
+      await updateList(allowlist, [], [], ROOT_BOB_CAROL);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    // alice's record was proven against the old root, and she's not in the new tree
+    await expectSubscribeError(alice, "Policy violation: account not allowed.");
+    try {
+      const txSig = await proveMembership(alice.publicKey, [LEAF_BOB]);
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual("Invalid merkle proof.");
+    }
+
+    // bob is in the new tree
+    await expectSubscribeError(bob, "Policy violation: account not allowed.");
+    try {
+      await proveMembership(bob.publicKey, [LEAF_CAROL]);
+      await clientOf(bob).investor.subscribe(statePda, WSOL, amount);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+  });
+
+  it("[list] can't be closed while a share class references it", async () => {
+    const closeList = (list: PublicKey) =>
+      glamClient.program.methods
+        .closeComplianceList()
+        .accounts({ complianceList: list, owner })
+        .rpc();
+
+    for (const list of [allowlist, blocklist]) {
+      const account =
+        await glamClient.program.account.complianceListAccount.fetch(list);
+      expect(account.shareClasses).toEqual(1);
+
+      try {
+        const txSig = await closeList(list);
+        expect(txSig).toBeUndefined();
+      } catch (e) {
+        expect((e as GlamError).message).toEqual(
+          "Compliance list is referenced by share classes.",
+        );
+      }
+    }
+
+    // a list no share class references can be closed
+    const unusedListId = str2seed("unused-list").slice(0, 8);
+    const unusedList = glamClient.getComplianceListPda(owner, unusedListId);
+    try {
+      await glamClient.program.methods
+        .createComplianceList(Array.from(unusedListId), { blocklist: {} })
+        .accounts({ owner })
+        .rpc();
+      await closeList(unusedList);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    expect(await connection.getAccountInfo(unusedList)).toBeNull();
+  });
+});
@@ -3,10 +3,16 @@ import {
   PublicKey,
   SystemProgram,
   Transaction,
+  TransactionInstruction,
 } from "@solana/web3.js";
 import { BN, Wallet } from "@coral-xyz/anchor";
 
-import { createGlamStateForTest, stateModelForTest, str2seed } from "./setup";
+import {
+  createGlamStateForTest,
+  sleep,
+  stateModelForTest,
+  str2seed,
+} from "./setup";
 import {
   StateModel,
   GlamClient,
@@ -20,6 +26,9 @@ import { createAssociatedTokenAccountIdempotentInstruction } from "@solana/spl-t
 
 const key1 = Keypair.fromSeed(str2seed("acl_test_key1"));
 const key2 = Keypair.fromSeed(str2seed("acl_test_key2"));
+const MEMO_PROGRAM_ID = new PublicKey(
+  "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+);
 
 describe("glam_crud", () => {
   const glamClient = new GlamClient();
@@ -180,6 +189,9 @@ describe("glam_crud", () => {
     expect(stateModel.delegateAcls![0].pubkey).toEqual(key1.publicKey);
     expect(stateModel.delegateAcls![0].permissions).toEqual([{ wSolWrap: {} }]);
     expect(stateModel.delegateAcls![0].expiresAt).toEqual(new BN(0));
+    // stored as a permissions bitset in the state params
+    const stateAccount = await glamClient.fetchStateAccount(statePda);
+    expect(stateAccount.delegateAcls).toEqual([]);
 
     // grant key1 wSolWrap and wSolUnwrap permission
     const expiresAt = new BN(Date.now() / 1000 + 60);
@@ -299,6 +311,377 @@ describe("glam_crud", () => {
     }
   }, 15_000);
 
+  it("[roles] delegate permissions from roles", async () => {
+    try {
+      await glamClient.state.upsertRoles(statePda, [
+        { name: "unwrapper", permissions: [{ wSolUnwrap: {} }] },
+      ]);
+      await glamClient.state.upsertDelegateRoles(statePda, [
+        { pubkey: key1.publicKey, roles: ["unwrapper"] },
+      ]);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    let stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.roles?.length).toEqual(1);
+    expect(stateModel.delegateRoles![0].roles).toEqual(["unwrapper"]);
+
+    // key1 now has wSolUnwrap from its role, on top of its wSolWrap permission
+    try {
+      const txSig = await glamClientCustomWallet.wsol.unwrap(statePda);
+      console.log("Unwrap:", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    // deleting the role revokes it from all its delegates
+    try {
+      await glamClient.state.upsertRoles(statePda, [
+        { name: "unwrapper", permissions: [] },
+      ]);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.roles).toBeNull();
+    expect(stateModel.delegateRoles).toBeNull();
+  });
+
+  it("[delegate-scopes] upsert and delete", async () => {
+    const scope = {
+      pubkey: key1.publicKey,
+      assets: [WSOL, USDC],
+      driftMarketIndexesPerp: [0],
+      driftMarketIndexesSpot: [],
+      driftOrderTypes: [],
+      meteoraPairs: [],
+      kaminoReserves: [],
+    };
+    try {
+      await glamClient.state.upsertDelegateScopes(statePda, [scope]);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    let stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.delegateScopes).toEqual([scope]);
+
+    try {
+      await glamClient.state.upsertDelegateScopes(statePda, [
+        { ...scope, assets: [], driftMarketIndexesPerp: [] },
+      ]);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.delegateScopes).toBeNull();
+  });
+
+  it("[delegate-limits] set and remove", async () => {
+    try {
+      const txSig = await glamClient.state.setDelegateLimits(
+        statePda,
+        key1.publicKey,
+        new BN(1_000_000_000), // $1k per tx
+        new BN(10_000_000_000), // $10k per day
+      );
+      console.log("Set delegate limits txSig", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    let stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.limitedDelegates).toEqual([key1.publicKey]);
+
+    const delegateUsage =
+      await glamClient.program.account.delegateUsageAccount.fetch(
+        glamClient.getDelegateUsagePda(statePda, key1.publicKey),
+      );
+    expect(delegateUsage.maxNotionalPerTx.toNumber()).toEqual(1_000_000_000);
+    expect(delegateUsage.maxNotionalPerDay.toNumber()).toEqual(10_000_000_000);
+
+    try {
+      await glamClient.state.setDelegateLimits(
+        statePda,
+        key1.publicKey,
+        new BN(0),
+        new BN(0),
+      );
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.limitedDelegates).toEqual([]);
+  });
+
+  it("[guardian] pause delegates", async () => {
+    try {
+      await glamClient.state.updateState(statePda, {
+        guardian: key1.publicKey,
+      });
+      // the guardian can pause, but not unpause
+      await glamClientCustomWallet.state.setDelegatesPaused(statePda, true);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    let stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.guardian).toEqual(key1.publicKey);
+    expect(stateModel.delegatesPaused).toEqual(true);
+
+    try {
+      const txSig = await glamClientCustomWallet.state.setDelegatesPaused(
+        statePda,
+        false,
+      );
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual("Signer is not authorized.");
+    }
+
+    try {
+      await glamClient.state.setDelegatesPaused(statePda, false);
+      await glamClient.state.updateState(statePda, {
+        guardian: PublicKey.default,
+      });
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.guardian).toBeNull();
+    expect(stateModel.delegatesPaused).toEqual(false);
+  });
+
+  it("[risk-limits] set and remove risk limits", async () => {
+    const riskLimits = {
+      maxAssetWeightBps: 5_000,
+      maxLstWeightBps: 8_000,
+      minBaseAssetWeightBps: 1_000,
+    };
+    try {
+      await glamClient.state.updateState(statePda, { riskLimits });
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    let stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.riskLimits).toEqual(riskLimits);
+
+    try {
+      const txSig = await glamClient.state.updateState(statePda, {
+        riskLimits: { ...riskLimits, maxAssetWeightBps: 10_001 },
+      });
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Invalid risk limits: max 10000 bps.",
+      );
+    }
+
+    try {
+      await glamClient.state.updateState(statePda, {
+        riskLimits: {
+          maxAssetWeightBps: 0,
+          maxLstWeightBps: 0,
+          minBaseAssetWeightBps: 0,
+        },
+      });
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.riskLimits).toBeNull();
+  });
+
+  it("[drift-limits] set and remove drift limits", async () => {
+    const driftLimits = {
+      maxOrderBaseAssetAmount: new BN(10_000_000_000),
+      maxLeverageBps: 30_000, // 3x
+      minCustomMarginRatio: 2_000,
+    };
+    try {
+      await glamClient.state.updateState(statePda, { driftLimits });
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    let stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.driftLimits?.maxOrderBaseAssetAmount.toString()).toEqual(
+      "10000000000",
+    );
+    expect(stateModel.driftLimits?.maxLeverageBps).toEqual(30_000);
+    expect(stateModel.driftLimits?.minCustomMarginRatio).toEqual(2_000);
+
+    try {
+      const txSig = await glamClient.state.updateState(statePda, {
+        driftLimits: { ...driftLimits, maxLeverageBps: 5_000 },
+      });
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Invalid drift limits: min leverage 1x, max margin ratio 10000.",
+      );
+    }
+
+    try {
+      await glamClient.state.updateState(statePda, {
+        driftLimits: {
+          maxOrderBaseAssetAmount: new BN(0),
+          maxLeverageBps: 0,
+          minCustomMarginRatio: 0,
+        },
+      });
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.driftLimits).toBeNull();
+  });
+
+  it("[admin-permissions] delegate manages allowlist", async () => {
+    try {
+      await glamClient.state.updateState(statePda, {
+        delegateAcls: [
+          {
+            pubkey: key1.publicKey,
+            permissions: [{ wSolWrap: {} }, { manageAllowlist: {} }],
+            expiresAt: new BN(0),
+          },
+        ],
+      });
+      await glamClientCustomWallet.mint.updateAllowlist(
+        statePda,
+        0,
+        [],
+        [key2.publicKey],
+      );
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.mints![0].allowlist).toEqual([key1.publicKey]);
+
+    // key1 doesn't have manageBlocklist permission
+    try {
+      const txSig = await glamClientCustomWallet.mint.updateBlocklist(
+        statePda,
+        0,
+        [key2.publicKey],
+        [],
+      );
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual("Signer is not authorized.");
+    }
+  });
+
+  it("[cpi-policy] execute allowed instructions only", async () => {
+    const glamVault = glamClient.getVaultPda(statePda);
+    const memo = new TransactionInstruction({
+      programId: MEMO_PROGRAM_ID,
+      keys: [{ pubkey: glamVault, isSigner: true, isWritable: false }],
+      data: Buffer.from("glam"),
+    });
+    try {
+      // memos signed by the vault
+      await glamClient.state.setCpiPolicy(statePda, MEMO_PROGRAM_ID, [
+        {
+          discriminator: Buffer.from("glam"),
+          accountSlots: [{ index: 0, constraint: { vault: {} } }],
+        },
+      ]);
+      // system program transfers from the vault to key1
+      await glamClient.state.setCpiPolicy(statePda, SystemProgram.programId, [
+        {
+          discriminator: Buffer.from([2, 0, 0, 0]),
+          accountSlots: [
+            { index: 0, constraint: { vault: {} } },
+            { index: 1, constraint: { pubkey: { val: key1.publicKey } } },
+          ],
+        },
+      ]);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    // the integration must be enabled
+    try {
+      const txSig = await glamClient.state.executeCpi(statePda, memo);
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual("Integration is disabled.");
+    }
+
+    const { integrations } = await glamClient.fetchState(statePda);
+    try {
+      await glamClient.state.updateState(statePda, {
+        integrations: [...(integrations || []), { executeCpi: {} }],
+      });
+      const txSig = await glamClient.state.executeCpi(statePda, memo);
+      console.log("Execute CPI txSig", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    try {
+      const txSig = await glamClient.state.executeCpi(
+        statePda,
+        SystemProgram.transfer({
+          fromPubkey: glamVault,
+          toPubkey: key2.publicKey,
+          lamports: 1_000,
+        }),
+      );
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "CPI not allowed by the policy.",
+      );
+    }
+
+    // allowed by the policy, but the vault balance guard reverts the drain
+    try {
+      const txSig = await glamClient.state.executeCpi(
+        statePda,
+        SystemProgram.transfer({
+          fromPubkey: glamVault,
+          toPubkey: key1.publicKey,
+          lamports: 1_000,
+        }),
+      );
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Vault balance violation: value left the vault's control.",
+      );
+    }
+
+    // closing the policy applies immediately
+    try {
+      await glamClient.state.closeCpiPolicy(statePda, MEMO_PROGRAM_ID);
+      await glamClient.state.closeCpiPolicy(statePda, SystemProgram.programId);
+      await glamClient.state.updateState(statePda, { integrations });
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const closed = await glamClient.provider.connection.getAccountInfo(
+      glamClient.getCpiPolicyPda(statePda, MEMO_PROGRAM_ID),
+    );
+    expect(closed).toBeNull();
+  });
+
   it("[drift-market-allowlists] upsert", async () => {
     let stateModel = await glamClient.fetchState(statePda);
     expect(stateModel.driftMarketIndexesPerp).toBeNull();
@@ -341,12 +724,32 @@ describe("glam_crud", () => {
     };
     try {
       const txSig = await glamClient.state.updateState(statePda, updated);
-      console.log("Owner updated from default to new", txSig);
+      console.log("Owner transfer proposed from default to new", txSig);
     } catch (e) {
       console.error(e);
       throw e;
     }
+    // the owner doesn't change until the new owner accepts
     let glamState = await glamClient.fetchStateAccount(statePda);
+    expect(glamState.owner).toEqual(glamClient.getSigner());
+    let stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.pendingOwner).toEqual(key1.publicKey);
+
+    // only the pending owner CAN accept
+    try {
+      const txSig = await glamClient.state.acceptOwnership(statePda);
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual("Signer is not authorized.");
+    }
+    try {
+      const txSig = await glamClientCustomWallet.state.acceptOwnership(statePda);
+      console.log("Owner updated from default to new", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    glamState = await glamClient.fetchStateAccount(statePda);
     expect(glamState.owner).toEqual(key1.publicKey);
 
     // previous owner CAN NOT update
@@ -368,7 +771,8 @@ describe("glam_crud", () => {
           kind: { wallet: {} },
         },
       });
-      console.log("Owner updated from new to default", txId);
+      console.log("Owner transfer proposed from new to default", txId);
+      await glamClient.state.acceptOwnership(statePda);
     } catch (e) {
       console.error(e);
       throw e;
@@ -377,6 +781,201 @@ describe("glam_crud", () => {
     expect(glamState.owner).toEqual(glamClient.getSigner());
   });
 
+  it("[ownership] Cancel ownership transfer", async () => {
+    try {
+      await glamClient.state.updateState(statePda, {
+        owner: {
+          portfolioManagerName: "Typo",
+          pubkey: key2.publicKey,
+          kind: { wallet: {} },
+        },
+      });
+      const txSig = await glamClient.state.cancelOwnershipTransfer(statePda);
+      console.log("Ownership transfer cancelled", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.pendingOwner).toBeNull();
+    const glamState = await glamClient.fetchStateAccount(statePda);
+    expect(glamState.owner).toEqual(glamClient.getSigner());
+  });
+
+  it("[timelock] Guardian, pausing and relaxing delegate limits are queued", async () => {
+    const timelockError = "State update is timelocked, it must be queued.";
+    try {
+      await glamClient.state.updateState(statePda, { timelockDuration: 1 });
+      // tightening delegate limits applies immediately
+      await glamClient.state.setDelegateLimits(
+        statePda,
+        key1.publicKey,
+        new BN(1_000_000_000),
+        new BN(10_000_000_000),
+      );
+      await glamClient.state.setDelegateLimits(
+        statePda,
+        key1.publicKey,
+        new BN(500_000_000),
+        new BN(10_000_000_000),
+      );
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    try {
+      const txSig = await glamClient.state.setDelegateLimits(
+        statePda,
+        key1.publicKey,
+        new BN(0),
+        new BN(10_000_000_000),
+      );
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(timelockError);
+    }
+
+    try {
+      const txSig = await glamClient.state.updateState(statePda, {
+        guardian: key2.publicKey,
+      });
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(timelockError);
+    }
+
+    // pausing applies immediately to subscriptions only
+    try {
+      await glamClient.state.setSubscribeRedeemEnabled(statePda, false);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const paused = await glamClient.fetchState(statePda);
+    expect(paused.subscriptionsPaused).toEqual(true);
+    expect(paused.externalVaultAccounts || []).not.toContainEqual(
+      SystemProgram.programId,
+    );
+
+    try {
+      await glamClient.state.queueStateUpdate(statePda, {
+        guardian: key2.publicKey,
+        subscribeRedeemEnabled: false,
+        delegateLimits: [
+          {
+            delegate: key1.publicKey,
+            maxNotionalPerTx: new BN(0),
+            maxNotionalPerDay: new BN(0),
+          },
+        ],
+      });
+      await sleep(3_000);
+      const txSig = await glamClient.state.applyStateUpdate(statePda);
+      console.log("Queued update applied", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const stateModel = await glamClient.fetchState(statePda);
+    expect(stateModel.guardian).toEqual(key2.publicKey);
+    expect(stateModel.limitedDelegates).toEqual([]);
+    expect(stateModel.externalVaultAccounts).toContainEqual(
+      SystemProgram.programId,
+    );
+    expect(stateModel.subscriptionsPaused).toBeNull();
+
+    // resuming applies immediately, removing the timelock is queued
+    try {
+      await glamClient.state.setSubscribeRedeemEnabled(statePda, true);
+      await glamClient.state.queueStateUpdate(statePda, {
+        guardian: PublicKey.default,
+        timelockDuration: 0,
+      });
+      await sleep(3_000);
+      await glamClient.state.applyStateUpdate(statePda);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const updated = await glamClient.fetchState(statePda);
+    expect(updated.timelockDuration).toBeNull();
+    expect(updated.guardian).toBeNull();
+  }, 20_000);
+
+  it("[timelock] Sensitive updates are queued", async () => {
+    try {
+      // setting a timelock applies immediately
+      await glamClient.state.updateState(statePda, { timelockDuration: 3600 });
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    try {
+      const txSig = await glamClient.state.updateState(statePda, {
+        assets: [WSOL],
+      });
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "State update is timelocked, it must be queued.",
+      );
+    }
+
+    // tightening limits and removing integrations apply immediately
+    try {
+      await glamClient.state.updateState(statePda, {
+        riskLimits: {
+          maxAssetWeightBps: 5_000,
+          maxLstWeightBps: 0,
+          minBaseAssetWeightBps: 0,
+        },
+        integrations: [],
+      });
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const tightened = await glamClient.fetchState(statePda);
+    expect(tightened.riskLimits?.maxAssetWeightBps).toEqual(5_000);
+    expect(tightened.integrations).toEqual([]);
+
+    try {
+      const txSig = await glamClient.state.updateState(statePda, {
+        riskLimits: {
+          maxAssetWeightBps: 0,
+          maxLstWeightBps: 0,
+          minBaseAssetWeightBps: 0,
+        },
+      });
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "State update is timelocked, it must be queued.",
+      );
+    }
+
+    try {
+      await glamClient.state.queueStateUpdate(statePda, { assets: [WSOL] });
+      const txSig = await glamClient.state.applyStateUpdate(statePda);
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual("Timelock has not expired.");
+    }
+
+    try {
+      await glamClient.state.cancelStateUpdate(statePda);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const pending = await glamClient.provider.connection.getAccountInfo(
+      glamClient.getPendingStateUpdatePda(statePda),
+    );
+    expect(pending).toBeNull();
+  });
+
   it("Close token accounts", async () => {
     // Create empty token accounts
     const transaction = new Transaction();
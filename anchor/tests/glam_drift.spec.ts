@@ -1,5 +1,5 @@
 import * as anchor from "@coral-xyz/anchor";
-import { DriftMarketConfigs, GlamClient } from "../src";
+import { DriftMarketConfigs, GlamClient, GlamError } from "../src";
 import { airdrop, createGlamStateForTest, stateModelForTest } from "./setup";
 import {
   DriftClient,
@@ -349,4 +349,94 @@ describe("glam_drift", () => {
       expect(errMsg).toContain("Signer is not authorized");
     }
   });
+
+  it("Drift: set drift limits", async () => {
+    try {
+      await glamClient.state.updateState(statePda, {
+        driftMarketIndexesPerp: [],
+        driftLimits: {
+          maxOrderBaseAssetAmount: new anchor.BN(1_000_000_000), // 1 SOL
+          maxLeverageBps: 0,
+          minCustomMarginRatio: 2_000, // 5x
+        },
+      });
+      const txId = await glamClient.drift.updateUserCustomMarginRatio(
+        statePda,
+        5,
+      );
+      console.log("driftUpdateUserCustomMarginRatio 5x", txId);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+  });
+
+  it("Drift: raise leverage above the limit - should fail", async () => {
+    try {
+      const txId = await glamClient.drift.updateUserCustomMarginRatio(
+        statePda,
+        10,
+      );
+      expect(txId).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Drift custom margin ratio below the vault minimum.",
+      );
+    }
+  });
+
+  it("Drift: place perp order above the max size - should fail", async () => {
+    const orderParams = getOrderParams({
+      orderType: OrderType.LIMIT,
+      marketType: MarketType.PERP,
+      direction: PositionDirection.LONG,
+      marketIndex: 0,
+      baseAssetAmount: new anchor.BN(2_000_000_000),
+      price: new anchor.BN(100_000_000), // set a very low limit price
+    });
+
+    try {
+      const txId = await glamClient.drift.placeOrder(
+        statePda,
+        orderParams,
+        0,
+        marketConfigs,
+      );
+      expect(txId).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Drift order size above the vault limit.",
+      );
+    }
+  });
+
+  it("Drift: modify order above the max size - should fail", async () => {
+    const orderParams = getOrderParams({
+      orderType: OrderType.LIMIT,
+      marketType: MarketType.PERP,
+      direction: PositionDirection.LONG,
+      marketIndex: 0,
+      baseAssetAmount: new anchor.BN(100_000_000),
+      price: new anchor.BN(100_000_000), // set a very low limit price
+    });
+    const modifyOrderParams = { ...orderParams } as ModifyOrderParams;
+    Object.keys(orderParams).forEach((key) => (modifyOrderParams[key] = null));
+    modifyOrderParams.baseAssetAmount = new anchor.BN(2_000_000_000);
+    // @ts-ignore
+    modifyOrderParams.policy = null;
+
+    try {
+      const txId = await glamClient.drift.modifyOrder(
+        statePda,
+        modifyOrderParams,
+        0,
+        marketConfigs,
+      );
+      expect(txId).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Drift order size above the vault limit.",
+      );
+    }
+  });
 });
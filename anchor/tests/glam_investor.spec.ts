@@ -257,7 +257,8 @@ describe("glam_investor", () => {
       commitment,
       TOKEN_2022_PROGRAM_ID,
     );
-    expect(managerShares.amount).toEqual(shares.supply);
+    // the first subscription minted 1000 dead shares to the share class itself
+    expect(managerShares.amount).toEqual(shares.supply - 1000n);
   }, 15_000);
 
   it("Invalid share class disallowed", async () => {
@@ -281,15 +282,16 @@ describe("glam_investor", () => {
         ASSOCIATED_TOKEN_PROGRAM_ID,
       );
       const txId = await glamClient.program.methods
-        .subscribe(0, new BN(1 * 10 ** 8), true)
+        .subscribe(0, new BN(1 * 10 ** 8), null, true)
         .accounts({
           glamState: statePda,
           glamMint: invalidShareClass,
           // glamVault: vaultPda,
-          // signerShareAta: shareAta,
+          // beneficiaryShareAta: shareAta,
           asset: btc.publicKey,
           vaultAta: vaultEthAta,
           signerAssetAta: managerEthAta,
+          beneficiary: wallet.publicKey,
           signer: wallet.publicKey,
           // tokenProgram: TOKEN_PROGRAM_ID,
           // token2022Program: TOKEN_2022_PROGRAM_ID,
@@ -340,7 +342,7 @@ describe("glam_investor", () => {
       commitment,
       TOKEN_2022_PROGRAM_ID,
     );
-    expect(managerShares.amount).toEqual(shares.supply);
+    expect(managerShares.amount).toEqual(shares.supply - 1000n);
   });
 
   it("Manager redeems 50% of fund", async () => {
@@ -375,7 +377,7 @@ describe("glam_investor", () => {
       commitment,
       TOKEN_2022_PROGRAM_ID,
     );
-    expect(managerShares.amount).toEqual(shares.supply);
+    expect(managerShares.amount).toEqual(shares.supply - 1000n);
   });
 
   it("Manager adds more tokens and redeems USDC", async () => {
@@ -475,6 +477,65 @@ describe("glam_investor", () => {
     expect(oldAmountSol).toEqual(newAmountSol);
   });
 
+  it("Manager redemption fails below min amounts out", async () => {
+    const stateModel = await glamClient.fetchState(statePda);
+    // 1 share (~$100) can't pay out 1M USDC
+    const minAmountsOut = (stateModel.assets || []).map((asset) =>
+      asset.equals(usdc.publicKey) ? new BN(10 ** 12) : new BN(0),
+    );
+    try {
+      const txId = await glamClient.investor.redeem(
+        statePda,
+        new BN(10 ** 9),
+        false,
+        undefined,
+        0,
+        true,
+        {},
+        [],
+        false,
+        minAmountsOut,
+      );
+      expect(txId).toBeUndefined();
+    } catch (err) {
+      expect(err.message).toContain("less than the minimum amount");
+    }
+  });
+
+  it("Manager redeems into USDC, falling back to BTC", async () => {
+    // 10 shares (~$1000) is more than the USDC left in the vault
+    const amount = new BN(10 * 10 ** 9);
+    const oldAmountBtc = (
+      await getAccount(connection, vaultBtcAta, commitment, BTC_TOKEN_PROGRAM_ID)
+    ).amount;
+    try {
+      const txId = await glamClient.investor.redeem(
+        statePda,
+        amount,
+        false,
+        undefined,
+        0,
+        true,
+        {},
+        [usdc.publicKey, btc.publicKey],
+      );
+      console.log("redeem USDC, BTC:", txId);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    const newAmountUsdc = (
+      await getAccount(connection, vaultUsdcAta, commitment, TOKEN_PROGRAM_ID)
+    ).amount;
+    const newAmountBtc = (
+      await getAccount(connection, vaultBtcAta, commitment, BTC_TOKEN_PROGRAM_ID)
+    ).amount;
+    // all usdc is redeemed, the rest is paid in btc
+    expect(newAmountUsdc.toString()).toEqual("0");
+    expect(oldAmountBtc).toBeGreaterThan(newAmountBtc);
+  });
+
   it("Manager redeems 100% of fund", async () => {
     let shares = await getMint(
       connection,
@@ -482,7 +543,8 @@ describe("glam_investor", () => {
       commitment,
       TOKEN_2022_PROGRAM_ID,
     );
-    const amount = new BN(shares.supply.toString());
+    // all shares but the dead shares, which are burned
+    const amount = new BN((shares.supply - 1000n).toString());
     try {
       const txId = await glamClient.investor.redeem(statePda, amount, true);
       console.log("redeem 100%:", txId);
@@ -565,6 +627,26 @@ describe("glam_investor", () => {
     expect((Number(shares.supply) / 1e9).toFixed(2)).toEqual("2.50");
   });
 
+  it("Alice subscription fails below min shares out", async () => {
+    const amount = new BN(250 * 10 ** 6); // ~2.5 shares
+    try {
+      const txId = await glamClientAlice.investor.subscribe(
+        statePda,
+        usdc.publicKey,
+        amount,
+        undefined,
+        0,
+        true,
+        {},
+        undefined,
+        new BN(3 * 10 ** 9),
+      );
+      expect(txId).toBeUndefined();
+    } catch (err) {
+      expect(err.message).toContain("fewer shares than the minimum");
+    }
+  });
+
   it("Bob is not allowed to subscribe", async () => {
     const amount = new BN(250 * 10 ** 6); // USDC has 6 decimals
     try {
@@ -580,6 +662,62 @@ describe("glam_investor", () => {
     }
   });
 
+  it("Alice is not allowed to subscribe on behalf of Bob", async () => {
+    const amount = new BN(250 * 10 ** 6); // USDC has 6 decimals
+    try {
+      const txId = await glamClientAlice.investor.subscribe(
+        statePda,
+        usdc.publicKey,
+        amount,
+        undefined,
+        0,
+        true,
+        {},
+        bob.publicKey,
+      );
+      console.log("tx:", txId);
+      expect(txId).toBeUndefined();
+    } catch (err) {
+      expect(err.message).toContain("Share class not allowed to subscribe");
+    }
+  });
+
+  it("Manager subscribes on behalf of Alice", async () => {
+    const amount = new BN(100 * 10 ** 6); // ~1 share
+    const aliceSharesAta = glamClient.getMintAta(alice.publicKey, mintPda);
+    const sharesBefore = await getAccount(
+      connection,
+      aliceSharesAta,
+      commitment,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    try {
+      const txId = await glamClient.investor.subscribe(
+        statePda,
+        usdc.publicKey,
+        amount,
+        undefined,
+        0,
+        true,
+        {},
+        alice.publicKey,
+      );
+      console.log("tx:", txId);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    // shares are minted to alice, who didn't need to sign (no lock-up)
+    const sharesAfter = await getAccount(
+      connection,
+      aliceSharesAta,
+      commitment,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    expect(sharesAfter.amount).toBeGreaterThan(sharesBefore.amount);
+  });
+
   it("Eve is not allowed to subscribe", async () => {
     const amount = new BN(250 * 10 ** 6); // USDC has 6 decimals
     try {
@@ -595,6 +733,105 @@ describe("glam_investor", () => {
     }
   });
 
+  it("Alice subscribes in kind with a basket not matching portfolio weights", async () => {
+    // vault only holds USDC, basket is USDC + BTC
+    const amounts = [new BN(100 * 10 ** 6), new BN(1 * 10 ** 5), new BN(0)];
+    try {
+      const txId = await glamClientAlice.investor.subscribeInKind(
+        statePda,
+        amounts,
+        true,
+      );
+      console.log("tx:", txId);
+      expect(txId).toBeUndefined();
+    } catch (err) {
+      expect(err.message).toContain("doesn't match the portfolio weights");
+    }
+  });
+
+  it("Alice in-kind subscription fails below min shares out", async () => {
+    // ~1 share of USDC, the only asset held by the vault
+    const amounts = [new BN(100 * 10 ** 6), new BN(0), new BN(0)];
+    try {
+      const txId = await glamClientAlice.investor.subscribeInKind(
+        statePda,
+        amounts,
+        false,
+        undefined,
+        0,
+        {},
+        undefined,
+        new BN(2 * 10 ** 9),
+      );
+      expect(txId).toBeUndefined();
+    } catch (err) {
+      expect(err.message).toContain("fewer shares than the minimum");
+    }
+  });
+
+  it("Alice redeems into native SOL when the unwrap account is prefunded", async () => {
+    const aliceSharesAta = glamClient.getMintAta(alice.publicKey, mintPda);
+    const getAliceShares = async () =>
+      (
+        await getAccount(
+          connection,
+          aliceSharesAta,
+          commitment,
+          TOKEN_2022_PROGRAM_ID,
+        )
+      ).amount;
+
+    // alice has no wSOL, she subscribes with native SOL
+    const sharesBefore = await getAliceShares();
+    try {
+      const txId = await glamClientAlice.investor.subscribe(
+        statePda,
+        WSOL,
+        new BN(5 * 10 ** 8),
+      );
+      console.log("alice subscribes with native SOL:", txId);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const shares = (await getAliceShares()) - sharesBefore;
+
+    // anyone can transfer lamports to the temporary wSOL account used to pay native SOL
+    const wsolUnwrap = glamClient.getWsolUnwrapPda(statePda);
+    const tx = new Transaction().add(
+      SystemProgram.transfer({
+        fromPubkey: wallet.publicKey,
+        toPubkey: wsolUnwrap,
+        lamports: 1_000_000,
+      }),
+    );
+    await sendAndConfirmTransaction(connection, tx, [wallet.payer]);
+
+    const aliceLamportsBefore = await connection.getBalance(alice.publicKey);
+    try {
+      const txId = await glamClientAlice.investor.redeem(
+        statePda,
+        new BN(shares.toString()),
+        false,
+        undefined,
+        0,
+        true,
+        {},
+        [WSOL],
+        true,
+      );
+      console.log("alice redeems into native SOL:", txId);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const aliceLamportsAfter = await connection.getBalance(alice.publicKey);
+    expect(aliceLamportsAfter - aliceLamportsBefore).toBeGreaterThan(
+      0.45 * 10 ** 9,
+    );
+    expect(await connection.getAccountInfo(wsolUnwrap)).toBeNull();
+  }, 15_000);
+
   it("Manager subscribes/redeems when marinade ticket exists", async () => {
     try {
       const airdropTx = await connection.requestAirdrop(vaultPda, 10 ** 9);
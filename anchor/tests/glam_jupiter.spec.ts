@@ -7,9 +7,13 @@ import {
   stateModelForTest,
   sleep,
 } from "./setup";
-import { GlamClient, JUP_VOTE_PROGRAM } from "../src";
+import { GlamClient, GlamError, JUP, JUP_VOTE_PROGRAM } from "../src";
 import { Keypair } from "@solana/web3.js";
-import { getAccount } from "@solana/spl-token";
+import {
+  createAssociatedTokenAccountIdempotentInstruction,
+  getAccount,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import { BN, Wallet } from "@coral-xyz/anchor";
 import { MSOL, WSOL } from "../src";
 import { PublicKey } from "@solana/web3.js";
@@ -509,4 +513,39 @@ describe("glam_jupiter", () => {
       throw e;
     }
   });
+
+  it("Withdraw JUP to a token account outside the vault", async () => {
+    const vault = glamClient.getVaultPda(statePda);
+    const escrow = glamClient.jupiterVote.getEscrowPda(vault);
+    const signer = glamClient.getSigner();
+    const signerJupAta = glamClient.getAta(JUP, signer);
+    try {
+      const tx = await glamClient.program.methods
+        .jupiterVoteWithdraw()
+        .accounts({
+          glamState: statePda,
+          locker: glamClient.jupiterVote.stakeLocker,
+          escrow,
+          escrowTokens: glamClient.getAta(JUP, escrow),
+          destinationTokens: signerJupAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .preInstructions([
+          createAssociatedTokenAccountIdempotentInstruction(
+            signer,
+            signerJupAta,
+            signer,
+            JUP,
+          ),
+        ])
+        .transaction();
+      const vTx = await glamClient.intoVersionedTransaction(tx);
+      const txSig = await glamClient.sendAndConfirm(vTx);
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Vault balance violation: value left the vault's control.",
+      );
+    }
+  });
 });
@@ -0,0 +1,199 @@
+import { BN, Wallet } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+
+import {
+  stateModelForTest,
+  createGlamStateForTest,
+  str2seed,
+  airdrop,
+  sleep,
+} from "./setup";
+import { CIVIC_GATEWAY_PROGRAM, GlamClient, GlamError, WSOL } from "../src";
+
+// Civic gateway tokens of these wallets are loaded from fixtures/accounts/civic
+const civicNetwork = Keypair.fromSeed(str2seed("kyc-network")).publicKey;
+const civicValid = Keypair.fromSeed(str2seed("kyc-valid"));
+const civicExpired = Keypair.fromSeed(str2seed("kyc-expired"));
+const civicRevoked = Keypair.fromSeed(str2seed("kyc-revoked"));
+const civicWrongNetwork = Keypair.fromSeed(str2seed("kyc-wrong-network"));
+const noKyc = Keypair.generate();
+
+describe("glam_kyc", () => {
+  const glamClient = new GlamClient();
+  const connection = glamClient.provider.connection;
+  const clientOf = (keypair: Keypair) =>
+    new GlamClient({ wallet: new Wallet(keypair) });
+
+  const mintModelForTest = {
+    ...stateModelForTest.mints![0],
+    asset: WSOL,
+  };
+  const civicStateModel = {
+    ...stateModelForTest,
+    name: "Glam KYC Civic",
+    assets: [WSOL],
+    mints: [
+      {
+        ...mintModelForTest,
+        kycGatewayProgram: CIVIC_GATEWAY_PROGRAM,
+        kycGatekeeperNetwork: civicNetwork,
+      },
+    ],
+  };
+  // Glam attestations issued by the manager
+  const attestationStateModel = {
+    ...stateModelForTest,
+    name: "Glam KYC Attestation",
+    assets: [WSOL],
+    mints: [
+      {
+        ...mintModelForTest,
+        kycGatewayProgram: glamClient.program.programId,
+        kycGatekeeperNetwork: glamClient.getSigner(),
+      },
+    ],
+  };
+  const civicStatePda = glamClient.getStatePda(civicStateModel);
+  const attestationStatePda = glamClient.getStatePda(attestationStateModel);
+
+  const amount = new BN(1_000_000_000);
+
+  beforeAll(async () => {
+    for (const keypair of [
+      civicValid,
+      civicExpired,
+      civicRevoked,
+      civicWrongNetwork,
+      noKyc,
+    ]) {
+      await airdrop(connection, keypair.publicKey, 10_000_000_000);
+    }
+    await createGlamStateForTest(glamClient, civicStateModel);
+    await createGlamStateForTest(glamClient, attestationStateModel);
+  }, 30_000);
+
+  const expectSubscribeError = async (
+    keypair: Keypair,
+    statePda: PublicKey,
+    message: string,
+  ) => {
+    try {
+      const txSig = await clientOf(keypair).investor.subscribe(
+        statePda,
+        WSOL,
+        amount,
+      );
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(message);
+    }
+  };
+
+  it("[civic] valid gateway token subscribes", async () => {
+    try {
+      await clientOf(civicValid).investor.subscribe(
+        civicStatePda,
+        WSOL,
+        amount,
+      );
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+  });
+
+  it("[civic] missing gateway token", async () => {
+    await expectSubscribeError(
+      noKyc,
+      civicStatePda,
+      "Policy violation: KYC gateway token required.",
+    );
+  });
+
+  it("[civic] expired gateway token", async () => {
+    await expectSubscribeError(
+      civicExpired,
+      civicStatePda,
+      "Policy violation: KYC gateway token expired.",
+    );
+  });
+
+  it("[civic] revoked gateway token", async () => {
+    await expectSubscribeError(
+      civicRevoked,
+      civicStatePda,
+      "Policy violation: invalid KYC gateway token.",
+    );
+  });
+
+  it("[civic] gateway token from the wrong network", async () => {
+    await expectSubscribeError(
+      civicWrongNetwork,
+      civicStatePda,
+      "Policy violation: invalid KYC gateway token.",
+    );
+  });
+
+  it("[attestation] valid, expired and revoked attestations", async () => {
+    const subject = noKyc.publicKey;
+    await expectSubscribeError(
+      noKyc,
+      attestationStatePda,
+      "Policy violation: KYC gateway token required.",
+    );
+
+    try {
+      await glamClient.program.methods
+        .issueAttestation(subject, new BN(0))
+        .accounts({ issuer: glamClient.getSigner() })
+        .rpc();
+      await clientOf(noKyc).investor.subscribe(
+        attestationStatePda,
+        WSOL,
+        amount,
+      );
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    try {
+      const expiresAt = Math.floor(Date.now() / 1000) + 2;
+      await glamClient.program.methods
+        .issueAttestation(subject, new BN(expiresAt))
+        .accounts({ issuer: glamClient.getSigner() })
+        .rpc();
+      await sleep(3_000);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    await expectSubscribeError(
+      noKyc,
+      attestationStatePda,
+      "Policy violation: KYC gateway token expired.",
+    );
+
+    try {
+      await glamClient.program.methods
+        .revokeAttestation()
+        .accounts({
+          attestation: glamClient.getKycTokenPda(
+            subject,
+            glamClient.program.programId,
+            glamClient.getSigner(),
+          ),
+          issuer: glamClient.getSigner(),
+        })
+        .rpc();
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    await expectSubscribeError(
+      noKyc,
+      attestationStatePda,
+      "Policy violation: KYC gateway token required.",
+    );
+  }, 30_000);
+});
@@ -0,0 +1,99 @@
+import { BN } from "@coral-xyz/anchor";
+import { getMint, TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
+
+import { stateModelForTest, createGlamStateForTest } from "./setup";
+import { GlamClient, GlamError, MSOL, USDC, WSOL } from "../src";
+
+describe("glam_launch_price", () => {
+  const glamClient = new GlamClient();
+  const connection = glamClient.provider.connection;
+  const commitment = "confirmed";
+
+  const stateModel = {
+    ...stateModelForTest,
+    name: "Glam Launch Price",
+    assets: [WSOL],
+    mints: [
+      {
+        ...stateModelForTest.mints![0],
+        asset: WSOL,
+        launchPrice: new BN(2_000_000_000), // 2 SOL
+      },
+    ],
+  };
+  const statePda = glamClient.getStatePda(stateModel);
+  const mintPda = glamClient.getMintPda(statePda, 0);
+
+  beforeAll(async () => {
+    try {
+      await createGlamStateForTest(glamClient, stateModel);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+  });
+
+  it("Launch price currency and date are set in openfunds", async () => {
+    const state = await glamClient.fetchState(statePda);
+    const openfunds = state.mints![0].rawOpenfunds!;
+    expect(openfunds.launchPrice).toEqual("2.000000000");
+    expect(openfunds.launchPriceCurrency).toEqual("SOL");
+    expect(openfunds.launchPriceDate).toEqual(
+      new Date().toISOString().split("T")[0],
+    );
+  });
+
+  it("First subscription is priced at the launch price", async () => {
+    try {
+      const txSig = await glamClient.investor.subscribe(
+        statePda,
+        WSOL,
+        new BN(1_000_000_000),
+      );
+      console.log("subscribe:", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    // 1 SOL buys half a share
+    const mint = await getMint(
+      connection,
+      mintPda,
+      commitment,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    expect(mint.supply.toString()).toEqual(
+      (10n ** BigInt(mint.decimals) / 2n).toString(),
+    );
+  });
+
+  it("Base asset can't change once a launch price is set", async () => {
+    try {
+      const txSig = await glamClient.state.updateState(statePda, {
+        assets: [USDC, WSOL],
+      });
+      expect(txSig).toBeUndefined();
+    } catch (e) {
+      expect((e as GlamError).message).toEqual(
+        "Base asset can't change once a share class has a launch price.",
+      );
+    }
+
+    // other assets can still be added
+    try {
+      const txSig = await glamClient.state.updateState(statePda, {
+        assets: [WSOL, MSOL],
+      });
+      console.log("updateState:", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+    const state = await glamClient.fetchState(statePda);
+    expect(state.assets!.map((a) => a.toBase58())).toEqual([
+      WSOL.toBase58(),
+      MSOL.toBase58(),
+    ]);
+  });
+});
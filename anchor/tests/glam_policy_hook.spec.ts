@@ -18,6 +18,7 @@ import {
   getMint,
   getAccount,
   createTransferCheckedWithTransferHookInstruction,
+  createApproveCheckedInstruction,
 } from "@solana/spl-token";
 
 import {
@@ -200,7 +201,8 @@ describe("glam_policy_hook", () => {
       commitment,
       TOKEN_2022_PROGRAM_ID,
     );
-    expect(managerShares.amount).toEqual(shares.supply);
+    // 1000 dead shares are minted to the share class itself
+    expect(managerShares.amount).toEqual(shares.supply - 1000n);
   }, 15_000);
 
   it("Manager redeems + transfers shares to Alice: both fail for lock-up", async () => {
@@ -288,11 +290,169 @@ describe("glam_policy_hook", () => {
       throw err;
     }
 
+    // Alice redeems into native SOL, without a wSOL account
+    const aliceLamportsBefore = await connection.getBalance(alice.publicKey);
     try {
-      const txId = await glamClientAlice.investor.redeem(statePda, amount);
+      const txId = await glamClientAlice.investor.redeem(
+        statePda,
+        amount,
+        false,
+        undefined,
+        0,
+        true,
+        {},
+        [],
+        true,
+      );
+      console.log("alice redeems shares:", txId);
+    } catch (err) {
+      throw err;
+    }
+    const aliceLamportsAfter = await connection.getBalance(alice.publicKey);
+    expect(aliceLamportsAfter - aliceLamportsBefore).toBeGreaterThan(
+      9 * 10 ** 9,
+    );
+  }, 15_000);
+
+  it("Manager approves Alice as delegate, Alice transfers manager shares to herself", async () => {
+    const amount = new BN(5 * 10 ** 9);
+
+    const tx = new Transaction().add(
+      createApproveCheckedInstruction(
+        managerSharesAta,
+        sharePda,
+        alice.publicKey,
+        wallet.publicKey,
+        BigInt(amount.toString()),
+        9,
+        [],
+        TOKEN_2022_PROGRAM_ID,
+      ),
+      await createTransferCheckedWithTransferHookInstruction(
+        connection,
+        managerSharesAta,
+        sharePda,
+        aliceSharesAta,
+        alice.publicKey,
+        BigInt(amount.toString()),
+        9,
+        [],
+        commitment,
+        TOKEN_2022_PROGRAM_ID,
+      ),
+    );
+
+    try {
+      const txId = await sendAndConfirmTransaction(connection, tx, [
+        wallet.payer,
+        alice,
+      ]);
+      console.log("alice transfers shares as delegate:", txId);
+    } catch (err) {
+      throw err;
+    }
+
+    const aliceShares = await getAccount(
+      connection,
+      aliceSharesAta,
+      commitment,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    expect(aliceShares.amount.toString()).toEqual(amount.toString());
+  });
+
+  it("Manager can't subscribe on behalf of Alice without her signature", async () => {
+    const amount = new BN(10 ** 9);
+    // pretend there's no lock-up, so that the client doesn't require alice to sign
+    const stateModelNoLockUp = {
+      ...stateModel,
+      mints: [{ ...mint, lockUpPeriodInSeconds: 0 }],
+    };
+    try {
+      const txId = await glamClient.investor.subscribe(
+        statePda,
+        WSOL,
+        amount,
+        stateModelNoLockUp,
+        0,
+        true,
+        {},
+        alice.publicKey,
+      );
+      expect(txId).toBeUndefined();
+    } catch (err) {
+      expect(err.message).toContain(
+        "Beneficiary must sign subscriptions to share classes with a lock-up",
+      );
+    }
+
+    // alice's lock-up wasn't extended, she can still redeem
+    try {
+      const txId = await glamClientAlice.investor.redeem(
+        statePda,
+        amount,
+        false,
+        undefined,
+        0,
+        true,
+        {},
+        [],
+        true,
+      );
       console.log("alice redeems shares:", txId);
     } catch (err) {
       throw err;
     }
   }, 15_000);
+
+  it("Manager subscribes on behalf of Alice with her signature, Alice is locked up", async () => {
+    const amount = new BN(10 ** 9);
+    const aliceSharesBefore = await getAccount(
+      connection,
+      aliceSharesAta,
+      commitment,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    try {
+      const txId = await glamClient.investor.subscribe(
+        statePda,
+        WSOL,
+        amount,
+        undefined,
+        0,
+        true,
+        {},
+        alice,
+      );
+      console.log("manager subscribes for alice:", txId);
+    } catch (err) {
+      console.error(err);
+      throw err;
+    }
+
+    const aliceSharesAfter = await getAccount(
+      connection,
+      aliceSharesAta,
+      commitment,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    expect(aliceSharesAfter.amount).toBeGreaterThan(aliceSharesBefore.amount);
+
+    try {
+      const txId = await glamClientAlice.investor.redeem(
+        statePda,
+        amount,
+        false,
+        undefined,
+        0,
+        true,
+        {},
+        [],
+        true,
+      );
+      expect(txId).toBeUndefined();
+    } catch (err) {
+      expect(err.message).toContain("Policy violation: lock-up period");
+    }
+  }, 15_000);
 });
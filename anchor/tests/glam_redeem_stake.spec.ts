@@ -0,0 +1,223 @@
+import { BN } from "@coral-xyz/anchor";
+import { PublicKey, StakeProgram } from "@solana/web3.js";
+import {
+  getAccount,
+  getMint,
+  getOrCreateAssociatedTokenAccount,
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+} from "@solana/spl-token";
+
+import { stateModelForTest, createGlamStateForTest, airdrop } from "./setup";
+import { GlamClient, WSOL } from "../src";
+
+describe("glam_redeem_stake", () => {
+  const glamClient = new GlamClient();
+  const connection = glamClient.provider.connection;
+  const manager = glamClient.getSigner();
+  const commitment = "confirmed";
+
+  const stateModel = {
+    ...stateModelForTest,
+    name: "Glam Redeem Stake",
+    assets: [WSOL],
+    mints: [{ ...stateModelForTest.mints![0], asset: WSOL }],
+  };
+  const statePda = glamClient.getStatePda(stateModel);
+  const vaultPda = glamClient.getVaultPda(statePda);
+  const mintPda = glamClient.getMintPda(statePda, 0);
+  const vaultWsolAta = glamClient.getVaultAta(statePda, WSOL);
+  const managerWsolAta = glamClient.getAta(WSOL, manager);
+  const managerSharesAta = glamClient.getMintAta(manager, mintPda);
+
+  let vaultStakeAccount: PublicKey;
+
+  const fetchAmounts = async () => ({
+    supply: new BN(
+      (
+        await getMint(connection, mintPda, commitment, TOKEN_2022_PROGRAM_ID)
+      ).supply.toString(),
+    ),
+    shares: new BN(
+      (
+        await getAccount(
+          connection,
+          managerSharesAta,
+          commitment,
+          TOKEN_2022_PROGRAM_ID,
+        )
+      ).amount.toString(),
+    ),
+    vaultWsol: new BN(
+      (
+        await getAccount(connection, vaultWsolAta, commitment, TOKEN_PROGRAM_ID)
+      ).amount.toString(),
+    ),
+    managerWsol: new BN(
+      (
+        await getAccount(
+          connection,
+          managerWsolAta,
+          commitment,
+          TOKEN_PROGRAM_ID,
+        )
+      ).amount.toString(),
+    ),
+    stakeLamports: new BN(await connection.getBalance(vaultStakeAccount)),
+  });
+
+  const managerStakeAccounts = async () =>
+    (await glamClient.staking.getStakeAccounts(manager)).map((a) =>
+      a.toBase58(),
+    );
+
+  beforeAll(async () => {
+    try {
+      await createGlamStateForTest(glamClient, stateModel);
+      await glamClient.state.updateState(statePda, {
+        integrations: [{ nativeStaking: {} }],
+      });
+
+      // 10 wSOL in the vault, then 20 SOL staked
+      await glamClient.investor.subscribe(statePda, WSOL, new BN(10 ** 10));
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        glamClient.getWallet().payer,
+        WSOL,
+        manager,
+      );
+      await airdrop(connection, vaultPda, 25_000_000_000);
+
+      const voteAccountStatus = await connection.getVoteAccounts();
+      const vote = voteAccountStatus.current.sort(
+        (a, b) => b.activatedStake - a.activatedStake,
+      )[0].votePubkey;
+      await glamClient.staking.initializeAndDelegateStake(
+        statePda,
+        new PublicKey(vote),
+        new BN(20_000_000_000),
+      );
+      [vaultStakeAccount] = await glamClient.staking.getStakeAccounts(vaultPda);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+  }, 30_000);
+
+  it("Activating stake is valued at its lamports", async () => {
+    // manager subscribes again: the AUM includes the activating stake account
+    const before = await fetchAmounts();
+    const amount = new BN(10 ** 9);
+    try {
+      const txSig = await glamClient.investor.subscribe(statePda, WSOL, amount);
+      console.log("subscribe:", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    // shares minted = amount * supply / (vault wSOL + stake lamports)
+    // (lamports of the vault itself aren't part of the AUM)
+    const after = await fetchAmounts();
+    const aum = before.vaultWsol.add(before.stakeLamports);
+    const expectedShares = amount.mul(before.supply).div(aum);
+    expect(
+      after.supply.sub(before.supply).sub(expectedShares).abs().toNumber(),
+    ).toBeLessThanOrEqual(1);
+  });
+
+  it("Partial in-kind redemption splits the stake pro rata", async () => {
+    const before = await fetchAmounts();
+    const stakeAccountsBefore = await managerStakeAccounts();
+
+    // 10% of the shares
+    const amount = before.supply.div(new BN(10));
+    try {
+      const txSig = await glamClient.investor.redeem(statePda, amount, true);
+      console.log("redeem in kind:", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    const after = await fetchAmounts();
+    const expectedStake = before.stakeLamports.mul(amount).div(before.supply);
+    expect(before.stakeLamports.sub(after.stakeLamports).toString()).toEqual(
+      expectedStake.toString(),
+    );
+    expect(after.managerWsol.sub(before.managerWsol).toString()).toEqual(
+      before.vaultWsol.mul(amount).div(before.supply).toString(),
+    );
+
+    const newStakeAccounts = (await managerStakeAccounts()).filter(
+      (a) => !stakeAccountsBefore.includes(a),
+    );
+    expect(newStakeAccounts.length).toEqual(1);
+    const rent = await connection.getMinimumBalanceForRentExemption(
+      StakeProgram.space,
+    );
+    expect(
+      await connection.getBalance(new PublicKey(newStakeAccounts[0])),
+    ).toEqual(expectedStake.toNumber() + rent);
+  });
+
+  it("Dust stake split is paid in wSOL instead", async () => {
+    const before = await fetchAmounts();
+    const stakeAccountsBefore = await managerStakeAccounts();
+
+    // pro rata share of the stake is ~0.002 SOL, below the minimum delegation of 1 SOL
+    const amount = new BN(1_000_000);
+    try {
+      const txSig = await glamClient.investor.redeem(statePda, amount, true);
+      console.log("redeem in kind:", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    const after = await fetchAmounts();
+    expect(after.stakeLamports.toString()).toEqual(
+      before.stakeLamports.toString(),
+    );
+    expect(after.managerWsol.sub(before.managerWsol).toString()).toEqual(
+      before.vaultWsol
+        .mul(amount)
+        .div(before.supply)
+        .add(before.stakeLamports.mul(amount).div(before.supply))
+        .toString(),
+    );
+
+    // the new stake account is handed over empty, its rent can be reclaimed
+    const newStakeAccounts = (await managerStakeAccounts()).filter(
+      (a) => !stakeAccountsBefore.includes(a),
+    );
+    expect(newStakeAccounts.length).toEqual(1);
+    const rent = await connection.getMinimumBalanceForRentExemption(
+      StakeProgram.space,
+    );
+    expect(
+      await connection.getBalance(new PublicKey(newStakeAccounts[0])),
+    ).toEqual(rent);
+  });
+
+  it("Full redemption hands over the vault stake accounts", async () => {
+    const { shares } = await fetchAmounts();
+    try {
+      const txSig = await glamClient.investor.redeem(statePda, shares, true);
+      console.log("redeem everything:", txSig);
+    } catch (e) {
+      console.error(e);
+      throw e;
+    }
+
+    expect(await managerStakeAccounts()).toContain(
+      vaultStakeAccount.toBase58(),
+    );
+    expect(await glamClient.staking.getStakeAccounts(vaultPda)).toEqual([]);
+
+    const state = await glamClient.fetchState(statePda);
+    expect(
+      (state.externalVaultAccounts || []).map((a) => a.toBase58()),
+    ).not.toContain(vaultStakeAccount.toBase58());
+  });
+});
@@ -70,6 +70,13 @@ export const metadata: { [key: string]: IntegrationMetadata } = {
     labels: ["LP"],
     imagePath: "/images/integrations/meteora.svg",
   },
+  ExecuteCpi: {
+    name: "CPI Policies",
+    description:
+      "Let delegates call owner-approved instructions of other programs, signed by the vault.",
+    labels: ["Programs"],
+    imagePath: "/images/integrations/solana.svg",
+  },
 } satisfies { [key: string]: IntegrationMetadata };
 
 export const allIntegrations = GlamIntegrations.sort().map((integ, index) => ({